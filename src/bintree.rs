@@ -1,6 +1,8 @@
 pub mod bintree {
 
+    use std::cmp::Ordering;
     use std::fmt::Debug;
+    use std::ops::{Bound, RangeBounds};
 
     pub enum RotationDirection {
         Left,
@@ -8,36 +10,50 @@ pub mod bintree {
         NoRotation,
     }
 
+    /// An ordered map kept balanced as an AVL tree, the heights of the two subtrees of every
+    /// node differ by at most one. Lookups, insertions and removals are logarithmic in the
+    /// number of entries. Each node also counts the entries below it, so the rank of a key
+    /// is logarithmic as well.
     ///
-    /// ```use super::*;
-    /// use bintree::*;
-    /// #[test]
-    /// fn test_basic_sort() {
-    ///     let mut t = BinTree::new();
-    ///     t.add_sorted(4);
-    ///     t.add_sorted(5);
-    ///     t.add_sorted(6);
-    ///     t.add_sorted(10);
-    ///     t.add_sorted(1);
-    ///     t.add_sorted(94);
-    ///     t.add_sorted(54);
-    ///     t.add_sorted(3);
-    ///     assert_eq!(t.max_value(), Some(94));
-    /// }
-    /// '''
-
+    /// A tree of keys alone, `BinTree<K>`, filled with [`BinTree::add_sorted`] is a sorted
+    /// multiset, equal keys are all kept:
+    ///
+    /// ```ignore
+    /// let mut t = BinTree::new();
+    /// t.add_sorted(4);
+    /// t.add_sorted(94);
+    /// t.add_sorted(3);
+    /// t.add_sorted(3);
+    /// assert_eq!(t.len(), 4);
+    /// assert_eq!(t.max_value(), Some(94));
+    /// ```
+    ///
+    /// Keyed by date it indexes cashflows or fixings without sorting a `Vec` for every
+    /// lookup: [`BinTree::floor`] finds the last fixing on or before a date and
+    /// [`BinTree::range`] the cashflows between two dates.
     #[derive(Debug)]
-    pub struct BinTree<T>(Option<Box<BinData<T>>>);
+    pub struct BinTree<K, V = ()>(Option<Box<BinData<K, V>>>);
 
     #[derive(Debug)]
-    pub struct BinData<T> {
-        data: T,
-        h: i8, // Could be boolean for Red Black.
-        left: BinTree<T>,
-        right: BinTree<T>,
+    pub struct BinData<K, V = ()> {
+        key: K,
+        value: V,
+        h: i8,
+        size: usize,
+        left: BinTree<K, V>,
+        right: BinTree<K, V>,
     }
 
-    impl<T> BinData<T> {
+    impl<K, V> BinData<K, V> {
+        fn update(&mut self) {
+            self.h = 1 + std::cmp::max(self.left.height(), self.right.height());
+            self.size = 1 + self.left.len() + self.right.len();
+        }
+
+        fn balance(&self) -> i8 {
+            self.left.height() - self.right.height()
+        }
+
         pub fn rot_left(mut self) -> Box<Self> {
             let mut res = match self.right.0 {
                 Some(res) => res,
@@ -47,7 +63,7 @@ pub mod bintree {
             self.right.set_height();
             res.left = BinTree(Some(Box::new(self)));
             res.left.set_height();
-            res.h = 1 + std::cmp::max(res.left.height(), res.right.height());
+            res.update();
             res
         }
 
@@ -60,35 +76,45 @@ pub mod bintree {
             self.left.set_height();
             res.right = BinTree(Some(Box::new(self)));
             res.right.set_height();
-            res.h = 1 + std::cmp::max(res.left.height(), res.right.height());
+            res.update();
             res
         }
+    }
 
-        pub fn max_value(self) -> Option<T> {
-            match self.right.0 {
-                Some(x) => {
-                    return x.max_value();
-                }
-                None => {
-                    return Some(self.data);
-                }
-            }
+    impl<K, V> Default for BinTree<K, V> {
+        fn default() -> Self {
+            BinTree::new()
         }
     }
 
-    impl<T> BinTree<T> {
+    impl<K, V> BinTree<K, V> {
         pub fn new() -> Self {
             BinTree(None)
         }
+
         pub fn height(&self) -> i8 {
             match self.0 {
                 Some(ref t) => t.h,
                 None => 0,
             }
         }
+
+        /// The number of entries.
+        pub fn len(&self) -> usize {
+            match self.0 {
+                Some(ref t) => t.size,
+                None => 0,
+            }
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.0.is_none()
+        }
+
+        /// Recomputes the height and the size of the root from its subtrees.
         pub fn set_height(&mut self) {
             if let Some(ref mut t) = self.0 {
-                t.h = 1 + std::cmp::max(t.left.height(), t.right.height());
+                t.update();
             }
         }
 
@@ -100,53 +126,396 @@ pub mod bintree {
             self.0 = self.0.take().map(|v| v.rot_right());
         }
 
-        pub fn max_value(&mut self) -> Option<T> {
-            match self.0.take() {
-                Some(v) => v.max_value(),
-                None => None,
+        /// Restores the balance of the root after one of its subtrees changed height by one,
+        /// with a double rotation when the taller grandchild is on the inside.
+        fn rebalance(&mut self) {
+            let rot_dir = match self.0 {
+                Some(ref mut bd) => {
+                    bd.update();
+                    let balance = bd.balance();
+                    if balance > 1 {
+                        if bd.left.0.as_ref().map_or(0, |l| l.balance()) < 0 {
+                            bd.left.rot_left();
+                        }
+                        RotationDirection::Left
+                    } else if balance < -1 {
+                        if bd.right.0.as_ref().map_or(0, |r| r.balance()) > 0 {
+                            bd.right.rot_right();
+                        }
+                        RotationDirection::Right
+                    } else {
+                        RotationDirection::NoRotation
+                    }
+                }
+                None => RotationDirection::NoRotation,
+            };
+            match rot_dir {
+                RotationDirection::Left => self.rot_right(),
+                RotationDirection::Right => self.rot_left(),
+                RotationDirection::NoRotation => {}
+            }
+        }
+
+        /// The entry with the smallest key.
+        pub fn min(&self) -> Option<(&K, &V)> {
+            let mut node = self.0.as_ref()?;
+            while let Some(ref left) = node.left.0 {
+                node = left;
             }
+            Some((&node.key, &node.value))
+        }
+
+        /// The entry with the largest key.
+        pub fn max(&self) -> Option<(&K, &V)> {
+            let mut node = self.0.as_ref()?;
+            while let Some(ref right) = node.right.0 {
+                node = right;
+            }
+            Some((&node.key, &node.value))
+        }
+
+        /// The largest key, see [`BinTree::max`].
+        pub fn max_value(&self) -> Option<K>
+        where
+            K: Clone,
+        {
+            self.max().map(|(key, _)| key.clone())
+        }
+
+        fn remove_min(&mut self) -> Option<(K, V)> {
+            let mut node = self.0.take()?;
+            if node.left.is_empty() {
+                let BinData {
+                    key, value, right, ..
+                } = *node;
+                *self = right;
+                return Some((key, value));
+            }
+            let min = node.left.remove_min();
+            self.0 = Some(node);
+            self.rebalance();
+            min
+        }
+
+        /// The entries in key order.
+        pub fn iter(&self) -> Iter<'_, K, V> {
+            let mut iter = Iter { stack: Vec::new() };
+            iter.push_left(self);
+            iter
+        }
+
+        pub fn keys(&self) -> impl Iterator<Item = &K> {
+            self.iter().map(|(key, _)| key)
+        }
+
+        pub fn values(&self) -> impl Iterator<Item = &V> {
+            self.iter().map(|(_, value)| value)
         }
     }
 
-    impl<T: PartialOrd> BinTree<T> {
-        pub fn add_sorted(&mut self, data: T) {
-            let rot_dir = match self.0 {
+    impl<K: Ord, V> BinTree<K, V> {
+        /// Inserts `value` at `key`, returning the value it replaces.
+        pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+            let replaced = match self.0 {
+                Some(ref mut bd) => match key.cmp(&bd.key) {
+                    Ordering::Less => bd.left.insert(key, value),
+                    Ordering::Greater => bd.right.insert(key, value),
+                    Ordering::Equal => return Some(std::mem::replace(&mut bd.value, value)),
+                },
+                None => {
+                    self.0 = Some(Box::new(BinData {
+                        key,
+                        value,
+                        h: 1,
+                        size: 1,
+                        left: BinTree::new(),
+                        right: BinTree::new(),
+                    }));
+                    return None;
+                }
+            };
+            self.rebalance();
+            replaced
+        }
+
+        fn node(&self, key: &K) -> Option<&BinData<K, V>> {
+            let mut current = self.0.as_ref();
+            while let Some(node) = current {
+                current = match key.cmp(&node.key) {
+                    Ordering::Less => node.left.0.as_ref(),
+                    Ordering::Greater => node.right.0.as_ref(),
+                    Ordering::Equal => return Some(node),
+                };
+            }
+            None
+        }
+
+        pub fn get(&self, key: &K) -> Option<&V> {
+            self.node(key).map(|node| &node.value)
+        }
+
+        pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+            let mut current = self.0.as_mut();
+            while let Some(node) = current {
+                current = match key.cmp(&node.key) {
+                    Ordering::Less => node.left.0.as_mut(),
+                    Ordering::Greater => node.right.0.as_mut(),
+                    Ordering::Equal => return Some(&mut node.value),
+                };
+            }
+            None
+        }
+
+        pub fn contains_key(&self, key: &K) -> bool {
+            self.node(key).is_some()
+        }
+
+        /// Removes `key`, returning its value. A node with two subtrees is replaced by the
+        /// smallest entry of its right subtree.
+        pub fn remove(&mut self, key: &K) -> Option<V> {
+            let mut node = self.0.take()?;
+            let removed = match key.cmp(&node.key) {
+                Ordering::Less => node.left.remove(key),
+                Ordering::Greater => node.right.remove(key),
+                Ordering::Equal => {
+                    let BinData {
+                        value,
+                        left,
+                        mut right,
+                        ..
+                    } = *node;
+                    match right.remove_min() {
+                        Some((min_key, min_value)) => {
+                            node = Box::new(BinData {
+                                key: min_key,
+                                value: min_value,
+                                h: 0,
+                                size: 0,
+                                left,
+                                right,
+                            });
+                        }
+                        None => {
+                            *self = left;
+                            return Some(value);
+                        }
+                    }
+                    Some(value)
+                }
+            };
+            self.0 = Some(node);
+            self.rebalance();
+            removed
+        }
+
+        /// The entry with the largest key at or below `key`.
+        pub fn floor(&self, key: &K) -> Option<(&K, &V)> {
+            let mut current = self.0.as_ref();
+            let mut result = None;
+            while let Some(node) = current {
+                match key.cmp(&node.key) {
+                    Ordering::Less => current = node.left.0.as_ref(),
+                    Ordering::Greater => {
+                        result = Some((&node.key, &node.value));
+                        current = node.right.0.as_ref();
+                    }
+                    Ordering::Equal => return Some((&node.key, &node.value)),
+                }
+            }
+            result
+        }
+
+        /// The entry with the smallest key at or above `key`.
+        pub fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+            let mut current = self.0.as_ref();
+            let mut result = None;
+            while let Some(node) = current {
+                match key.cmp(&node.key) {
+                    Ordering::Less => {
+                        result = Some((&node.key, &node.value));
+                        current = node.left.0.as_ref();
+                    }
+                    Ordering::Greater => current = node.right.0.as_ref(),
+                    Ordering::Equal => return Some((&node.key, &node.value)),
+                }
+            }
+            result
+        }
+
+        /// The number of keys below `key`, its index in key order when it is present.
+        pub fn rank(&self, key: &K) -> usize {
+            let mut current = self.0.as_ref();
+            let mut rank = 0;
+            while let Some(node) = current {
+                if *key <= node.key {
+                    current = node.left.0.as_ref();
+                } else {
+                    rank += node.left.len() + 1;
+                    current = node.right.0.as_ref();
+                }
+            }
+            rank
+        }
+
+        /// The entries with keys in `range`, in key order.
+        pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+            let mut iter = Iter { stack: Vec::new() };
+            let mut current = self.0.as_ref();
+            while let Some(node) = current {
+                let above_start = match range.start_bound() {
+                    Bound::Included(start) => node.key >= *start,
+                    Bound::Excluded(start) => node.key > *start,
+                    Bound::Unbounded => true,
+                };
+                if above_start {
+                    iter.stack.push(node);
+                    current = node.left.0.as_ref();
+                } else {
+                    current = node.right.0.as_ref();
+                }
+            }
+            Range { iter, range }
+        }
+
+        /// Adds `data` to a tree used as a sorted multiset. Unlike [`BinTree::insert`] a key
+        /// already present is added again, after the keys equal to it; lookups and removals
+        /// then find one of the equal entries.
+        pub fn add_sorted(&mut self, data: K)
+        where
+            V: Default,
+        {
+            match self.0 {
                 Some(ref mut bd) => {
-                    if data < bd.data {
+                    if data < bd.key {
                         bd.left.add_sorted(data);
-                        if bd.left.height() - bd.right.height() > 1 {
-                            RotationDirection::Left
-                        } else {
-                            RotationDirection::NoRotation
-                        }
                     } else {
                         bd.right.add_sorted(data);
-                        if bd.right.height() - bd.left.height() > 1 {
-                            RotationDirection::Right
-                        } else {
-                            RotationDirection::NoRotation
-                        }
                     }
                 }
                 None => {
                     self.0 = Some(Box::new(BinData {
-                        data,
-                        h: 0,
+                        key: data,
+                        value: V::default(),
+                        h: 1,
+                        size: 1,
                         left: BinTree::new(),
                         right: BinTree::new(),
                     }));
-                    RotationDirection::NoRotation
+                    return;
                 }
+            }
+            self.rebalance();
+        }
+    }
+
+    /// The entries of a [`BinTree`] in key order, see [`BinTree::iter`].
+    pub struct Iter<'a, K, V> {
+        stack: Vec<&'a BinData<K, V>>,
+    }
+
+    impl<'a, K, V> Iter<'a, K, V> {
+        fn push_left(&mut self, tree: &'a BinTree<K, V>) {
+            let mut current = tree.0.as_ref();
+            while let Some(node) = current {
+                self.stack.push(node);
+                current = node.left.0.as_ref();
+            }
+        }
+    }
+
+    impl<'a, K, V> Iterator for Iter<'a, K, V> {
+        type Item = (&'a K, &'a V);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let node = self.stack.pop()?;
+            self.push_left(&node.right);
+            Some((&node.key, &node.value))
+        }
+    }
+
+    /// The entries of a [`BinTree`] with keys in a range, see [`BinTree::range`].
+    pub struct Range<'a, K, V, R> {
+        iter: Iter<'a, K, V>,
+        range: R,
+    }
+
+    impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+        type Item = (&'a K, &'a V);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let (key, value) = self.iter.next()?;
+            let below_end = match self.range.end_bound() {
+                Bound::Included(end) => key <= end,
+                Bound::Excluded(end) => key < end,
+                Bound::Unbounded => true,
             };
-            match rot_dir {
-                RotationDirection::Left => self.rot_right(),
-                RotationDirection::Right => self.rot_left(),
-                RotationDirection::NoRotation => self.set_height(),
+            if below_end {
+                Some((key, value))
+            } else {
+                self.iter.stack.clear();
+                None
+            }
+        }
+    }
+
+    /// The entries of a [`BinTree`] in key order, consuming the tree.
+    pub struct IntoIter<K, V> {
+        stack: Vec<Box<BinData<K, V>>>,
+    }
+
+    impl<K, V> IntoIter<K, V> {
+        fn push_left(&mut self, mut tree: BinTree<K, V>) {
+            while let Some(mut node) = tree.0.take() {
+                tree = BinTree(node.left.0.take());
+                self.stack.push(node);
+            }
+        }
+    }
+
+    impl<K, V> Iterator for IntoIter<K, V> {
+        type Item = (K, V);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let node = self.stack.pop()?;
+            let BinData {
+                key, value, right, ..
+            } = *node;
+            self.push_left(right);
+            Some((key, value))
+        }
+    }
+
+    impl<K, V> IntoIterator for BinTree<K, V> {
+        type Item = (K, V);
+        type IntoIter = IntoIter<K, V>;
+
+        fn into_iter(self) -> IntoIter<K, V> {
+            let mut iter = IntoIter { stack: Vec::new() };
+            iter.push_left(self);
+            iter
+        }
+    }
+
+    impl<'a, K, V> IntoIterator for &'a BinTree<K, V> {
+        type Item = (&'a K, &'a V);
+        type IntoIter = Iter<'a, K, V>;
+
+        fn into_iter(self) -> Iter<'a, K, V> {
+            self.iter()
+        }
+    }
+
+    impl<K: Ord, V> FromIterator<(K, V)> for BinTree<K, V> {
+        fn from_iter<I: IntoIterator<Item = (K, V)>>(entries: I) -> Self {
+            let mut tree = BinTree::new();
+            for (key, value) in entries {
+                tree.insert(key, value);
             }
+            tree
         }
     }
 
-    impl<T: Debug> BinTree<T> {
+    impl<K: Debug, V> BinTree<K, V> {
         pub fn print_lfirst(&self, dp: i32) {
             if let Some(ref bd) = self.0 {
                 bd.left.print_lfirst(dp + 1);
@@ -154,7 +523,7 @@ pub mod bintree {
                 for _ in 0..dp {
                     spc.push('.');
                 }
-                println!("{} : {}{:?}", bd.h, spc, bd.data);
+                println!("{} : {}{:?}", bd.h, spc, bd.key);
                 bd.right.print_lfirst(dp + 1);
             }
         }
@@ -165,6 +534,9 @@ pub mod bintree {
 mod tests {
     use super::*;
     use bintree::*;
+    use chrono::NaiveDate;
+    use std::ops::Bound;
+
     #[test]
     fn test_basic_sort() {
         let mut t = BinTree::new();
@@ -177,5 +549,100 @@ mod tests {
         t.add_sorted(54);
         t.add_sorted(3);
         assert_eq!(t.max_value(), Some(94));
+        assert_eq!(t.max(), Some((&94, &())));
+        // Reading the maximum leaves the tree intact.
+        assert_eq!(t.min(), Some((&1, &())));
+        assert_eq!(t.len(), 8);
+        let sorted: Vec<i32> = t.into_iter().map(|(key, _)| key).collect();
+        assert_eq!(sorted, vec![1, 3, 4, 5, 6, 10, 54, 94]);
+    }
+
+    #[test]
+    fn test_add_sorted_keeps_duplicates() {
+        let mut t = BinTree::new();
+        for key in [5, 3, 5, 8, 3, 5, 1, 5] {
+            t.add_sorted(key);
+        }
+        assert_eq!(t.len(), 8);
+        assert!(t.height() <= 4, "{}", t.height());
+        let sorted: Vec<i32> = t.keys().copied().collect();
+        assert_eq!(sorted, vec![1, 3, 3, 5, 5, 5, 5, 8]);
+        assert_eq!(t.rank(&5), 3);
+        assert_eq!(t.rank(&8), 7);
+        // A removal takes out one of the equal keys.
+        assert_eq!(t.remove(&5), Some(()));
+        assert_eq!(t.len(), 7);
+        assert_eq!(t.keys().filter(|key| **key == 5).count(), 3);
+        // Insert keeps the map semantics and replaces a value at an existing key.
+        let mut set: BinTree<i32> = BinTree::new();
+        set.insert(5, ());
+        assert_eq!(set.insert(5, ()), Some(()));
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_ordered_map() {
+        // Keys inserted in order would degenerate into a list without rebalancing.
+        let mut t: BinTree<i32, i32> = (0..1000).map(|i| (i * 2, i)).collect();
+        assert_eq!(t.len(), 1000);
+        assert!(t.height() <= 15, "{}", t.height());
+        assert_eq!(t.get(&10), Some(&5));
+        assert_eq!(t.get(&11), None);
+        assert_eq!(t.insert(10, -5), Some(5));
+        *t.get_mut(&10).unwrap() = 5;
+        assert_eq!(t.floor(&11), Some((&10, &5)));
+        assert_eq!(t.ceiling(&11), Some((&12, &6)));
+        assert_eq!(t.floor(&-1), None);
+        assert_eq!(t.ceiling(&1999), None);
+        assert_eq!(t.rank(&10), 5);
+        assert_eq!(t.rank(&11), 6);
+        let range: Vec<i32> = t.range(10..16).map(|(key, _)| *key).collect();
+        assert_eq!(range, vec![10, 12, 14]);
+        let range: Vec<i32> = t
+            .range((Bound::Excluded(1990), Bound::Unbounded))
+            .map(|(k, _)| *k)
+            .collect();
+        assert_eq!(range, vec![1992, 1994, 1996, 1998]);
+
+        for i in (0..1000).filter(|i| i % 3 != 0) {
+            assert_eq!(t.remove(&(i * 2)), Some(i));
+        }
+        assert_eq!(t.remove(&2), None);
+        assert_eq!(t.len(), 334);
+        assert!(t.height() <= 11, "{}", t.height());
+        let keys: Vec<i32> = t.keys().copied().collect();
+        assert_eq!(
+            keys,
+            (0..1000)
+                .filter(|i| i % 3 == 0)
+                .map(|i| i * 2)
+                .collect::<Vec<i32>>()
+        );
+        assert_eq!(t.rank(&600), 100);
+        assert_eq!((&t).into_iter().count(), t.len());
+    }
+
+    #[test]
+    fn test_fixings_by_date() {
+        let date = |input: &str| NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap();
+        let fixings: BinTree<NaiveDate, f64> = [
+            ("01/02/2024", 5.40),
+            ("01/03/2024", 5.38),
+            ("01/05/2024", 5.35),
+            ("01/08/2024", 5.33),
+        ]
+        .iter()
+        .map(|(d, rate)| (date(d), *rate))
+        .collect();
+        // The fixing for a holiday is the last one published before it.
+        assert_eq!(
+            fixings.floor(&date("01/06/2024")),
+            Some((&date("01/05/2024"), &5.35))
+        );
+        let week: Vec<f64> = fixings
+            .range(date("01/03/2024")..=date("01/05/2024"))
+            .map(|(_, rate)| *rate)
+            .collect();
+        assert_eq!(week, vec![5.38, 5.35]);
     }
 }
@@ -1,11 +1,16 @@
 //! This module assumes that the bonds are option-free, therefore are non-callable or non-putable.
 pub mod bond {
-    use std::boxed::Box;
-    use chrono::{Datelike, NaiveDate, Months, ParseError};
+    use crate::calendar::calendar::{BusinessDayConvention, Calendar};
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::interpolation::interpolation::Interpolation;
+    use crate::schedule::schedule::{Schedule, SchedulePeriod, StubType};
+    use crate::solver::solver::{brent, solve, SolverConfig, SolverError};
+    use chrono::{Datelike, NaiveDate, ParseError};
+    use filters::filter::Filter;
     use log::debug;
     use serde::{Deserialize, Serialize};
+    use std::boxed::Box;
     use std::cmp::Ordering;
-    use filters::filter::Filter;
     use std::cmp::{Eq, Ord, PartialEq, PartialOrd};
 
     /// Most products support annual, quarterly and semiannual payments.
@@ -17,25 +22,40 @@ pub mod bond {
         Annual,
     }
 
-    /// Evaluate the sign of a given function.
-    #[derive(PartialEq)]
-    enum Sign {
-        POSITIVE,
-        NEGATIVE,
-    }
-
-    fn sign(test: f32) -> Sign {
-        if test < 0.0 {
-            Sign::NEGATIVE
-        } else {
-            Sign::POSITIVE
+    impl Periodicity {
+        pub fn periods_per_year(&self) -> f64 {
+            match self {
+                Periodicity::Quarterly => 4.0,
+                Periodicity::SemiAnnual => 2.0,
+                Periodicity::Annual => 1.0,
+            }
         }
     }
 
     #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
     pub struct DiscountFactor {
-        pub term: f32,
-        pub discount: f32,
+        pub term: f64,
+        pub discount: f64,
+    }
+
+    /// A discount curve returns the value today of one unit of currency paid
+    /// after `term` years.
+    pub trait DiscountCurve {
+        fn discount(&self, term: f64) -> f64;
+    }
+
+    /// Discount factors sorted by term are interpolated log-linearly, which holds the
+    /// forward rate constant between terms. Beyond the last term the zero rate is held flat.
+    impl DiscountCurve for [DiscountFactor] {
+        fn discount(&self, term: f64) -> f64 {
+            Interpolation::LogLinearDiscount.discount(self, term)
+        }
+    }
+
+    impl DiscountCurve for Vec<DiscountFactor> {
+        fn discount(&self, term: f64) -> f64 {
+            self.as_slice().discount(term)
+        }
     }
 
     /// Market data is assumed to be for the
@@ -104,21 +124,21 @@ pub mod bond {
     ///     let market_data: Vec<MarketData> = create_test_market_data();
     ///     let discount_factor: Vec<DiscountFactor> =
     ///         discount_factor(&market_data, Periodicity::SemiAnnual);
-    ///     assert_approx_eq!(discount_factor[0].discount, 0.9999231, f32::EPSILON);
-    ///     assert_approx_eq!(discount_factor[1].discount, 0.99941903, f32::EPSILON);
-    ///     assert_approx_eq!(discount_factor[2].discount, 0.9985045, f32::EPSILON);
-    ///     assert_approx_eq!(discount_factor[3].discount, 0.99704117, f32::EPSILON);
-    ///     assert_approx_eq!(discount_factor[4].discount, 0.9945582, f32::EPSILON);
-    ///     assert_approx_eq!(discount_factor[5].discount, 0.99019545, f32::EPSILON);
-    ///     assert_approx_eq!(discount_factor[6].discount, 0.9847417, f32::EPSILON);
+    ///     assert_approx_eq!(discount_factor[0].discount, 0.9999231054, 1e-9);
+    ///     assert_approx_eq!(discount_factor[1].discount, 0.9994189902, 1e-9);
+    ///     assert_approx_eq!(discount_factor[2].discount, 0.9985044964, 1e-9);
+    ///     assert_approx_eq!(discount_factor[3].discount, 0.9970411951, 1e-9);
+    ///     assert_approx_eq!(discount_factor[4].discount, 0.9945581925, 1e-9);
+    ///     assert_approx_eq!(discount_factor[5].discount, 0.9901954482, 1e-9);
+    ///     assert_approx_eq!(discount_factor[6].discount, 0.9847416899, 1e-9);
     /// }
     ///```
 
     #[derive(Debug, Clone, Copy)]
     pub struct MarketData {
-        pub coupon_rate: f32,
-        pub term: f32,
-        pub market_price: f32,
+        pub coupon_rate: f64,
+        pub term: f64,
+        pub market_price: f64,
     }
 
     /// The one-factor metrics for a Bond are:
@@ -145,17 +165,22 @@ pub mod bond {
     ///
     ///     Yield based duration is a modified version of a Duration and is expressed
     ///
-    ///     . D_c0 = T / (100.0 * (f32::pow(1 + y/2), 2T + 1.0))
-    ///     . D_c100y = 1.0/y * (1.0  - 1.0 / (f32::pow(1 + y/2.0, 2 * T)))
+    ///     . D_c0 = T / (100.0 * (f64::pow(1 + y/2), 2T + 1.0))
+    ///     . D_c100y = 1.0/y * (1.0  - 1.0 / (f64::pow(1 + y/2.0, 2 * T)))
 
     ///     . The duration of a bond approximately equals its term.
     ///     . The duration of a par bond increases with term but increases less linearly with term.
     ///     . The duration of a premium bond is less than the duration of a par bond.
+    ///
+    /// `macaulay_duration` is the present value weighted time to the cashflows in years and
+    /// `modified_duration` is the percentage change in price for a change in yield. The `dv01`
+    /// is quoted for the principal of the bond.
     #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
     pub struct BondMetrics {
-        pub dv01: f32,
-        pub convexity: f32,
-        pub duration: f32,
+        pub dv01: f64,
+        pub convexity: f64,
+        pub macaulay_duration: f64,
+        pub modified_duration: f64,
     }
 
     #[derive(Debug, Clone, Copy)]
@@ -229,7 +254,8 @@ pub mod bond {
     ///   * If the *coupon rate* < *yield to maturity* the bond is priced at a discount.
     ///   * If the *coupon rate* > *yield to maturity* the bond is priced at a premium.
     /// These above rules apply only on the coupon dates and the rest of the dates need to account for
-    /// accrued interest on the bond.
+    /// accrued interest on the bond. Between coupon dates, see [`Bond::accrued_interest`],
+    /// [`Bond::dirty_price`] and [`Bond::clean_price`]; bonds are quoted clean and settle dirty.
 
     /// #### The Yield-to-maturity (ytm)
     /// Yield-to-maturity measures the investors rate of return only if the coupons
@@ -241,20 +267,57 @@ pub mod bond {
     /// Computing *ytm* for a zero coupon bond is a simpler expression in contrast with a
     /// coupon bond.
     ///
-    /// Yield to Maturity  = f32::powf(Face Value/ Present Value, N) - 1
+    /// Yield to Maturity  = f64::powf(Face Value/ Present Value, N) - 1
     ///
     /// Where N is the periodicity of the bond; bonds pay coupons if any, semi-annually. This is true for
     /// most bonds with 10, 20 and 30 years maturity.
-
+    ///
+    /// ### Day count
+    /// The `day_count` converts dates into year fractions. US Treasuries use *ACT/ACT ICMA*,
+    /// corporates and agencies generally use *30/360*.
+    ///
+    /// ### Business days
+    /// Coupon dates which are not business days of the `calendar` are paid on the date given
+    /// by the `payment_convention`. The `accrual_convention` moves the dates interest accrues
+    /// between; US Treasuries accrue to the unadjusted dates, most corporates to the adjusted
+    /// ones. Both conventions are `Unadjusted` unless set, Treasuries pay `Following` on the
+    /// `Sifma` calendar.
+    ///
+    /// ### Amortization
+    /// The `principal_schedule` repays principal before maturity, see
+    /// [`Bond::with_principal_schedule`]. Coupons are paid on the balance outstanding at the
+    /// start of each period and the rest of the principal is repaid at maturity.
+    ///
+    /// ### Coupons which change
+    /// The `coupon_rate` is the rate at issue. The `coupon_steps` set a new rate from a date
+    /// (step-up and step-down bonds), the `rating_triggers` add a step from the rating change
+    /// which triggered it, see [`Bond::coupon_rate_at`]. Coupons paid in kind are added to
+    /// the principal instead of paid, see [`PaymentInKind`].
+    ///
+    /// Fields missing from a serialized bond take their [`Default`] values, so bonds saved
+    /// before the conventions were added still load.
     #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(default)]
     pub struct Bond {
         pub issuer: Option<Issuer>,
-        pub principal: f32,
+        pub principal: f64,
         pub issue_date: NaiveDate,
         pub maturity_date: NaiveDate,
-        pub coupon_rate: f32,
+        pub coupon_rate: f64,
         pub periodicity: Periodicity,
-        pub reinvestment_interest: Option<f32>,
+        pub reinvestment_interest: Option<f64>,
+        pub day_count: DayCountConvention,
+        pub calendar: Calendar,
+        pub payment_convention: BusinessDayConvention,
+        pub accrual_convention: BusinessDayConvention,
+        pub first_coupon_date: Option<NaiveDate>,
+        pub penultimate_coupon_date: Option<NaiveDate>,
+        pub stub: StubType,
+        pub end_of_month: bool,
+        pub principal_schedule: Vec<PrincipalPayment>,
+        pub coupon_steps: Vec<CouponStep>,
+        pub rating_triggers: Vec<RatingTrigger>,
+        pub payment_in_kind: PaymentInKind,
     }
 
     impl Default for Bond {
@@ -267,19 +330,93 @@ pub mod bond {
                 coupon_rate: 0.00,
                 periodicity: Periodicity::SemiAnnual,
                 reinvestment_interest: None,
+                day_count: DayCountConvention::ActActIcma,
+                calendar: Calendar::Sifma,
+                payment_convention: BusinessDayConvention::Unadjusted,
+                accrual_convention: BusinessDayConvention::Unadjusted,
+                first_coupon_date: None,
+                penultimate_coupon_date: None,
+                stub: StubType::ShortFirst,
+                end_of_month: true,
+                principal_schedule: Vec::new(),
+                coupon_steps: Vec::new(),
+                rating_triggers: Vec::new(),
+                payment_in_kind: PaymentInKind::Cash,
             }
         }
     }
+    /// A repayment of principal before maturity. For an amortizing bond the `amount` is the
+    /// scheduled repayment. For a sinking fund it is the mandatory sink and `accelerated` is
+    /// the optional sink the issuer elects on top of it. Bonds retired by a sink are redeemed
+    /// pro rata, so every holder sees the same factor.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+    pub struct PrincipalPayment {
+        pub date: NaiveDate,
+        pub amount: f64,
+        #[serde(default)]
+        pub accelerated: f64,
+    }
+
+    /// The coupon rate of the periods starting on or after `date`, for bonds whose coupon
+    /// steps up or down on a schedule.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+    pub struct CouponStep {
+        pub date: NaiveDate,
+        pub rate: f64,
+    }
+
+    /// A change of the coupon rate by `step` triggered by a change of the credit rating of
+    /// the issuer on `date`, paid from the first period starting after the change. A
+    /// downgrade below the trigger rating has a positive step, the upgrade back a negative
+    /// one.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+    pub struct RatingTrigger {
+        pub date: NaiveDate,
+        pub step: f64,
+    }
+
+    /// How the coupons are paid.
+    ///
+    /// * `Cash` - every coupon is paid in cash.
+    /// * `InKind` - every coupon before maturity is added to the principal (PIK).
+    /// * `Toggle` - the coupons of the periods ending on the `elected` dates are added to the
+    ///   principal at the coupon rate plus the `spread`, the others are paid in cash.
+    ///
+    /// Interest is paid on the principal including the coupons added to it and the last
+    /// coupon is always paid in cash.
+    #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+    pub enum PaymentInKind {
+        #[default]
+        Cash,
+        InKind,
+        Toggle {
+            elected: Vec<NaiveDate>,
+            spread: f64,
+        },
+    }
+
+    /// The interest and principal of a coupon period. The `balance` is the principal over
+    /// the period, `interest` is paid in cash and `capitalized` is added to the principal.
+    #[derive(Debug, Clone, Copy)]
+    struct PeriodFlow {
+        period: SchedulePeriod,
+        balance: f64,
+        coupon_rate: f64,
+        interest: f64,
+        capitalized: f64,
+        principal: f64,
+    }
+
     #[derive(Debug, Clone)]
     pub struct CashFlow {
         pub bond: Bond,
         pub time: NaiveDate,
-        pub amount: f32,
+        pub amount: f64,
     }
 
     impl PartialEq for CashFlow {
         fn eq(&self, other: &Self) -> bool {
-            return self.time == other.time && (f32::EPSILON < (self.amount - other.amount).abs());
+            return self.time == other.time && (f64::EPSILON < (self.amount - other.amount).abs());
         }
     }
 
@@ -317,7 +454,7 @@ pub mod bond {
         fn cmp(&self, other: &Self) -> Ordering {
             if self.term < other.term {
                 Ordering::Less
-            } else if (self.term - other.term).abs() < f32::EPSILON {
+            } else if (self.term - other.term).abs() < f64::EPSILON {
                 Ordering::Equal
             } else {
                 Ordering::Greater
@@ -333,11 +470,11 @@ pub mod bond {
 
     /// A convenience function that creates a bond with a specific [`Periodicity`]
     pub fn create_bond_with_periodicity<'a>(
-        principal: f32,
+        principal: f64,
         issue_date: &'a str,
         maturity_date: &'a str,
-        rate: f32,
-        reinvestment_interest_rate: f32,
+        rate: f64,
+        reinvestment_interest_rate: f64,
         periodicity: Periodicity,
         date_format: &'a str,
     ) -> Result<Box<Bond>, BondError> {
@@ -355,6 +492,18 @@ pub mod bond {
                     coupon_rate: rate,
                     periodicity,
                     reinvestment_interest: Some(reinvestment_interest_rate),
+                    day_count: DayCountConvention::ActActIcma,
+                    calendar: Calendar::Sifma,
+                    payment_convention: BusinessDayConvention::Unadjusted,
+                    accrual_convention: BusinessDayConvention::Unadjusted,
+                    first_coupon_date: None,
+                    penultimate_coupon_date: None,
+                    stub: StubType::ShortFirst,
+                    end_of_month: true,
+                    principal_schedule: Vec::new(),
+                    coupon_steps: Vec::new(),
+                    rating_triggers: Vec::new(),
+                    payment_in_kind: PaymentInKind::Cash,
                 };
                 let r = Box::new(b1);
                 return Ok(r);
@@ -370,11 +519,11 @@ pub mod bond {
 
     /// Creates a bond with `SemiAnnual` periodicity.
     pub fn create_bond<'a>(
-        principal: f32,
+        principal: f64,
         issue_date: &'a str,
         maturity_date: &'a str,
-        rate: f32,
-        date_format: &'a str
+        rate: f64,
+        date_format: &'a str,
     ) -> Result<Box<Bond>, BondError> {
         let m_date: Result<NaiveDate, ParseError> =
             NaiveDate::parse_from_str(maturity_date, date_format);
@@ -391,6 +540,18 @@ pub mod bond {
                     coupon_rate: rate,
                     periodicity: Periodicity::SemiAnnual,
                     reinvestment_interest: None,
+                    day_count: DayCountConvention::ActActIcma,
+                    calendar: Calendar::Sifma,
+                    payment_convention: BusinessDayConvention::Unadjusted,
+                    accrual_convention: BusinessDayConvention::Unadjusted,
+                    first_coupon_date: None,
+                    penultimate_coupon_date: None,
+                    stub: StubType::ShortFirst,
+                    end_of_month: true,
+                    principal_schedule: Vec::new(),
+                    coupon_steps: Vec::new(),
+                    rating_triggers: Vec::new(),
+                    payment_in_kind: PaymentInKind::Cash,
                 };
                 return Ok(Box::new(b1));
             }
@@ -405,7 +566,7 @@ pub mod bond {
 
     impl Bond {
         /// The coupon payment adjusted to the 'periodicity' of the bond.
-        pub fn coupon_payment(&self) -> f32 {
+        pub fn coupon_payment(&self) -> f64 {
             match self.periodicity {
                 Periodicity::Quarterly => {
                     return self.principal * (self.coupon_rate / 4.0);
@@ -419,17 +580,22 @@ pub mod bond {
             }
         }
 
-        /// TODO: Macaulay Duration or Duration is a one-factor metric
+        /// Macaulay Duration or Duration is a one-factor metric
         /// for interest rate sensitivity. The duration represents a local percentage change
         /// in price for a corresponding change in rates. Duration is generally represented as a number and
         /// is used to imply the number of time periods and cannot be greater than the maturity of the bond
         /// adjusted to its periodicity.
-        pub fn macaulay_duration(&self) -> Option<f32> {
-            None
+        /// Returns `None` if the bond is not outstanding at `settlement`.
+        pub fn macaulay_duration(&self, settlement: NaiveDate, ytm: f64) -> Option<f64> {
+            if self.coupon_period(settlement).is_none() {
+                None
+            } else {
+                Some(self.metrics(settlement, ytm).macaulay_duration)
+            }
         }
 
         /// The amount of the bond when re-invested at the `reinvestment_interest`
-        pub fn reinvestment_amount(&self) -> f32 {
+        pub fn reinvestment_amount(&self) -> f64 {
             match self.periodicity {
                 Periodicity::Quarterly => match self.reinvestment_interest {
                     Some(int_value) => self.coupon_payment() * int_value / 4.0,
@@ -447,45 +613,36 @@ pub mod bond {
         }
 
         // Some helper functions
-        fn get_months(&self) -> u32 {
-            match self.periodicity {
-                Periodicity::SemiAnnual => {
-                    return 6;
-                }
-                Periodicity::Quarterly => {
-                    return 3;
-                }
-                Periodicity::Annual => {
-                    return 1;
-                }
-            }
-        }
-
         /// Compute the infinitely compounded rate for a specified market rate.
-        pub fn infinitely_compounded_rate(&self, market_price: f32) -> f32 {
-            1.0 / (*self).total_years() * (f32::ln((*self).principal / market_price))
+        pub fn infinitely_compounded_rate(&self, market_price: f64) -> f64 {
+            1.0 / (*self).total_years() * (f64::ln((*self).principal / market_price))
         }
 
-        pub fn rate_for_periodicity(&self, periodicity: Periodicity, market_price: f32) -> f32 {
+        pub fn rate_for_periodicity(&self, periodicity: Periodicity, market_price: f64) -> f64 {
             let compounded_rate = self.infinitely_compounded_rate(market_price);
             let prefix = match periodicity {
                 Periodicity::Quarterly => 4.0,
                 Periodicity::SemiAnnual => 2.0,
                 Periodicity::Annual => 1.0,
             };
-            return prefix * (f32::exp(compounded_rate / prefix) - 1.0);
+            return prefix * (f64::exp(compounded_rate / prefix) - 1.0);
         }
 
         /// The remaining term for the 'Bond'.
-        pub fn term_remaining(self, from_date: NaiveDate) -> f32 {
-            self.maturity_date.years_since(from_date).unwrap() as f32
+        pub fn term_remaining(self, from_date: NaiveDate) -> f64 {
+            self.maturity_date.years_since(from_date).unwrap() as f64
+        }
+
+        fn total_years(&self) -> f64 {
+            self.year_fraction(self.issue_date, self.maturity_date)
         }
 
-        fn total_years(&self) -> f32 {
-            self.maturity_date.years_since(self.issue_date).unwrap() as f32
+        /// The year fraction between two dates using the `day_count` of the bond.
+        pub fn year_fraction(&self, start: NaiveDate, end: NaiveDate) -> f64 {
+            self.day_count.year_fraction(start, end)
         }
 
-        fn get_num_periods(&self) -> f32 {
+        fn get_num_periods(&self) -> f64 {
             match self.periodicity {
                 Periodicity::Quarterly => self.total_years() * 4.0,
                 Periodicity::SemiAnnual => self.total_years() * 2.0,
@@ -493,7 +650,7 @@ pub mod bond {
             }
         }
 
-        fn get_num_periods_for_years(&self, years: f32) -> f32 {
+        fn get_num_periods_for_years(&self, years: f64) -> f64 {
             match self.periodicity {
                 Periodicity::Quarterly => years * 4.0,
                 Periodicity::SemiAnnual => years * 2.0,
@@ -501,15 +658,11 @@ pub mod bond {
             }
         }
 
-        fn get_periods_per_year(&self) -> f32 {
-            match self.periodicity {
-                Periodicity::Quarterly => 4.0,
-                Periodicity::SemiAnnual => 2.0,
-                Periodicity::Annual => 1.0,
-            }
+        fn get_periods_per_year(&self) -> f64 {
+            self.periodicity.periods_per_year()
         }
 
-        fn get_adj_interest_per_period(&self) -> f32 {
+        fn get_adj_interest_per_period(&self) -> f64 {
             match self.periodicity {
                 Periodicity::Quarterly => self.coupon_rate / 4.0,
                 Periodicity::SemiAnnual => self.coupon_rate / 6.0,
@@ -517,7 +670,7 @@ pub mod bond {
             }
         }
 
-        fn adj_interest_per_period(&self, ytm: f32) -> f32 {
+        fn adj_interest_per_period(&self, ytm: f64) -> f64 {
             match self.periodicity {
                 Periodicity::Quarterly => ytm / 4.0,
                 Periodicity::SemiAnnual => ytm / 2.0,
@@ -525,36 +678,35 @@ pub mod bond {
             }
         }
         pub fn is_zero_coupon_bond(&self) -> bool {
-            return (self.coupon_rate - 0.0).abs() < f32::EPSILON;
+            return (self.coupon_rate - 0.0).abs() < f64::EPSILON;
         }
 
         /// Assume the entire period of maturity from the beginning of the
-        /// bond.
-        pub fn yield_to_maturity(&self, market_price: f32) -> Option<f32> {
+        /// bond. Zero coupon bonds have a closed form, coupon bonds are solved with
+        /// Newton's method using the analytic derivative of the price.
+        pub fn yield_to_maturity(&self, market_price: f64) -> Result<f64, SolverError> {
             if self.is_zero_coupon_bond() {
-                let num_per: f32 = self.get_num_periods();
-                println!("Using num_per {:?}", num_per);
-                let fv = f32::powf(self.principal / market_price, 1.0 / num_per);
-                println!("Fv {:?}", fv);
-                return Some((fv - 1.0) * self.get_periods_per_year());
+                let num_per: f64 = self.get_num_periods();
+                let fv = f64::powf(self.principal / market_price, 1.0 / num_per);
+                Ok((fv - 1.0) * self.get_periods_per_year())
             } else {
-                None
+                self.solve_yield(&self.cashflow_periods(self.issue_date), market_price)
             }
         }
 
-        pub fn realized_return(&self, purchase_price: f32, sale_price: f32, years: f32) -> f32 {
-            let periods: f32 = self.get_num_periods_for_years(years);
-            let rhs: f32 = f32::powf(sale_price / purchase_price, 1.0 / periods);
+        pub fn realized_return(&self, purchase_price: f64, sale_price: f64, years: f64) -> f64 {
+            let periods: f64 = self.get_num_periods_for_years(years);
+            let rhs: f64 = f64::powf(sale_price / purchase_price, 1.0 / periods);
             return (rhs - 1.0) * self.get_periods_per_year();
         }
 
         /// Return the baseline price at a `market_price` after `years`.
-        pub fn at_the_money_yield_trajectory(&self, market_price: f32, years: i32) -> f32 {
-            let ytm_option: Option<f32> = self.yield_to_maturity(market_price);
-            match ytm_option {
-                Some(ytm) => {
+        pub fn at_the_money_yield_trajectory(&self, market_price: f64, years: i32) -> f64 {
+            let ytm_result: Result<f64, SolverError> = self.yield_to_maturity(market_price);
+            match ytm_result {
+                Ok(ytm) => {
                     let intervals: &Vec<NaiveDate> = &self.periodicity();
-                    let interest_rate: f32 = self.adj_interest_per_period(ytm);
+                    let interest_rate: f64 = self.adj_interest_per_period(ytm);
                     let mut iter = intervals.into_iter().peekable();
                     let mut accum = market_price;
 
@@ -569,15 +721,15 @@ pub mod bond {
 
                     return accum;
                 }
-                None => {
+                Err(_) => {
                     return 0.0;
                 }
             }
         }
 
-        fn market_price_at_date(&self, ytm: f32, at_date: NaiveDate) -> f32 {
+        fn market_price_at_date(&self, ytm: f64, at_date: NaiveDate) -> f64 {
             let intervals: &Vec<NaiveDate> = &self.periodicity();
-            let interest_rate: f32 = self.adj_interest_per_period(ytm);
+            let interest_rate: f64 = self.adj_interest_per_period(ytm);
             let mut iter = intervals.into_iter().peekable();
             let mut accum = 0.0;
             let mut counter = 0;
@@ -588,7 +740,7 @@ pub mod bond {
                     self, coupon_time, at_date, interest_rate
                 );
                 if *coupon_time >= at_date {
-                    let den = f32::powf(1.0 + interest_rate, counter as f32);
+                    let den = f64::powf(1.0 + interest_rate, counter as f64);
                     println!("Time value {:?}", den);
                     if iter.peek().is_none() {
                         accum = accum + (self.coupon_rate + self.principal) / den;
@@ -601,7 +753,7 @@ pub mod bond {
             return accum;
         }
 
-        pub fn market_price_trajectory(&self, ytm: f32) -> Vec<(NaiveDate, f32)> {
+        pub fn market_price_trajectory(&self, ytm: f64) -> Vec<(NaiveDate, f64)> {
             let intervals: &Vec<NaiveDate> = &self.periodicity();
             let mut iter = intervals.into_iter().peekable();
             let mut result = Vec::new();
@@ -615,13 +767,13 @@ pub mod bond {
         /// A useful yardstick is the constant yield price trajectory. This is the path the bond
         /// take over time to maturity. The trajectory says the following, if the market price is above
         /// the price point in the trajectory, the investor could sell it.
-        pub fn constant_yield_price_trajectory(&self, market_price: f32) -> Vec<(NaiveDate, f32)> {
-            let mut result: Vec<(NaiveDate, f32)> = Vec::new();
-            let ytm_option: Option<f32> = self.yield_to_maturity(market_price);
-            match ytm_option {
-                Some(ytm) => {
+        pub fn constant_yield_price_trajectory(&self, market_price: f64) -> Vec<(NaiveDate, f64)> {
+            let mut result: Vec<(NaiveDate, f64)> = Vec::new();
+            let ytm_result: Result<f64, SolverError> = self.yield_to_maturity(market_price);
+            match ytm_result {
+                Ok(ytm) => {
                     let intervals: &Vec<NaiveDate> = &self.periodicity();
-                    let interest_rate: f32 = self.adj_interest_per_period(ytm);
+                    let interest_rate: f64 = self.adj_interest_per_period(ytm);
                     let mut iter = intervals.into_iter().peekable();
                     let mut accum = market_price;
                     while let Some(coupon_time) = iter.next() {
@@ -632,20 +784,20 @@ pub mod bond {
 
                     return result;
                 }
-                None => {
-                    panic!("Failed to compute ytm");
+                Err(err) => {
+                    panic!("Failed to compute ytm {:?}", err);
                 }
             }
         }
 
-        pub fn get_effective_rate(&self, input: f32) -> f32 {
+        pub fn get_effective_rate(&self, input: f64) -> f64 {
             match self.periodicity {
                 Periodicity::Annual => input,
                 Periodicity::SemiAnnual => input / 2.0,
                 Periodicity::Quarterly => input / 4.0,
             }
         }
-        pub fn get_effective_coupon_payment(&self) -> f32 {
+        pub fn get_effective_coupon_payment(&self) -> f64 {
             match self.periodicity {
                 Periodicity::Annual => self.coupon_rate,
                 Periodicity::SemiAnnual => self.coupon_rate / 2.0,
@@ -653,43 +805,32 @@ pub mod bond {
             }
         }
 
+        /// The issue date followed by the unadjusted coupon dates up to the maturity date,
+        /// see [`Bond::schedule`].
         pub fn periodicity(&self) -> Vec<NaiveDate> {
-            let mut result = Vec::new();
-            let mut st = self.issue_date;
-            result.push(st);
-            while st <= self.maturity_date {
-                st = st + Months::new(self.get_months());
-                result.push(st);
-            }
-            println!("{:?}", result);
-            return result;
+            self.schedule().dates()
         }
 
-        /// Simple cash flow based on the
-        /// Coupon rate and paid out over the year.
+        /// The coupon payments on the payment dates of the [`Bond::schedule`], with the
+        /// principal repaid with the last coupon.
         pub fn cashflow(&self) -> Vec<CashFlow> {
-            let intervals: &Vec<NaiveDate> = &self.periodicity();
-            let mut iter = intervals.into_iter().peekable();
-            let mut result = Vec::new();
-            while let Some(coupon_time) = iter.next() {
-                println!("Bond : {:?}", self);
-                if iter.peek().is_none() {
-                    let cashflow: CashFlow = CashFlow {
-                        bond: self.clone(),
-                        time: coupon_time.clone(),
-                        amount: self.principal + self.coupon_payment(),
-                    };
-                    result.push(cashflow);
-                } else {
-                    let cashflow: CashFlow = CashFlow {
-                        bond: self.clone(),
-                        time: coupon_time.clone(),
-                        amount: self.coupon_payment(),
-                    };
-                    result.push(cashflow);
-                }
-            }
-            return result;
+            self.period_flows()
+                .iter()
+                .map(|flow| CashFlow {
+                    bond: self.clone(),
+                    time: flow.period.payment_date,
+                    amount: flow.interest + flow.principal,
+                })
+                .collect()
+        }
+
+        /// The payment date, the interest and the principal of each coupon period, the two
+        /// parts of the amounts of [`Bond::cashflow`].
+        pub fn payments(&self) -> Vec<(NaiveDate, f64, f64)> {
+            self.period_flows()
+                .iter()
+                .map(|flow| (flow.period.payment_date, flow.interest, flow.principal))
+                .collect()
         }
 
         /// Return cash flow between two time intervals
@@ -725,7 +866,7 @@ pub mod bond {
             &self,
             start_date: NaiveDate,
             end_date: NaiveDate,
-        ) -> Vec<f32> {
+        ) -> Vec<f64> {
             let inrange =
                 (|a: &CashFlow| a.time >= start_date).and(|a: &CashFlow| a.time <= end_date);
             let cashflows: Vec<CashFlow> = self
@@ -749,9 +890,9 @@ pub mod bond {
         fn iterate_rates_generated(
             &self,
             transaction_date: NaiveDate,
-            market_price: f32,
-            guess_rate: f32,
-        ) -> f32 {
+            market_price: f64,
+            guess_rate: f64,
+        ) -> f64 {
             let mut result = 0.0;
             let mut denom = 1.0;
             let intervals: &Vec<NaiveDate> = &self
@@ -760,14 +901,8 @@ pub mod bond {
                 .filter(|x| *x > transaction_date)
                 .filter(|x| *x <= self.maturity_date)
                 .collect();
-            println!("Relevant intervals {:?}", intervals);
             for _term in 0..intervals.len() {
                 denom = denom * (1.0 + self.get_effective_rate(guess_rate));
-                println!(
-                    "Using coupon {:?} - {:?}",
-                    self.get_effective_rate(guess_rate),
-                    self.coupon_payment()
-                );
                 result += self.coupon_payment() / denom;
             }
             result += self.principal / denom;
@@ -779,47 +914,583 @@ pub mod bond {
         /// discount rate at which the sum of all future cashflows equals the price of the bond.
         /// Compute the yield-to-maturity for the bond using start and end points
         /// where the sign changes with respect to the market price.
-        /// ytm is being computed using some narrow constraints to compute the value.
-        /// TODO: Need to replace the bisection with a library.
+        /// `low` and `high` bracket the yield, which is solved with Brent's method.
         pub fn ytm(
             &self,
             transaction_date: NaiveDate,
-            market_price: f32,
-            mut low: f32,
-            mut high: f32,
-        ) -> Result<f32, String> {
-            let mut iter = 1;
-            let max_iter = 100;
-            let mut x;
-            let mut current = 0.0;
-            let init_a = self.iterate_rates_generated(transaction_date, market_price, low);
-            let init_b = self.iterate_rates_generated(transaction_date, market_price, high);
-            if sign(init_a) == sign(init_b) {
-                return Err(
-                    "Endpoints don't have y = 0 in its range. Can't solve this.".to_string()
-                );
+            market_price: f64,
+            low: f64,
+            high: f64,
+        ) -> Result<f64, SolverError> {
+            brent(
+                |rate| self.iterate_rates_generated(transaction_date, market_price, rate),
+                low,
+                high,
+                &SolverConfig::default(),
+            )
+        }
+
+        /// The coupon schedule of the bond, rolled back from the maturity date with the issue
+        /// date as the start of the first accrual period.
+        pub fn schedule(&self) -> Schedule {
+            Schedule {
+                effective_date: self.issue_date,
+                maturity_date: self.maturity_date,
+                periodicity: self.periodicity,
+                first_coupon_date: self.first_coupon_date,
+                penultimate_coupon_date: self.penultimate_coupon_date,
+                stub: self.stub,
+                end_of_month: self.end_of_month,
+                calendar: self.calendar.clone(),
+                accrual_convention: self.accrual_convention,
+                payment_convention: self.payment_convention,
             }
-            while iter < max_iter {
-                println!("Current {:?}", current);
-                current = (low + high) / 2.0;
-                x = self.iterate_rates_generated(transaction_date, market_price, current);
-                println!("{:?} : {:?} : {:?} : {:?}", current, x, init_a, init_b);
-                if high - low / 2.0 < f32::EPSILON {
-                    println!("{:?}", current);
-                    break;
+        }
+
+        /// The coupon rate of the period starting on `date`, the last coupon step on or
+        /// before the date and the rating triggers before it.
+        pub fn coupon_rate_at(&self, date: NaiveDate) -> f64 {
+            let rate = self
+                .coupon_steps
+                .iter()
+                .filter(|step| step.date <= date)
+                .max_by_key(|step| step.date)
+                .map_or(self.coupon_rate, |step| step.rate);
+            let triggered: f64 = self
+                .rating_triggers
+                .iter()
+                .filter(|trigger| trigger.date < date)
+                .map(|trigger| trigger.step)
+                .sum();
+            rate + triggered
+        }
+
+        /// The interest and principal of every coupon period. Interest accrues on the
+        /// balance at the start of the period at the [`Bond::coupon_rate_at`] the start; a
+        /// stub accrues for its year fraction, a regular period for a whole period.
+        fn period_flows(&self) -> Vec<PeriodFlow> {
+            let periods = self.schedule().periods();
+            let last = periods.len().saturating_sub(1);
+            let mut capitalized_total = 0.0;
+            let mut result = Vec::new();
+            for (i, period) in periods.into_iter().enumerate() {
+                let balance = self.principal
+                    - self.principal_repaid(self.issue_date, period.accrual_start)
+                    + capitalized_total;
+                let mut coupon_rate = self.coupon_rate_at(period.accrual_start);
+                let in_kind = i != last
+                    && match &self.payment_in_kind {
+                        PaymentInKind::Cash => false,
+                        PaymentInKind::InKind => true,
+                        PaymentInKind::Toggle { elected, spread } => {
+                            let toggled = elected.contains(&period.accrual_end);
+                            if toggled {
+                                coupon_rate += spread;
+                            }
+                            toggled
+                        }
+                    };
+                let fraction = if period.is_stub {
+                    period.year_fraction(
+                        period.accrual_start,
+                        period.accrual_end,
+                        self.day_count.clone(),
+                        self.periodicity,
+                    )
+                } else {
+                    1.0 / self.get_periods_per_year()
+                };
+                let accrued = balance * coupon_rate * fraction;
+                let (interest, capitalized) = if in_kind {
+                    (0.0, accrued)
+                } else {
+                    (accrued, 0.0)
+                };
+                capitalized_total += capitalized;
+                let principal = if i == last {
+                    balance
+                } else {
+                    self.principal_repaid(period.accrual_start, period.accrual_end)
+                };
+                result.push(PeriodFlow {
+                    period,
+                    balance,
+                    coupon_rate,
+                    interest,
+                    capitalized,
+                    principal,
+                });
+            }
+            result
+        }
+
+        /// The bond repaying the `schedule` of principal before maturity. The repayments
+        /// are paid on the accrual dates they fall on or with the next coupon, they must be
+        /// after the issue date, no later than the maturity date and not more than the
+        /// principal in total.
+        pub fn with_principal_schedule(
+            mut self,
+            schedule: Vec<PrincipalPayment>,
+        ) -> Result<Bond, BondError> {
+            let total: f64 = schedule.iter().map(|p| p.amount + p.accelerated).sum();
+            if total > self.principal
+                || schedule
+                    .iter()
+                    .any(|p| p.amount < 0.0 || p.accelerated < 0.0)
+            {
+                return Err(BondError {
+                    message: "The principal repayments exceed the principal.",
+                    message_code: ErrorType::InvalidPrincipal,
+                });
+            }
+            if schedule
+                .iter()
+                .any(|p| p.date <= self.issue_date || p.date > self.maturity_date)
+            {
+                return Err(BondError {
+                    message: "Principal repayments must be between the issue and maturity dates.",
+                    message_code: ErrorType::InvalidDate,
+                });
+            }
+            self.principal_schedule = schedule;
+            self.principal_schedule.sort_by_key(|p| p.date);
+            Ok(self)
+        }
+
+        /// The principal repaid by the schedule in the interval (start, end].
+        fn principal_repaid(&self, start: NaiveDate, end: NaiveDate) -> f64 {
+            self.principal_schedule
+                .iter()
+                .filter(|p| start < p.date && p.date <= end)
+                .map(|p| p.amount + p.accelerated)
+                .sum()
+        }
+
+        /// The principal outstanding after the repayments on or before `date`, with the
+        /// coupons paid in kind up to the date.
+        pub fn outstanding(&self, date: NaiveDate) -> f64 {
+            self.outstanding_from(&self.period_flows(), date)
+        }
+
+        /// The [`Bond::outstanding`] principal on each of the `dates`.
+        pub fn outstanding_on(&self, dates: &[NaiveDate]) -> Vec<f64> {
+            let flows = self.period_flows();
+            dates
+                .iter()
+                .map(|date| self.outstanding_from(&flows, *date))
+                .collect()
+        }
+
+        fn outstanding_from(&self, flows: &[PeriodFlow], date: NaiveDate) -> f64 {
+            let capitalized: f64 = flows
+                .iter()
+                .filter(|flow| flow.period.accrual_end <= date)
+                .map(|flow| flow.capitalized)
+                .sum();
+            self.principal - self.principal_repaid(self.issue_date, date) + capitalized
+        }
+
+        /// The fraction of the principal outstanding at `date`.
+        pub fn factor(&self, date: NaiveDate) -> f64 {
+            self.outstanding(date) / self.principal
+        }
+
+        /// The index of the flow whose period is accruing at `settlement`.
+        fn current_flow(flows: &[PeriodFlow], settlement: NaiveDate) -> Option<usize> {
+            flows.iter().position(|flow| {
+                flow.period.accrual_start <= settlement && settlement < flow.period.accrual_end
+            })
+        }
+
+        /// The coupon period `(previous coupon date, next coupon date)` containing the
+        /// `settlement` date; `None` if the bond is not outstanding at `settlement`.
+        pub fn coupon_period(&self, settlement: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+            self.schedule()
+                .periods()
+                .into_iter()
+                .find(|p| p.accrual_start <= settlement && settlement < p.accrual_end)
+                .map(|p| (p.accrual_start, p.accrual_end))
+        }
+
+        /// The interest accrued from the previous coupon date up to the `settlement` date,
+        /// measured with the `day_count` of the bond.
+        pub fn accrued_interest(&self, settlement: NaiveDate) -> f64 {
+            self.accrued_from(&self.period_flows(), settlement)
+        }
+
+        fn accrued_from(&self, flows: &[PeriodFlow], settlement: NaiveDate) -> f64 {
+            match Self::current_flow(flows, settlement).map(|i| &flows[i]) {
+                Some(flow) => {
+                    let year_fraction = flow.period.year_fraction(
+                        flow.period.accrual_start,
+                        settlement,
+                        self.day_count.clone(),
+                        self.periodicity,
+                    );
+                    flow.balance * flow.coupon_rate * year_fraction
                 }
-                if sign(x) == sign(init_a) {
-                    low = current;
+                None => 0.0,
+            }
+        }
+
+        /// The remaining cashflows after `settlement` as the number of coupon periods until
+        /// the payment, the interest and the principal. The first period is the fraction of
+        /// the current coupon period left until the next coupon date, a later stub counts as
+        /// its fraction of a regular period.
+        fn cashflow_components(
+            &self,
+            flows: &[PeriodFlow],
+            settlement: NaiveDate,
+        ) -> Vec<(f64, f64, f64)> {
+            let current = match Self::current_flow(flows, settlement) {
+                Some(current) => current,
+                None => return Vec::new(),
+            };
+            let periods_per_year = self.get_periods_per_year();
+            let mut elapsed = 0.0;
+            let mut result = Vec::new();
+            for (i, flow) in flows.iter().enumerate().skip(current) {
+                let period = &flow.period;
+                elapsed += if i == current || period.is_stub {
+                    periods_per_year
+                        * period.year_fraction(
+                            period.accrual_start.max(settlement),
+                            period.accrual_end,
+                            self.day_count.clone(),
+                            self.periodicity,
+                        )
                 } else {
-                    high = current;
+                    1.0
+                };
+                result.push((elapsed, flow.interest, flow.principal));
+            }
+            result
+        }
+
+        /// The remaining cashflows after `settlement` as pairs of the number of coupon
+        /// periods until the payment and the amount, see [`Bond::cashflow_components`].
+        fn cashflow_periods(&self, settlement: NaiveDate) -> Vec<(f64, f64)> {
+            self.flow_periods(&self.period_flows(), settlement)
+        }
+
+        fn flow_periods(&self, flows: &[PeriodFlow], settlement: NaiveDate) -> Vec<(f64, f64)> {
+            self.cashflow_components(flows, settlement)
+                .into_iter()
+                .map(|(periods, interest, principal)| (periods, interest + principal))
+                .collect()
+        }
+
+        /// The remaining cashflows after `settlement` as the payment date, the time in years
+        /// and the amount. The times are those of [`Bond::price_from_curve`].
+        pub fn cashflow_times(&self, settlement: NaiveDate) -> Vec<(NaiveDate, f64, f64)> {
+            let flows = self.period_flows();
+            let current = match Self::current_flow(&flows, settlement) {
+                Some(current) => current,
+                None => return Vec::new(),
+            };
+            let periods_per_year = self.get_periods_per_year();
+            flows
+                .iter()
+                .skip(current)
+                .zip(self.cashflow_components(&flows, settlement))
+                .map(|(flow, (periods, interest, principal))| {
+                    (
+                        flow.period.payment_date,
+                        periods / periods_per_year,
+                        interest + principal,
+                    )
+                })
+                .collect()
+        }
+
+        /// The weighted average life, the average time in years from `settlement` until the
+        /// remaining principal is repaid, weighted by the principal repaid.
+        pub fn average_life(&self, settlement: NaiveDate) -> f64 {
+            let periods_per_year = self.get_periods_per_year();
+            let flows = self.period_flows();
+            let (weighted, total) = self.cashflow_components(&flows, settlement).iter().fold(
+                (0.0, 0.0),
+                |(weighted, total), (periods, _, principal)| {
+                    (
+                        weighted + periods / periods_per_year * principal,
+                        total + principal,
+                    )
+                },
+            );
+            if total == 0.0 {
+                0.0
+            } else {
+                weighted / total
+            }
+        }
+
+        /// The yield to average life, the yield of a bullet bond with the same coupon for
+        /// the balance accruing interest at `settlement`, maturing on the coupon date whose
+        /// number of coupon periods after `settlement` is nearest the average life in periods.
+        /// Settling on a coupon date, that is the average life rounded to whole periods. The
+        /// bullet keeps the coupon dates of the bond, so its
+        /// accrued interest is that of the bond. The `clean_price` is for the balance.
+        pub fn yield_to_average_life(
+            &self,
+            settlement: NaiveDate,
+            clean_price: f64,
+        ) -> Result<f64, SolverError> {
+            let average_life = self.average_life(settlement) * self.get_periods_per_year();
+            let distance = |periods: f64| (periods - average_life).abs();
+            let maturity_date = self
+                .schedule()
+                .dates()
+                .into_iter()
+                .filter(|date| *date > settlement)
+                .zip(self.cashflow_components(&self.period_flows(), settlement))
+                .min_by(|(_, (a, _, _)), (_, (b, _, _))| distance(*a).total_cmp(&distance(*b)))
+                .map_or(self.maturity_date, |(date, _)| date);
+            let principal = self
+                .coupon_period(settlement)
+                .map_or(0.0, |(start, _)| self.outstanding(start));
+            let bullet = Bond {
+                principal,
+                maturity_date,
+                first_coupon_date: self.first_coupon_date.filter(|date| *date < maturity_date),
+                penultimate_coupon_date: None,
+                principal_schedule: Vec::new(),
+                ..self.clone()
+            };
+            bullet.yield_from_clean_price(settlement, clean_price)
+        }
+
+        /// The full (invoice) price paid at `settlement` for a yield of `ytm`. Each remaining
+        /// cashflow is discounted for the fraction of the current coupon period left until the
+        /// next coupon date plus the number of whole periods after that.
+        pub fn dirty_price(&self, settlement: NaiveDate, ytm: f64) -> f64 {
+            self.present_value(&self.cashflow_periods(settlement), ytm)
+        }
+
+        /// The value of the `(periods, amount)` cashflows for a yield of `ytm`.
+        fn present_value(&self, cashflows: &[(f64, f64)], ytm: f64) -> f64 {
+            let rate = self.adj_interest_per_period(ytm);
+            cashflows
+                .iter()
+                .map(|(periods, amount)| amount / f64::powf(1.0 + rate, *periods))
+                .sum()
+        }
+
+        /// The derivative of [`Bond::present_value`] with respect to the yield.
+        fn present_value_derivative(&self, cashflows: &[(f64, f64)], ytm: f64) -> f64 {
+            let rate = self.adj_interest_per_period(ytm);
+            let periods_per_year = self.get_periods_per_year();
+            cashflows
+                .iter()
+                .map(|(periods, amount)| {
+                    -periods / periods_per_year * amount / f64::powf(1.0 + rate, periods + 1.0)
+                })
+                .sum()
+        }
+
+        /// The yield at which the `(periods, amount)` cashflows are worth `target`.
+        fn solve_yield(&self, cashflows: &[(f64, f64)], target: f64) -> Result<f64, SolverError> {
+            solve(
+                |y| self.present_value(cashflows, y) - target,
+                |y| self.present_value_derivative(cashflows, y),
+                self.coupon_rate,
+                -0.5,
+                1.0,
+                &SolverConfig::default(),
+            )
+        }
+
+        /// The analytic duration, DV01 and convexity at `settlement` for a yield of `ytm`
+        /// compounded at the periodicity of the bond.
+        pub fn metrics(&self, settlement: NaiveDate, ytm: f64) -> BondMetrics {
+            let rate = self.adj_interest_per_period(ytm);
+            let periods_per_year = self.get_periods_per_year();
+            let mut price = 0.0;
+            let mut weighted_time = 0.0;
+            let mut weighted_convexity = 0.0;
+            for (periods, amount) in self.cashflow_periods(settlement) {
+                let pv = amount / f64::powf(1.0 + rate, periods);
+                price += pv;
+                weighted_time += periods / periods_per_year * pv;
+                weighted_convexity +=
+                    periods * (periods + 1.0) / (periods_per_year * periods_per_year) * pv
+                        / ((1.0 + rate) * (1.0 + rate));
+            }
+            if price == 0.0 {
+                return BondMetrics {
+                    dv01: 0.0,
+                    convexity: 0.0,
+                    macaulay_duration: 0.0,
+                    modified_duration: 0.0,
+                };
+            }
+            let macaulay_duration = weighted_time / price;
+            let modified_duration = macaulay_duration / (1.0 + rate);
+            BondMetrics {
+                dv01: modified_duration * price * 0.0001,
+                convexity: weighted_convexity / price,
+                macaulay_duration,
+                modified_duration,
+            }
+        }
+
+        /// The dirty price at `settlement` discounting each cashflow off the `curve`.
+        pub fn price_from_curve<C: DiscountCurve + ?Sized>(
+            &self,
+            settlement: NaiveDate,
+            curve: &C,
+        ) -> f64 {
+            let periods_per_year = self.get_periods_per_year();
+            self.cashflow_periods(settlement)
+                .iter()
+                .map(|(periods, amount)| amount * curve.discount(periods / periods_per_year))
+                .sum()
+        }
+
+        /// The duration, DV01 and convexity for a parallel shift of the continuously
+        /// compounded zero rates of the `curve` (Fisher-Weil duration). With continuous
+        /// compounding the Macaulay and modified durations are equal.
+        pub fn metrics_from_curve<C: DiscountCurve + ?Sized>(
+            &self,
+            settlement: NaiveDate,
+            curve: &C,
+        ) -> BondMetrics {
+            let periods_per_year = self.get_periods_per_year();
+            let mut price = 0.0;
+            let mut weighted_time = 0.0;
+            let mut weighted_convexity = 0.0;
+            for (periods, amount) in self.cashflow_periods(settlement) {
+                let term = periods / periods_per_year;
+                let pv = amount * curve.discount(term);
+                price += pv;
+                weighted_time += term * pv;
+                weighted_convexity += term * term * pv;
+            }
+            if price == 0.0 {
+                return BondMetrics {
+                    dv01: 0.0,
+                    convexity: 0.0,
+                    macaulay_duration: 0.0,
+                    modified_duration: 0.0,
+                };
+            }
+            let duration = weighted_time / price;
+            BondMetrics {
+                dv01: duration * price * 0.0001,
+                convexity: weighted_convexity / price,
+                macaulay_duration: duration,
+                modified_duration: duration,
+            }
+        }
+
+        /// The quoted price, the dirty price less the accrued interest.
+        pub fn clean_price(&self, settlement: NaiveDate, ytm: f64) -> f64 {
+            let flows = self.period_flows();
+            let cashflows = self.flow_periods(&flows, settlement);
+            self.present_value(&cashflows, ytm) - self.accrued_from(&flows, settlement)
+        }
+
+        /// The yield for a quoted `clean_price` at `settlement`.
+        pub fn yield_from_clean_price(
+            &self,
+            settlement: NaiveDate,
+            clean_price: f64,
+        ) -> Result<f64, SolverError> {
+            let flows = self.period_flows();
+            let target = clean_price + self.accrued_from(&flows, settlement);
+            self.solve_yield(&self.flow_periods(&flows, settlement), target)
+        }
+
+        /// The remaining cashflows, as in [`Bond::cashflow_periods`], when the bond is
+        /// redeemed on `redemption_date` at `redemption_price` per 100 of the principal then
+        /// outstanding. On a payment date the redemption is paid with the payment; between
+        /// coupon dates it is paid on the date with the interest accrued to it.
+        fn redemption_periods(
+            &self,
+            period_flows: &[PeriodFlow],
+            settlement: NaiveDate,
+            redemption_date: NaiveDate,
+            redemption_price: f64,
+        ) -> Vec<(f64, f64)> {
+            let current = match Self::current_flow(period_flows, settlement) {
+                Some(current) => current,
+                None => return Vec::new(),
+            };
+            let flows: Vec<(&PeriodFlow, (f64, f64, f64))> = period_flows
+                .iter()
+                .skip(current)
+                .zip(self.cashflow_components(period_flows, settlement))
+                .collect();
+            let paid = flows
+                .iter()
+                .take_while(|(flow, _)| flow.period.payment_date <= redemption_date)
+                .count();
+            let mut result: Vec<(f64, f64)> = flows[..paid]
+                .iter()
+                .map(|(_, (periods, interest, principal))| (*periods, interest + principal))
+                .collect();
+            match flows.get(paid) {
+                Some((flow, _))
+                    if flow.period.accrual_start < redemption_date
+                        && redemption_date < flow.period.accrual_end =>
+                {
+                    let period = &flow.period;
+                    let elapsed = result.last().map_or(0.0, |(periods, _)| *periods);
+                    let periods = elapsed
+                        + self.get_periods_per_year()
+                            * period.year_fraction(
+                                period.accrual_start.max(settlement),
+                                redemption_date,
+                                self.day_count.clone(),
+                                self.periodicity,
+                            );
+                    let accrued = flow.balance
+                        * flow.coupon_rate
+                        * period.year_fraction(
+                            period.accrual_start,
+                            redemption_date,
+                            self.day_count.clone(),
+                            self.periodicity,
+                        );
+                    // Interest paid in kind is capitalized into the principal redeemed.
+                    let amount = if flow.capitalized > 0.0 {
+                        redemption_price / 100.0 * (flow.balance + accrued)
+                    } else {
+                        accrued + redemption_price / 100.0 * flow.balance
+                    };
+                    result.push((periods, amount));
+                }
+                _ => {
+                    if let (Some(last), Some((flow, (_, _, principal)))) =
+                        (result.last_mut(), paid.checked_sub(1).map(|i| &flows[i]))
+                    {
+                        let remaining = flow.balance + flow.capitalized - principal;
+                        last.1 += redemption_price / 100.0 * remaining;
+                    }
                 }
-                iter = iter + 1;
             }
-            Ok(current)
+            result
+        }
+
+        /// The yield for a quoted `clean_price` at `settlement` when the bond is redeemed on
+        /// `redemption_date` at `redemption_price` per 100 of the principal outstanding, the
+        /// yield to call for a call date and price. The yield to maturity is the yield for
+        /// the maturity date at 100.
+        pub fn yield_to_redemption(
+            &self,
+            settlement: NaiveDate,
+            clean_price: f64,
+            redemption_date: NaiveDate,
+            redemption_price: f64,
+        ) -> Result<f64, SolverError> {
+            let flows = self.period_flows();
+            let target = clean_price + self.accrued_from(&flows, settlement);
+            let cashflows =
+                self.redemption_periods(&flows, settlement, redemption_date, redemption_price);
+            self.solve_yield(&cashflows, target)
         }
     }
 
-    fn get_months_as_f32(payment_schedule: Periodicity) -> f32 {
+    fn get_months_as_f64(payment_schedule: Periodicity) -> f64 {
         match payment_schedule {
             Periodicity::Quarterly => {
                 return 3.0;
@@ -843,21 +1514,21 @@ pub mod bond {
         payment_schedule: Periodicity,
     ) -> Vec<DiscountFactor> {
         let mut result: Vec<DiscountFactor> = Vec::new();
-        let months_f32: f32 = get_months_as_f32(payment_schedule);
-        let months_in_year: f32 = 12.0;
-        let interest_factor: f32 = months_in_year / months_f32;
-        let mut counter: f32 = 0.0;
+        let months_f64: f64 = get_months_as_f64(payment_schedule);
+        let months_in_year: f64 = 12.0;
+        let interest_factor: f64 = months_in_year / months_f64;
+        let mut counter: f64 = 0.0;
         for i in 0..market_data.len() {
             if i == 0 {
-                let numerator: f32 = market_data[i].market_price;
-                let denominator: f32 = 100.0 + market_data[i].coupon_rate / interest_factor;
-                let init_value: f32 = numerator / denominator;
+                let numerator: f64 = market_data[i].market_price;
+                let denominator: f64 = 100.0 + market_data[i].coupon_rate / interest_factor;
+                let init_value: f64 = numerator / denominator;
                 println!(
                     "Using numerator {:?} and denominator {:?} discount_factor {:?}",
                     numerator, denominator, init_value
                 );
                 let df: DiscountFactor = DiscountFactor {
-                    term: months_f32 / months_in_year,
+                    term: months_f64 / months_in_year,
                     discount: init_value,
                 };
                 counter = counter + 1.0;
@@ -870,8 +1541,8 @@ pub mod bond {
                         inter_sigma + (md.coupon_rate / interest_factor) * result[i].discount;
                 }
                 debug!("Using intermediate discounts {:?}", inter_sigma);
-                let numerator: f32 = md.market_price - inter_sigma;
-                let denominator: f32 = 100.00 + (md.coupon_rate / interest_factor);
+                let numerator: f64 = md.market_price - inter_sigma;
+                let denominator: f64 = 100.00 + (md.coupon_rate / interest_factor);
                 let new_value = numerator / denominator;
                 debug!(
                     "Using numerator {:?} and denominator {:?}",
@@ -879,7 +1550,7 @@ pub mod bond {
                 );
 
                 let df: DiscountFactor = DiscountFactor {
-                    term: counter * months_f32 / months_in_year,
+                    term: counter * months_f64 / months_in_year,
                     discount: new_value,
                 };
                 result.push(df);
@@ -920,17 +1591,24 @@ mod tests {
     use crate::bond::bond::discount_factor;
     use crate::bond::bond::Bond;
     use crate::bond::bond::BondError;
+    use crate::bond::bond::DiscountCurve;
     use crate::bond::bond::DiscountFactor;
     use crate::bond::bond::MarketData;
     use crate::bond::bond::Periodicity;
+    use crate::bond::bond::{
+        CouponStep, ErrorType, PaymentInKind, PrincipalPayment, RatingTrigger,
+    };
+    use crate::calendar::calendar::BusinessDayConvention;
     use crate::pandl::pandl::BondTransaction;
+    use crate::schedule::schedule::StubType;
+    use crate::solver::solver::SolverErrorType;
     use crate::Create_Market_Data;
     use crate::Issue_Bond;
     use assert_approx_eq::assert_approx_eq;
     use chrono::{Datelike, NaiveDate, ParseError};
 
     fn create_zcb_principal_maturity<'a>(
-        principal: f32,
+        principal: f64,
         issue_date: &'a str,
         mat_date: &'a str,
     ) -> Result<Box<Bond>, BondError> {
@@ -938,7 +1616,7 @@ mod tests {
         return Issue_Bond! (with principal issue_date mat_date 0.0);
     }
 
-    fn create_zcb<'a> (principal: f32) -> Result<Box<Bond>, BondError> {
+    fn create_zcb<'a>(principal: f64) -> Result<Box<Bond>, BondError> {
         return Issue_Bond! (with principal "04/15/2021" "04/15/2051" 0.0);
     }
 
@@ -949,10 +1627,13 @@ mod tests {
 
     #[test]
     fn test_bond_sort() {
-        let b1: Result<Box<Bond>, BondError> = Issue_Bond!(using 100.0 "04/15/2014" "05/15/2024" 2.5);
-        let b3: Result<Box<Bond>, BondError> = Issue_Bond!(using 100.0 "04/15/2014" "05/15/2024" 2.5);
+        let b1: Result<Box<Bond>, BondError> =
+            Issue_Bond!(using 100.0 "04/15/2014" "05/15/2024" 2.5);
+        let b3: Result<Box<Bond>, BondError> =
+            Issue_Bond!(using 100.0 "04/15/2014" "05/15/2024" 2.5);
 
-        let b2: Result<Box<Bond>, BondError> = Issue_Bond!(using 100.0 "03/15/2014" "05/15/2024" 2.5);
+        let b2: Result<Box<Bond>, BondError> =
+            Issue_Bond!(using 100.0 "03/15/2014" "05/15/2024" 2.5);
         let mut bonds: Vec<Bond> = Vec::new();
         match (b1, b2) {
             (Ok(bond1), Ok(bond2)) => {
@@ -1026,11 +1707,15 @@ mod tests {
         match b1 {
             Result::Ok(val) => {
                 let mut cashflows = val.cashflow().into_iter().peekable();
+                // The bond is issued a month before the first coupon date, which pays a short
+                // first coupon for 30 of the 181 days of the regular period.
+                let first = cashflows.next().unwrap();
+                assert_approx_eq!(first.amount, 125.0 * 30.0 / 181.0, 1e-4);
                 while let Some(cashflow) = cashflows.next() {
                     if cashflows.peek().is_none() {
-                        assert_approx_eq!(cashflow.amount, 225.0, f32::EPSILON);
+                        assert_approx_eq!(cashflow.amount, 225.0, f64::EPSILON);
                     } else {
-                        assert_approx_eq!(cashflow.amount, 125.0, f32::EPSILON);
+                        assert_approx_eq!(cashflow.amount, 125.0, f64::EPSILON);
                     }
                 }
             }
@@ -1079,8 +1764,9 @@ mod tests {
                 match (start_date_opt, end_date_opt) {
                     (Ok(start_date), Ok(end_date)) => {
                         let cashflows = val.cashflow_between(start_date, end_date);
-                        assert_eq!(1, cashflows.len());
-                        assert_approx_eq!(125.0, cashflows[0].amount);
+                        assert_eq!(2, cashflows.len());
+                        assert_approx_eq!(125.0 * 30.0 / 181.0, cashflows[0].amount, 1e-4);
+                        assert_approx_eq!(125.0, cashflows[1].amount);
                         println!("Cashflow {:?}", cashflows);
                     }
                     (_, _) => {
@@ -1099,13 +1785,13 @@ mod tests {
         let market_data: Vec<MarketData> = create_test_market_data();
         let discount_factor: Vec<DiscountFactor> =
             discount_factor(&market_data, Periodicity::SemiAnnual);
-        assert_approx_eq!(discount_factor[0].discount, 0.9999231, f32::EPSILON);
-        assert_approx_eq!(discount_factor[1].discount, 0.99941903, f32::EPSILON);
-        assert_approx_eq!(discount_factor[2].discount, 0.9985045, f32::EPSILON);
-        assert_approx_eq!(discount_factor[3].discount, 0.99704117, f32::EPSILON);
-        assert_approx_eq!(discount_factor[4].discount, 0.9945582, f32::EPSILON);
-        assert_approx_eq!(discount_factor[5].discount, 0.99019545, f32::EPSILON);
-        assert_approx_eq!(discount_factor[6].discount, 0.9847417, f32::EPSILON);
+        assert_approx_eq!(discount_factor[0].discount, 0.9999231054, 1e-9);
+        assert_approx_eq!(discount_factor[1].discount, 0.9994189902, 1e-9);
+        assert_approx_eq!(discount_factor[2].discount, 0.9985044964, 1e-9);
+        assert_approx_eq!(discount_factor[3].discount, 0.9970411951, 1e-9);
+        assert_approx_eq!(discount_factor[4].discount, 0.9945581925, 1e-9);
+        assert_approx_eq!(discount_factor[5].discount, 0.9901954482, 1e-9);
+        assert_approx_eq!(discount_factor[6].discount, 0.9847416899, 1e-9);
     }
 
     #[test]
@@ -1138,7 +1824,7 @@ mod tests {
         let date_format = "%m/%d/%Y";
         let purchase_date = NaiveDate::parse_from_str("11/13/2020", "%m/%d/%Y").unwrap();
         let b1 = create_bond(100.0, "11/16/1992", "11/15/2022", 7.625, date_format).unwrap();
-        let spread: f32 = -0.000116; // TODO: This needs to be computed separately.
+        let spread: f64 = -0.000116; // TODO: This needs to be computed separately.
         let mut bt = BondTransaction {
             underlying: &*b1,
             purchase_date,
@@ -1149,8 +1835,8 @@ mod tests {
         };
         let market_data: Vec<MarketData> = create_test_market_data();
         let term_remaining = (*b1).clone().term_remaining(purchase_date);
-        assert_approx_eq!(term_remaining, 2.0, f32::EPSILON);
-        let result: Vec<f32> = [0.001013, 0.001746, 0.002429, 0.002185].to_vec();
+        assert_approx_eq!(term_remaining, 2.0, f64::EPSILON);
+        let result: Vec<f64> = [0.001013, 0.001746, 0.002429, 0.002185].to_vec();
         bt.set_term_rates(&result);
         assert_approx_eq!(
             111.11555,
@@ -1180,7 +1866,7 @@ mod tests {
         match b1 {
             Result::Ok(val) => {
                 let ytm = val.yield_to_maturity(50.00).unwrap();
-                assert_approx_eq!(ytm, 0.10239267, f32::EPSILON);
+                assert_approx_eq!(ytm, 0.1023926468, 1e-9);
             }
             Result::Err(_) => {
                 panic!("Failed to create bond.");
@@ -1188,7 +1874,7 @@ mod tests {
         }
     }
 
-    fn internal_yield_2() -> Vec<(NaiveDate, f32)> {
+    fn internal_yield_2() -> Vec<(NaiveDate, f64)> {
         let b1 = create_bond(100.00, "04/15/2021", "04/15/2051", 6.00, "%m/%d/%Y");
         let constant_yield = 0.2;
         match b1 {
@@ -1199,7 +1885,7 @@ mod tests {
         }
     }
 
-    fn internal_yield_1() -> Vec<(NaiveDate, f32)> {
+    fn internal_yield_1() -> Vec<(NaiveDate, f64)> {
         let b1 = create_bond(100.00, "04/15/2021", "04/15/2041", 6.00, "%m/%d/%Y");
         let constant_yield = 0.20;
         match b1 {
@@ -1259,9 +1945,9 @@ mod tests {
         match b1 {
             Result::Ok(val) => {
                 let result = val.at_the_money_yield_trajectory(60.0, 2);
-                assert_approx_eq!(66.45397, result, f32::EPSILON);
+                assert_approx_eq!(66.4539805949, result, 1e-9);
                 let realized_return = val.realized_return(60.0, 68.0, 2.0);
-                assert_approx_eq!(0.063570976, realized_return, f32::EPSILON);
+                assert_approx_eq!(0.0635709775481, realized_return, 1e-9);
             }
             Err(_) => {
                 panic!("Failed to create bond");
@@ -1274,9 +1960,9 @@ mod tests {
         match b1 {
             Result::Ok(val) => {
                 let inf_compounded_rate = val.infinitely_compounded_rate(60.0);
-                assert_approx_eq!(inf_compounded_rate, 0.05108256, f32::EPSILON);
+                assert_approx_eq!(inf_compounded_rate, 0.0510825624, 1e-9);
                 let mut rate_for_per = (*val).rate_for_periodicity(Periodicity::Quarterly, 60.0);
-                assert_approx_eq!(rate_for_per, 0.0514102, f32::EPSILON);
+                assert_approx_eq!(rate_for_per, 0.0514101338, 1e-9);
                 rate_for_per = val.rate_for_periodicity(Periodicity::Annual, 60.00);
                 assert_approx_eq!(rate_for_per, 0.052409768);
                 rate_for_per = val.rate_for_periodicity(Periodicity::SemiAnnual, 60.00);
@@ -1295,4 +1981,278 @@ mod tests {
         let ytd = b1.ytm(date, 111.3969, 0.00, 0.01).unwrap();
         assert_approx_eq!(0.000252, ytd);
     }
+
+    #[test]
+    fn test_accrued_interest() {
+        let b1 = create_bond(100.0, "01/15/2020", "01/15/2025", 0.05, "%m/%d/%Y").unwrap();
+        let settlement = NaiveDate::parse_from_str("04/15/2022", "%m/%d/%Y").unwrap();
+        let (previous, next) = b1.coupon_period(settlement).unwrap();
+        assert_eq!(
+            previous,
+            NaiveDate::parse_from_str("01/15/2022", "%m/%d/%Y").unwrap()
+        );
+        assert_eq!(
+            next,
+            NaiveDate::parse_from_str("07/15/2022", "%m/%d/%Y").unwrap()
+        );
+        assert_approx_eq!(b1.accrued_interest(settlement), 1.2430939, 1e-5);
+        assert_approx_eq!(b1.accrued_interest(previous), 0.0, f64::EPSILON);
+    }
+
+    #[test]
+    fn test_clean_dirty_price() {
+        let b1 = create_bond(100.0, "01/15/2020", "01/15/2025", 0.05, "%m/%d/%Y").unwrap();
+        let settlement = NaiveDate::parse_from_str("04/15/2022", "%m/%d/%Y").unwrap();
+        assert_approx_eq!(b1.dirty_price(settlement, 0.05), 101.23538, 1e-3);
+        assert_approx_eq!(b1.clean_price(settlement, 0.05), 99.99228, 1e-3);
+        assert_approx_eq!(b1.clean_price(settlement, 0.06), 97.48884, 1e-3);
+        let coupon_date = NaiveDate::parse_from_str("01/15/2022", "%m/%d/%Y").unwrap();
+        assert_approx_eq!(b1.clean_price(coupon_date, 0.05), 100.0, 1e-3);
+    }
+
+    #[test]
+    fn test_yield_from_clean_price() {
+        let b1 = create_bond(100.0, "01/15/2020", "01/15/2025", 0.05, "%m/%d/%Y").unwrap();
+        let settlement = NaiveDate::parse_from_str("04/15/2022", "%m/%d/%Y").unwrap();
+        let ytm = b1.yield_from_clean_price(settlement, 97.48884).unwrap();
+        assert_approx_eq!(ytm, 0.06, 1e-4);
+        assert!(b1.yield_from_clean_price(settlement, 1000.0).is_err());
+    }
+
+    #[test]
+    fn test_ytm_coupon_bond() {
+        let b1 = create_bond(100.0, "01/15/2020", "01/15/2025", 0.05, "%m/%d/%Y").unwrap();
+        assert_approx_eq!(b1.yield_to_maturity(95.7349).unwrap(), 0.06, 1e-5);
+        assert_approx_eq!(b1.yield_to_maturity(100.0).unwrap(), 0.05, 1e-5);
+        let date = NaiveDate::parse_from_str("05/15/2021", "%m/%d/%Y").unwrap();
+        let err = b1.ytm(date, 95.7349, 0.10, 0.20).unwrap_err();
+        assert_eq!(err.message_code, SolverErrorType::NoBracket);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let b1 = create_bond(100.0, "01/15/2020", "01/15/2025", 0.05, "%m/%d/%Y").unwrap();
+        let settlement = NaiveDate::parse_from_str("01/15/2020", "%m/%d/%Y").unwrap();
+        let metrics = b1.metrics(settlement, 0.06);
+        assert_approx_eq!(metrics.macaulay_duration, 4.471679, 1e-4);
+        assert_approx_eq!(metrics.modified_duration, 4.341436, 1e-4);
+        assert_approx_eq!(metrics.dv01, 0.04156269, 1e-6);
+        assert_approx_eq!(metrics.convexity, 22.30473, 1e-3);
+        assert_approx_eq!(
+            b1.macaulay_duration(settlement, 0.06).unwrap(),
+            4.471679,
+            1e-4
+        );
+        let matured = NaiveDate::parse_from_str("01/15/2026", "%m/%d/%Y").unwrap();
+        assert!(b1.macaulay_duration(matured, 0.06).is_none());
+    }
+
+    #[test]
+    fn test_metrics_from_curve() {
+        let curve: Vec<DiscountFactor> = (1..11)
+            .map(|i| DiscountFactor {
+                term: i as f64 / 2.0,
+                discount: f64::exp(-0.05 * i as f64 / 2.0),
+            })
+            .collect();
+        assert_approx_eq!(curve.discount(0.75), f64::exp(-0.0375), 1e-6);
+        assert_approx_eq!(curve.discount(7.0), f64::exp(-0.35), 1e-6);
+        let b1 = create_bond(100.0, "01/15/2020", "01/15/2025", 0.05, "%m/%d/%Y").unwrap();
+        let settlement = NaiveDate::parse_from_str("01/15/2020", "%m/%d/%Y").unwrap();
+        assert_approx_eq!(b1.price_from_curve(settlement, &curve), 99.72465, 1e-3);
+        let metrics = b1.metrics_from_curve(settlement, &curve);
+        assert_approx_eq!(metrics.macaulay_duration, 4.484574, 1e-4);
+        assert_approx_eq!(metrics.dv01, 0.04472226, 1e-6);
+        assert_approx_eq!(metrics.convexity, 21.50891, 1e-3);
+    }
+
+    #[test]
+    fn test_business_day_adjustment() {
+        // 15 May 2021 is a Saturday.
+        let mut b1 = *create_bond(100.0, "05/15/2020", "05/15/2022", 0.02, "%m/%d/%Y").unwrap();
+        let coupon = NaiveDate::parse_from_str("05/15/2021", "%m/%d/%Y").unwrap();
+        assert!(b1.cashflow().iter().any(|cf| cf.time == coupon));
+        b1.payment_convention = BusinessDayConvention::Following;
+        let paid = NaiveDate::parse_from_str("05/17/2021", "%m/%d/%Y").unwrap();
+        assert!(b1.cashflow().iter().any(|cf| cf.time == paid));
+        let settlement = NaiveDate::parse_from_str("05/16/2021", "%m/%d/%Y").unwrap();
+        let unadjusted = b1.accrued_interest(settlement);
+        assert_approx_eq!(unadjusted, 1.0 / 184.0, 1e-5);
+        // Accruing to the adjusted date, the coupon period has not ended on Sunday.
+        b1.accrual_convention = BusinessDayConvention::Following;
+        assert_eq!(b1.coupon_period(settlement).unwrap().1, paid);
+        assert!(b1.accrued_interest(settlement) > unadjusted);
+    }
+
+    #[test]
+    fn test_stub_coupons() {
+        let mut b1 = *create_bond(100.0, "01/10/2020", "06/30/2022", 0.04, "%m/%d/%Y").unwrap();
+        let cashflows = b1.cashflow();
+        assert_eq!(cashflows.len(), 5);
+        assert_eq!(
+            cashflows[1].time,
+            NaiveDate::parse_from_str("12/31/2020", "%m/%d/%Y").unwrap()
+        );
+        // The short first coupon from 10 January to 30 June.
+        assert_approx_eq!(cashflows[0].amount, 2.0 * 172.0 / 182.0, 1e-4);
+        assert_approx_eq!(cashflows[4].amount, 102.0, 1e-4);
+        b1.stub = StubType::LongFirst;
+        let long = b1.cashflow();
+        assert_eq!(long.len(), 4);
+        assert_approx_eq!(long[0].amount, 2.0 + 2.0 * 172.0 / 182.0, 1e-4);
+        // Accrued interest and prices run over the long first period.
+        let settlement = NaiveDate::parse_from_str("03/01/2020", "%m/%d/%Y").unwrap();
+        assert_approx_eq!(b1.accrued_interest(settlement), 2.0 * 51.0 / 182.0, 1e-4);
+        let price = b1.dirty_price(settlement, 0.04);
+        let clean = b1.yield_from_clean_price(settlement, price - b1.accrued_interest(settlement));
+        assert_approx_eq!(clean.unwrap(), 0.04, 1e-4);
+    }
+
+    #[test]
+    fn test_sinking_fund() {
+        let bond = create_bond(100.0, "01/15/2020", "01/15/2025", 0.05, "%m/%d/%Y").unwrap();
+        let sink = |date: &str, accelerated: f64| PrincipalPayment {
+            date: NaiveDate::parse_from_str(date, "%m/%d/%Y").unwrap(),
+            amount: 20.0,
+            accelerated,
+        };
+        let too_much = (*bond.clone())
+            .with_principal_schedule(vec![sink("01/15/2021", 50.0), sink("01/15/2022", 50.0)]);
+        assert!(matches!(
+            too_much.unwrap_err().message_code,
+            ErrorType::InvalidPrincipal
+        ));
+        // The issuer doubles up the sink in 2023.
+        let sinking_fund = (*bond.clone())
+            .with_principal_schedule(vec![
+                sink("01/15/2024", 0.0),
+                sink("01/15/2021", 0.0),
+                sink("01/15/2022", 0.0),
+                sink("01/15/2023", 10.0),
+            ])
+            .unwrap();
+        let amounts: Vec<f64> = sinking_fund.cashflow().iter().map(|cf| cf.amount).collect();
+        let expected = [2.5, 22.5, 2.0, 22.0, 1.5, 31.5, 0.75, 20.75, 0.25, 10.25];
+        assert_eq!(amounts.len(), expected.len());
+        for (amount, expected) in amounts.iter().zip(expected) {
+            assert_approx_eq!(amount, expected, 1e-12);
+        }
+        let settlement = NaiveDate::parse_from_str("02/15/2023", "%m/%d/%Y").unwrap();
+        assert_approx_eq!(sinking_fund.factor(settlement), 0.3, 1e-12);
+        assert_approx_eq!(
+            sinking_fund.accrued_interest(settlement),
+            30.0 * 0.05 * 31.0 / 362.0,
+            1e-12
+        );
+
+        let issue = sinking_fund.issue_date;
+        assert_approx_eq!(sinking_fund.average_life(issue), 2.8, 1e-12);
+        assert_approx_eq!(bond.average_life(issue), 5.0, 1e-12);
+        // A par bond yields its coupon, the bullet at the average life is close to par.
+        let ytm = sinking_fund.yield_from_clean_price(issue, 100.0).unwrap();
+        assert_approx_eq!(ytm, 0.05, 1e-9);
+        let ytal = sinking_fund.yield_to_average_life(issue, 100.0).unwrap();
+        assert_approx_eq!(ytal, 0.05, 1e-12);
+        // Mid-period the average life of 1.25 years snaps to the 07/15/2024 coupon date.
+        let bullet = create_bond(30.0, "01/15/2020", "07/15/2024", 0.05, "%m/%d/%Y").unwrap();
+        assert_approx_eq!(
+            bullet.accrued_interest(settlement),
+            sinking_fund.accrued_interest(settlement),
+            1e-12
+        );
+        let clean_price = bullet.clean_price(settlement, 0.06);
+        let ytal = sinking_fund
+            .yield_to_average_life(settlement, clean_price)
+            .unwrap();
+        assert_approx_eq!(ytal, 0.06, 1e-10);
+        let sinking_duration = sinking_fund.metrics(issue, 0.05).modified_duration;
+        assert!(sinking_duration < bond.metrics(issue, 0.05).modified_duration);
+    }
+
+    #[test]
+    fn test_coupon_steps_and_pik() {
+        let date = |input: &str| NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap();
+        let amounts =
+            |bond: &Bond| -> Vec<f64> { bond.cashflow().iter().map(|cf| cf.amount).collect() };
+        let cash = *create_bond(100.0, "01/15/2020", "01/15/2023", 0.04, "%m/%d/%Y").unwrap();
+        let issue = cash.issue_date;
+
+        let mut step_up = cash.clone();
+        step_up.coupon_steps = vec![CouponStep {
+            date: date("07/15/2021"),
+            rate: 0.05,
+        }];
+        // A downgrade in March 2022 adds 25bp from the next period.
+        step_up.rating_triggers = vec![RatingTrigger {
+            date: date("03/01/2022"),
+            step: 0.0025,
+        }];
+        for (amount, expected) in amounts(&step_up)
+            .iter()
+            .zip([2.0, 2.0, 2.0, 2.5, 2.5, 102.625])
+        {
+            assert_approx_eq!(amount, expected, 1e-12);
+        }
+        assert_approx_eq!(step_up.coupon_rate_at(date("07/15/2022")), 0.0525, 1e-12);
+        assert!(step_up.yield_from_clean_price(issue, 100.0).unwrap() > 0.04);
+
+        let mut pik = cash.clone();
+        pik.payment_in_kind = PaymentInKind::InKind;
+        let pik_amounts = amounts(&pik);
+        assert!(pik_amounts[..5].iter().all(|amount| *amount == 0.0));
+        assert_approx_eq!(pik_amounts[5], 100.0 * f64::powf(1.02, 6.0), 1e-9);
+        assert_approx_eq!(
+            pik.yield_from_clean_price(issue, 100.0).unwrap(),
+            0.04,
+            1e-9
+        );
+        let settlement = date("02/15/2021");
+        assert_approx_eq!(pik.outstanding(settlement), 104.04, 1e-9);
+        assert_approx_eq!(
+            pik.accrued_interest(settlement),
+            104.04 * 0.04 * 31.0 / 362.0,
+            1e-9
+        );
+        assert!(
+            pik.metrics(issue, 0.04).modified_duration
+                > cash.metrics(issue, 0.04).modified_duration
+        );
+
+        let mut toggle = cash.clone();
+        toggle.payment_in_kind = PaymentInKind::Toggle {
+            elected: vec![date("07/15/2020")],
+            spread: 0.0075,
+        };
+        let toggle_amounts = amounts(&toggle);
+        assert_eq!(toggle_amounts[0], 0.0);
+        assert_approx_eq!(toggle.outstanding(date("08/01/2020")), 102.375, 1e-12);
+        assert_approx_eq!(toggle_amounts[1], 102.375 * 0.02, 1e-12);
+        assert_approx_eq!(toggle_amounts[5], 102.375 * 1.02, 1e-12);
+    }
+
+    #[test]
+    fn test_deserialize_baseline_bond() {
+        // The shape of a bond serialized before the conventions were added.
+        let json = r#"{
+            "issuer": {"issuer": "USA"},
+            "principal": 100.0,
+            "issue_date": "2021-05-15",
+            "maturity_date": "2031-05-15",
+            "coupon_rate": 0.01625,
+            "periodicity": "SemiAnnual",
+            "reinvestment_interest": null
+        }"#;
+        let date = |input: &str| NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap();
+        let bond: Bond = serde_json::from_str(json).unwrap();
+        let default = Bond::default();
+        assert_eq!(bond.maturity_date, date("05/15/2031"));
+        assert_eq!(bond.coupon_rate, 0.01625);
+        assert_eq!(bond.day_count, default.day_count);
+        assert_eq!(bond.calendar, default.calendar);
+        assert_eq!(bond.payment_convention, default.payment_convention);
+        assert_eq!(bond.stub, default.stub);
+        assert!(bond.end_of_month && bond.principal_schedule.is_empty());
+        let ytm = bond.yield_from_clean_price(date("11/15/2021"), 100.0);
+        assert_approx_eq!(ytm.unwrap(), 0.01625, 1e-9);
+    }
 }
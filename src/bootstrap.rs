@@ -0,0 +1,497 @@
+//! A sequential bootstrap of a single curve from money market and swap quotes. The
+//! instruments are sorted by maturity and each adds a node at its maturity, whose discount
+//! factor is solved so that the instrument has zero NPV on the curve with the nodes before
+//! it. Deposits and FRAs or futures fix the short end, par swap rates such as the
+//! `SwapRate` records of `load_spot_rates` the rest.
+//!
+//! With a local interpolation, log-linear or linear, a node does not move the discount
+//! factors before the previous node, and every instrument reprices. A spline or monotone
+//! convex interpolation moves them, so the repricing errors of the instruments are
+//! reported with the curve.
+//!
+//! References :
+//!
+//!     . "Interest Rate Modeling", Leif B.G. Andersen, Vladimir V. Piterbarg, Volume 1,
+//!       section 6.2
+//!     . Patrick S. Hagan, Graeme West, "Interpolation Methods for Curve Construction",
+//!       Applied Mathematical Finance, 2006
+pub mod bootstrap {
+    use crate::bond::bond::{DiscountCurve, DiscountFactor, Periodicity};
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::interpolation::interpolation::Interpolation;
+    use crate::rates::rates::{CurveError, CurveErrorType, SwapRate, YieldCurve};
+    use crate::solver::solver::{brent, solve_linear, SolverConfig};
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Serialize};
+
+    /// A simply compounded rate in percent from `start` to `end`.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct RateQuote {
+        pub start: NaiveDate,
+        pub end: NaiveDate,
+        pub rate: f64,
+    }
+
+    /// A future on the rate from `start` to `end`, quoted at a `price` of 100 less the rate
+    /// in percent.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct FuturesQuote {
+        pub start: NaiveDate,
+        pub end: NaiveDate,
+        pub price: f64,
+    }
+
+    /// The instruments of a bootstrap. On a single curve a deposit and an FRA have the
+    /// same value, a deposit usually starts on the curve date. A future is an FRA at 100
+    /// less its price, without a convexity adjustment, and a swap pays its par rate at the
+    /// periodicity of the curve against a floating leg worth par.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub enum Instrument {
+        Deposit(RateQuote),
+        Fra(RateQuote),
+        Futures(FuturesQuote),
+        Swap(SwapRate),
+    }
+
+    /// The value of an instrument on a curve, for [`solve_nodes`].
+    pub type Valuation<'a> = Box<dyn Fn(&YieldCurve) -> f64 + 'a>;
+
+    /// The value of an instrument on curves solved together, for [`solve_nodes_jointly`].
+    pub type JointValuation<'a> = Box<dyn Fn(&[YieldCurve]) -> f64 + 'a>;
+
+    /// How far the curve misses an `instrument`: its `npv` for a unit notional, and the
+    /// `rate_error`, the rate implied by the curve less the quoted rate, as decimals.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Repricing {
+        pub instrument: Instrument,
+        pub term: f64,
+        pub npv: f64,
+        pub rate_error: f64,
+    }
+
+    /// A bootstrapped curve with the repricing of each instrument, in order of maturity.
+    #[derive(Debug, Clone)]
+    pub struct BootstrappedCurve {
+        pub curve: YieldCurve,
+        pub repricing: Vec<Repricing>,
+    }
+
+    impl BootstrappedCurve {
+        /// The largest absolute rate error.
+        pub fn max_rate_error(&self) -> f64 {
+            self.repricing
+                .iter()
+                .map(|repricing| repricing.rate_error.abs())
+                .fold(0.0, f64::max)
+        }
+    }
+
+    /// Bootstraps curves as of a date, with terms on the `day_count` and swaps paying at
+    /// the `periodicity`.
+    ///
+    /// ```ignore
+    /// let bootstrapped = Bootstrapper::new(as_of, DayCountConvention::Act360, Periodicity::Annual)
+    ///     .with_interpolation(Interpolation::MonotoneConvex)
+    ///     .bootstrap(&instruments)?;
+    /// assert!(bootstrapped.max_rate_error() < 1e-6);
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct Bootstrapper {
+        pub as_of: NaiveDate,
+        pub day_count: DayCountConvention,
+        pub periodicity: Periodicity,
+        pub interpolation: Interpolation,
+    }
+
+    impl Bootstrapper {
+        pub fn new(
+            as_of: NaiveDate,
+            day_count: DayCountConvention,
+            periodicity: Periodicity,
+        ) -> Bootstrapper {
+            Bootstrapper {
+                as_of,
+                day_count,
+                periodicity,
+                interpolation: Interpolation::default(),
+            }
+        }
+
+        pub fn with_interpolation(mut self, interpolation: Interpolation) -> Bootstrapper {
+            self.interpolation = interpolation;
+            self
+        }
+
+        /// The term of the last cashflow of the `instrument`.
+        fn maturity(&self, instrument: &Instrument) -> f64 {
+            match instrument {
+                Instrument::Deposit(quote) | Instrument::Fra(quote) => {
+                    self.day_count.year_fraction(self.as_of, quote.end)
+                }
+                Instrument::Futures(quote) => self.day_count.year_fraction(self.as_of, quote.end),
+                Instrument::Swap(quote) => quote.term,
+            }
+        }
+
+        /// The start and end terms and the rate of a money market instrument.
+        fn period(&self, start: NaiveDate, end: NaiveDate, rate: f64) -> (f64, f64, f64) {
+            let term = |date: NaiveDate| self.day_count.year_fraction(self.as_of, date);
+            (term(start), term(end), rate)
+        }
+
+        /// The NPV for a unit notional of receiving the quoted rate, and the rate error.
+        fn reprice(&self, instrument: &Instrument, curve: &YieldCurve) -> (f64, f64) {
+            let (start, end, rate) = match instrument {
+                Instrument::Deposit(quote) | Instrument::Fra(quote) => {
+                    self.period(quote.start, quote.end, quote.rate / 100.0)
+                }
+                Instrument::Futures(quote) => {
+                    self.period(quote.start, quote.end, (100.0 - quote.price) / 100.0)
+                }
+                Instrument::Swap(quote) => {
+                    let step = 1.0 / self.periodicity.periods_per_year();
+                    let mut annuity = 0.0;
+                    let mut end = quote.term;
+                    while end > 1e-9 {
+                        let start = (end - step).max(0.0);
+                        annuity += (end - start) * curve.discount(end);
+                        end = start;
+                    }
+                    let rate = quote.rate / 100.0;
+                    let npv = curve.discount(quote.term) + rate * annuity - 1.0;
+                    return (npv, curve.par_rate(quote.term) - rate);
+                }
+            };
+            let npv = curve.discount(end) * (1.0 + rate * (end - start)) - curve.discount(start);
+            (npv, curve.forward_rate(start, end) - rate)
+        }
+
+        /// The curve with a node at the maturity of each instrument, and the repricing of
+        /// the instruments on it. Two instruments cannot mature together.
+        pub fn bootstrap(
+            &self,
+            instruments: &[Instrument],
+        ) -> Result<BootstrappedCurve, CurveError> {
+            let curve = YieldCurve {
+                as_of: self.as_of,
+                day_count: self.day_count.clone(),
+                periodicity: self.periodicity,
+                discount_factors: Vec::new(),
+                interpolation: self.interpolation,
+            };
+            let valuations: Vec<(f64, Valuation)> = instruments
+                .iter()
+                .map(|instrument| {
+                    let valuation: Valuation =
+                        Box::new(move |curve: &YieldCurve| self.reprice(instrument, curve).0);
+                    (self.maturity(instrument), valuation)
+                })
+                .collect();
+            let curve = solve_nodes(curve, valuations)?;
+            let mut repricing: Vec<Repricing> = instruments
+                .iter()
+                .map(|instrument| {
+                    let (npv, rate_error) = self.reprice(instrument, &curve);
+                    Repricing {
+                        instrument: *instrument,
+                        term: self.maturity(instrument),
+                        npv,
+                        rate_error,
+                    }
+                })
+                .collect();
+            repricing.sort_by(|a, b| a.term.total_cmp(&b.term));
+            Ok(BootstrappedCurve { curve, repricing })
+        }
+    }
+
+    /// Adds a node to the `curve` at the maturity of each instrument, in order of
+    /// maturity, with the discount factor at which the valuation of the instrument is zero
+    /// on the curve so far. The nodes of the curve passed in are kept and must mature
+    /// before the instruments.
+    pub fn solve_nodes(
+        mut curve: YieldCurve,
+        mut instruments: Vec<(f64, Valuation)>,
+    ) -> Result<YieldCurve, CurveError> {
+        if instruments.is_empty() && curve.discount_factors.is_empty() {
+            return Err(CurveError {
+                message: "No instruments to bootstrap.",
+                message_code: CurveErrorType::EmptyCurve,
+            });
+        }
+        instruments.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (maturity, valuation) in instruments {
+            let last = curve.discount_factors.last().map_or(0.0, |node| node.term);
+            if maturity <= last {
+                return Err(CurveError {
+                    message: "Two instruments mature together, or before the curve.",
+                    message_code: CurveErrorType::InvalidTerm,
+                });
+            }
+            curve.discount_factors.push(DiscountFactor {
+                term: maturity,
+                discount: 1.0,
+            });
+            let discount = brent(
+                |discount| {
+                    let mut trial = curve.clone();
+                    if let Some(node) = trial.discount_factors.last_mut() {
+                        node.discount = discount;
+                    }
+                    valuation(&trial)
+                },
+                1e-4,
+                2.0,
+                &SolverConfig::default(),
+            )
+            .map_err(|_| CurveError {
+                message: "No discount factor reprices the instrument.",
+                message_code: CurveErrorType::NoSolution,
+            })?;
+            if let Some(node) = curve.discount_factors.last_mut() {
+                node.discount = discount;
+            }
+        }
+        Ok(curve)
+    }
+
+    /// Adds a node to one of the `curves` at the maturity of each instrument, given as the
+    /// position of its curve, its maturity and its valuation, and solves the nodes of all
+    /// the curves together, for instruments which depend on several curves that depend on
+    /// each other. The zero rates of the nodes are found by Newton's method on the
+    /// valuations of the instruments, with a forward difference Jacobian, starting from a
+    /// flat zero curve. The nodes of the curves passed in are kept and must mature before
+    /// the instruments of their curve.
+    pub fn solve_nodes_jointly(
+        mut curves: Vec<YieldCurve>,
+        instruments: Vec<(usize, f64, JointValuation)>,
+    ) -> Result<Vec<YieldCurve>, CurveError> {
+        if instruments.is_empty() {
+            return Err(CurveError {
+                message: "No instruments to bootstrap.",
+                message_code: CurveErrorType::EmptyCurve,
+            });
+        }
+        let existing: Vec<usize> = curves.iter().map(|c| c.discount_factors.len()).collect();
+        for (position, maturity, _) in &instruments {
+            curves[*position].discount_factors.push(DiscountFactor {
+                term: *maturity,
+                discount: 1.0,
+            });
+        }
+        for (curve, existing) in curves.iter_mut().zip(&existing) {
+            curve.discount_factors[*existing..].sort_by(|a, b| a.term.total_cmp(&b.term));
+            if curve
+                .discount_factors
+                .windows(2)
+                .any(|w| w[1].term <= w[0].term)
+            {
+                return Err(CurveError {
+                    message: "Two instruments mature together, or before the curve.",
+                    message_code: CurveErrorType::InvalidTerm,
+                });
+            }
+        }
+        // The node of each instrument, as the position of its curve and of the node.
+        let nodes: Vec<(usize, usize)> = instruments
+            .iter()
+            .map(|(position, maturity, _)| {
+                let curve = &curves[*position].discount_factors;
+                let node = curve.iter().position(|df| df.term == *maturity).unwrap();
+                (*position, node)
+            })
+            .collect();
+        let set_rates = |curves: &mut Vec<YieldCurve>, rates: &[f64]| {
+            for ((position, node), rate) in nodes.iter().zip(rates) {
+                let df = &mut curves[*position].discount_factors[*node];
+                df.discount = f64::exp(-rate * df.term);
+            }
+        };
+        let residuals = |curves: &[YieldCurve]| -> Vec<f64> {
+            instruments
+                .iter()
+                .map(|(_, _, valuation)| valuation(curves))
+                .collect()
+        };
+        let no_solution = CurveError {
+            message: "No discount factors reprice the instruments together.",
+            message_code: CurveErrorType::NoSolution,
+        };
+        let config = SolverConfig::default();
+        let bump = 1e-7;
+        let mut rates = vec![0.0; nodes.len()];
+        set_rates(&mut curves, &rates);
+        for _ in 0..config.max_iterations {
+            let values = residuals(&curves);
+            if values.iter().any(|value| !value.is_finite()) {
+                return Err(no_solution);
+            }
+            if values.iter().all(|value| value.abs() <= config.tolerance) {
+                return Ok(curves);
+            }
+            let mut jacobian = vec![vec![0.0; rates.len()]; values.len()];
+            for j in 0..rates.len() {
+                let mut bumped = rates.clone();
+                bumped[j] += bump;
+                let mut trial = curves.clone();
+                set_rates(&mut trial, &bumped);
+                let bumped_values = residuals(&trial);
+                for (i, row) in jacobian.iter_mut().enumerate() {
+                    row[j] = (bumped_values[i] - values[i]) / bump;
+                }
+            }
+            let negative: Vec<f64> = values.iter().map(|value| -value).collect();
+            let step = solve_linear(jacobian, negative).ok_or(no_solution)?;
+            for (rate, step) in rates.iter_mut().zip(step) {
+                *rate += step;
+            }
+            set_rates(&mut curves, &rates);
+        }
+        Err(no_solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bond::bond::Periodicity;
+    use crate::bootstrap::bootstrap::*;
+    use crate::data_loader::data_loader::load_spot_rates;
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::interpolation::interpolation::Interpolation;
+    use crate::rates::rates::{CurveErrorType, OvernightRateType, YieldCurve};
+    use assert_approx_eq::assert_approx_eq;
+    use chrono::NaiveDate;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_bootstrap_reprices_instruments() {
+        let as_of = date("05/14/2021");
+        let swap_rates = load_spot_rates(
+            String::from("tests/spot_rates.csv"),
+            OvernightRateType::SOFR,
+        )
+        .await
+        .unwrap();
+        let bootstrapper =
+            Bootstrapper::new(as_of, DayCountConvention::Act360, Periodicity::SemiAnnual);
+
+        // The swaps alone give the curve of the par rate bootstrap.
+        let swaps: Vec<Instrument> = swap_rates.iter().map(|q| Instrument::Swap(*q)).collect();
+        let bootstrapped = bootstrapper.bootstrap(&swaps).unwrap();
+        let by_par_rates = YieldCurve::from_swap_rates(
+            &swap_rates,
+            DayCountConvention::Act360,
+            Periodicity::SemiAnnual,
+        )
+        .unwrap();
+        for (node, expected) in bootstrapped
+            .curve
+            .discount_factors
+            .iter()
+            .zip(by_par_rates.discount_factors.iter())
+        {
+            assert_approx_eq!(node.term, expected.term, 1e-12);
+            assert_approx_eq!(node.discount, expected.discount, 1e-10);
+        }
+
+        // With a deposit, a future and an FRA before the swaps.
+        let mut instruments = swaps.clone();
+        instruments.push(Instrument::Deposit(RateQuote {
+            start: as_of,
+            end: date("06/14/2021"),
+            rate: 0.02,
+        }));
+        instruments.push(Instrument::Futures(FuturesQuote {
+            start: date("06/16/2021"),
+            end: date("09/15/2021"),
+            price: 99.97,
+        }));
+        instruments.push(Instrument::Fra(RateQuote {
+            start: date("09/14/2021"),
+            end: date("12/14/2021"),
+            rate: 0.04,
+        }));
+        let bootstrapped = bootstrapper.bootstrap(&instruments).unwrap();
+        let curve = &bootstrapped.curve;
+        assert_eq!(curve.discount_factors.len(), 7);
+        assert_eq!(bootstrapped.repricing.len(), 7);
+        assert!(matches!(
+            bootstrapped.repricing[0].instrument,
+            Instrument::Deposit(_)
+        ));
+        for repricing in &bootstrapped.repricing {
+            assert!(repricing.npv.abs() < 1e-10);
+        }
+        assert!(bootstrapped.max_rate_error() < 1e-9);
+        assert_approx_eq!(
+            curve.discount_at(date("06/14/2021")),
+            1.0 / (1.0 + 0.0002 * 31.0 / 360.0),
+            1e-12
+        );
+        let years = |d: &str| curve.term(date(d));
+        assert_approx_eq!(
+            curve.forward_rate(years("09/14/2021"), years("12/14/2021")),
+            0.0004,
+            1e-9
+        );
+        for quote in &swap_rates {
+            assert_approx_eq!(curve.par_rate(quote.term), quote.rate / 100.0, 1e-9);
+        }
+
+        // A spline moves the earlier nodes as later ones are added, and the errors say by
+        // how much.
+        let spline = bootstrapper
+            .with_interpolation(Interpolation::NaturalCubicSpline)
+            .bootstrap(&instruments)
+            .unwrap();
+        assert_eq!(
+            spline.curve.interpolation,
+            Interpolation::NaturalCubicSpline
+        );
+        assert!(spline.max_rate_error() > 1e-9);
+        assert!(spline.max_rate_error() < 1e-3);
+        let last = spline.repricing.last().unwrap();
+        assert!(last.rate_error.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_bootstrap_errors() {
+        let as_of = date("05/14/2021");
+        let bootstrapper =
+            Bootstrapper::new(as_of, DayCountConvention::Act360, Periodicity::Annual);
+        assert_eq!(
+            bootstrapper.bootstrap(&[]).unwrap_err().message_code,
+            CurveErrorType::EmptyCurve
+        );
+        let deposit = Instrument::Deposit(RateQuote {
+            start: as_of,
+            end: date("08/14/2021"),
+            rate: 0.05,
+        });
+        let fra = Instrument::Fra(RateQuote {
+            start: date("05/14/2021"),
+            end: date("08/14/2021"),
+            rate: 0.06,
+        });
+        assert_eq!(
+            bootstrapper
+                .bootstrap(&[deposit, fra])
+                .unwrap_err()
+                .message_code,
+            CurveErrorType::InvalidTerm
+        );
+        // A deposit at minus 100000 percent needs a discount factor above two.
+        let deposit = Instrument::Deposit(RateQuote {
+            start: as_of,
+            end: date("08/14/2021"),
+            rate: -100000.0,
+        });
+        assert_eq!(
+            bootstrapper.bootstrap(&[deposit]).unwrap_err().message_code,
+            CurveErrorType::NoSolution
+        );
+    }
+}
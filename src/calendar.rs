@@ -0,0 +1,670 @@
+//! Holiday calendars and business day conventions. Coupon and payment dates generated by
+//! adding months fall on weekends and holidays, a business day convention moves them to a
+//! good business day of the calendar of the market.
+pub mod calendar {
+    use chrono::{Datelike, Days, NaiveDate, Weekday};
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeSet;
+
+    /// How a date which is not a business day is moved.
+    ///
+    /// * `Following` - the next business day.
+    /// * `ModifiedFollowing` - the next business day, unless that is in the next month, in
+    ///   which case the previous business day.
+    /// * `Preceding` - the previous business day.
+    /// * `Unadjusted` - the date is not moved.
+    #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+    pub enum BusinessDayConvention {
+        Following,
+        ModifiedFollowing,
+        Preceding,
+        #[default]
+        Unadjusted,
+    }
+
+    /// The holiday calendars. Weekends are never business days.
+    ///
+    /// * `WeekendsOnly` - no holidays.
+    /// * `Sifma` - US government securities, the holidays recommended by SIFMA.
+    /// * `Nyse` - New York Stock Exchange.
+    /// * `UnitedKingdom` - England and Wales bank holidays, used by the London markets.
+    /// * `Target` - the TARGET2 payment system for the euro.
+    /// * `FederalReserve` - the Federal Reserve Banks, open on Good Friday, the calendar of
+    ///   the Fed Funds rate.
+    /// * `Japan` - Tokyo banks, national holidays and the year end closure.
+    /// * `Switzerland` - Zurich banks.
+    /// * `Canada` - Toronto settlement.
+    /// * `Australia` - Sydney settlement.
+    /// * `Brazil` - the national holidays published by ANBIMA, the calendar of the BUS/252
+    ///   day count.
+    /// * `Joint` - a holiday in any of the calendars, for instruments which settle in more than
+    ///   one market.
+    /// * `Custom` - an explicit set of holidays, see
+    ///   [`crate::data_loader::data_loader::load_holidays`].
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    pub enum Calendar {
+        WeekendsOnly,
+        Sifma,
+        Nyse,
+        UnitedKingdom,
+        Target,
+        FederalReserve,
+        Japan,
+        Switzerland,
+        Canada,
+        Australia,
+        Brazil,
+        Joint(Vec<Calendar>),
+        Custom(BTreeSet<NaiveDate>),
+    }
+
+    fn is_weekend(date: NaiveDate) -> bool {
+        date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun
+    }
+
+    /// Easter Sunday by the anonymous Gregorian algorithm.
+    fn easter_sunday(year: i32) -> NaiveDate {
+        let a = year % 19;
+        let b = year / 100;
+        let c = year % 100;
+        let d = b / 4;
+        let e = b % 4;
+        let f = (b + 8) / 25;
+        let g = (b - f + 1) / 3;
+        let h = (19 * a + b - d - g + 15) % 30;
+        let i = c / 4;
+        let k = c % 4;
+        let l = (32 + 2 * e + 2 * i - h - k) % 7;
+        let m = (a + 11 * h + 22 * l) / 451;
+        let month = (h + l - 7 * m + 114) / 31;
+        let day = (h + l - 7 * m + 114) % 31 + 1;
+        NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap()
+    }
+
+    fn good_friday(year: i32) -> NaiveDate {
+        easter_sunday(year) - Days::new(2)
+    }
+
+    fn easter_monday(year: i32) -> NaiveDate {
+        easter_sunday(year) + Days::new(1)
+    }
+
+    /// The `n`-th `weekday` of the month.
+    fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).unwrap()
+    }
+
+    fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+            .unwrap_or_else(|| nth_weekday(year, month, weekday, 4))
+    }
+
+    /// A US holiday on a Saturday is observed on the Friday before, on a Sunday on the Monday
+    /// after.
+    fn us_observed(year: i32, month: u32, day: u32) -> NaiveDate {
+        let date = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        match date.weekday() {
+            Weekday::Sat => date - Days::new(1),
+            Weekday::Sun => date + Days::new(1),
+            _ => date,
+        }
+    }
+
+    fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// The holidays common to the US bond and stock markets. New Year's Day on a Saturday is
+    /// not moved to the Friday before, which is the last day of the year.
+    fn is_us_holiday(date: NaiveDate) -> bool {
+        let (y, m, d) = (date.year(), date.month(), date.day());
+        (m == 1 && d == 1)
+            || (m == 1 && d == 2 && date.weekday() == Weekday::Mon)
+            || (y >= 1998 && date == nth_weekday(y, 1, Weekday::Mon, 3))
+            || date == nth_weekday(y, 2, Weekday::Mon, 3)
+            || date == good_friday(y)
+            || date == last_weekday(y, 5, Weekday::Mon)
+            || (y >= 2022 && date == us_observed(y, 6, 19))
+            || date == us_observed(y, 7, 4)
+            || date == nth_weekday(y, 9, Weekday::Mon, 1)
+            || date == nth_weekday(y, 11, Weekday::Thu, 4)
+            || date == us_observed(y, 12, 25)
+    }
+
+    fn is_sifma_holiday(date: NaiveDate) -> bool {
+        let y = date.year();
+        is_us_holiday(date)
+            || date == nth_weekday(y, 10, Weekday::Mon, 2)
+            || date == us_observed(y, 11, 11)
+            // Closures for the September 11 attacks and Hurricane Sandy.
+            || date == ymd(2001, 9, 11)
+            || date == ymd(2001, 9, 12)
+            || date == ymd(2012, 10, 30)
+    }
+
+    fn is_nyse_holiday(date: NaiveDate) -> bool {
+        is_us_holiday(date)
+            // Closures for the September 11 attacks, Hurricane Sandy and days of mourning
+            // for former presidents.
+            || (ymd(2001, 9, 11) <= date && date <= ymd(2001, 9, 14))
+            || date == ymd(2004, 6, 11)
+            || date == ymd(2007, 1, 2)
+            || date == ymd(2012, 10, 29)
+            || date == ymd(2012, 10, 30)
+            || date == ymd(2018, 12, 5)
+            || date == ymd(2025, 1, 9)
+    }
+
+    /// Bank holidays on a weekend are moved to the following Monday (or Tuesday at
+    /// Christmas).
+    fn is_uk_holiday(date: NaiveDate) -> bool {
+        let (y, m, d, w) = (date.year(), date.month(), date.day(), date.weekday());
+        let early_may = match y {
+            1995 | 2020 => ymd(y, 5, 8),
+            _ => nth_weekday(y, 5, Weekday::Mon, 1),
+        };
+        let spring = match y {
+            2002 | 2012 => ymd(y, 6, 4),
+            2022 => ymd(y, 6, 2),
+            _ => last_weekday(y, 5, Weekday::Mon),
+        };
+        let monday_or_tuesday = w == Weekday::Mon || w == Weekday::Tue;
+        (m == 1 && (d == 1 || ((d == 2 || d == 3) && w == Weekday::Mon)))
+            || date == good_friday(y)
+            || date == easter_monday(y)
+            || date == early_may
+            || date == spring
+            || date == last_weekday(y, 8, Weekday::Mon)
+            || (m == 12 && (d == 25 || d == 26 || ((d == 27 || d == 28) && monday_or_tuesday)))
+            // Royal occasions and the millennium.
+            || date == ymd(1999, 12, 31)
+            || date == ymd(2002, 6, 3)
+            || date == ymd(2011, 4, 29)
+            || date == ymd(2012, 6, 5)
+            || date == ymd(2022, 6, 3)
+            || date == ymd(2022, 9, 19)
+            || date == ymd(2023, 5, 8)
+    }
+
+    fn is_target_holiday(date: NaiveDate) -> bool {
+        let (y, m, d) = (date.year(), date.month(), date.day());
+        (m == 1 && d == 1)
+            || (y >= 2000 && (date == good_friday(y) || date == easter_monday(y)))
+            || (y >= 2000 && m == 5 && d == 1)
+            || (m == 12 && d == 25)
+            || (y >= 2000 && m == 12 && d == 26)
+            || date == ymd(1998, 12, 31)
+            || date == ymd(1999, 12, 31)
+            || date == ymd(2001, 12, 31)
+    }
+
+    /// The federal holidays. A holiday on a Sunday is observed on the Monday, the Reserve
+    /// Banks stay open on the Friday before a holiday on a Saturday.
+    fn is_federal_reserve_holiday(date: NaiveDate) -> bool {
+        let (y, m, d, w) = (date.year(), date.month(), date.day(), date.weekday());
+        let on_or_monday =
+            |month: u32, day: u32| m == month && (d == day || (d == day + 1 && w == Weekday::Mon));
+        on_or_monday(1, 1)
+            || (y >= 1998 && date == nth_weekday(y, 1, Weekday::Mon, 3))
+            || date == nth_weekday(y, 2, Weekday::Mon, 3)
+            || date == last_weekday(y, 5, Weekday::Mon)
+            || (y >= 2022 && on_or_monday(6, 19))
+            || on_or_monday(7, 4)
+            || date == nth_weekday(y, 9, Weekday::Mon, 1)
+            || date == nth_weekday(y, 10, Weekday::Mon, 2)
+            || on_or_monday(11, 11)
+            || date == nth_weekday(y, 11, Weekday::Thu, 4)
+            || on_or_monday(12, 25)
+    }
+
+    /// The day of the vernal or autumnal equinox in Japan, by the approximation of the
+    /// National Astronomical Observatory for 1980 to 2099.
+    fn japan_equinox(year: i32, base: f64) -> u32 {
+        let years = year - 1980;
+        (base + 0.242194 * years as f64 - (years / 4) as f64).floor() as u32
+    }
+
+    /// The national holidays of Japan since 2000, with the holidays moved for the Tokyo
+    /// Olympics and the enthronement of 2019.
+    fn is_japan_national_holiday(date: NaiveDate) -> bool {
+        let (y, m, d) = (date.year(), date.month(), date.day());
+        let marine = match y {
+            2020 => ymd(y, 7, 23),
+            2021 => ymd(y, 7, 22),
+            _ => nth_weekday(y, 7, Weekday::Mon, 3),
+        };
+        let mountain = match y {
+            2020 => ymd(y, 8, 10),
+            2021 => ymd(y, 8, 8),
+            _ => ymd(y, 8, 11),
+        };
+        let sports = match y {
+            2020 => ymd(y, 7, 24),
+            2021 => ymd(y, 7, 23),
+            _ => nth_weekday(y, 10, Weekday::Mon, 2),
+        };
+        (m == 1 && d == 1)
+            || date == nth_weekday(y, 1, Weekday::Mon, 2)
+            || (m == 2 && d == 11)
+            || (y >= 2020 && m == 2 && d == 23)
+            || (m == 3 && d == japan_equinox(y, 20.8431))
+            || (m == 4 && d == 29)
+            || (m == 5 && (3..=5).contains(&d))
+            || (y >= 2003 && date == marine)
+            || (y >= 2016 && date == mountain)
+            || (y >= 2003 && date == nth_weekday(y, 9, Weekday::Mon, 3))
+            || (m == 9 && d == japan_equinox(y, 23.2488))
+            || date == sports
+            || (m == 11 && (d == 3 || d == 23))
+            || (y <= 2018 && m == 12 && d == 23)
+            || date == ymd(2019, 4, 30)
+            || date == ymd(2019, 5, 1)
+            || date == ymd(2019, 5, 2)
+            || date == ymd(2019, 10, 22)
+    }
+
+    /// A national holiday on a Sunday moves to the next day which is not a holiday, and a
+    /// day between two national holidays is a holiday. Banks also close on the 2nd and 3rd
+    /// of January and on the 31st of December.
+    fn is_japan_holiday(date: NaiveDate) -> bool {
+        let (m, d) = (date.month(), date.day());
+        let substitute = {
+            let mut day = date - Days::new(1);
+            let mut sunday = false;
+            while is_japan_national_holiday(day) {
+                sunday |= day.weekday() == Weekday::Sun;
+                day = day - Days::new(1);
+            }
+            sunday
+        };
+        let between = is_japan_national_holiday(date - Days::new(1))
+            && is_japan_national_holiday(date + Days::new(1));
+        is_japan_national_holiday(date)
+            || substitute
+            || between
+            || (m == 1 && (d == 2 || d == 3))
+            || (m == 12 && d == 31)
+    }
+
+    fn is_swiss_holiday(date: NaiveDate) -> bool {
+        let (y, m, d) = (date.year(), date.month(), date.day());
+        let easter = easter_sunday(y);
+        (m == 1 && (d == 1 || d == 2))
+            || date == good_friday(y)
+            || date == easter_monday(y)
+            || date == easter + Days::new(39)
+            || date == easter + Days::new(50)
+            || (m == 5 && d == 1)
+            || (m == 8 && d == 1)
+            || (m == 12 && (d == 25 || d == 26))
+    }
+
+    /// Holidays on a weekend are moved to the following Monday, Christmas and Boxing Day
+    /// to the Monday and Tuesday.
+    fn is_canada_holiday(date: NaiveDate) -> bool {
+        let (y, m, d, w) = (date.year(), date.month(), date.day(), date.weekday());
+        let on_or_monday = |month: u32, day: u32| {
+            m == month && (d == day || ((d == day + 1 || d == day + 2) && w == Weekday::Mon))
+        };
+        let monday_or_tuesday = w == Weekday::Mon || w == Weekday::Tue;
+        on_or_monday(1, 1)
+            || (y >= 2008 && date == nth_weekday(y, 2, Weekday::Mon, 3))
+            || date == good_friday(y)
+            // Victoria Day, the Monday before the 25th of May.
+            || (m == 5 && (18..=24).contains(&d) && w == Weekday::Mon)
+            || on_or_monday(7, 1)
+            || date == nth_weekday(y, 8, Weekday::Mon, 1)
+            || date == nth_weekday(y, 9, Weekday::Mon, 1)
+            || (y >= 2021 && (on_or_monday(9, 30) || (m == 10 && d <= 2 && w == Weekday::Mon)))
+            || date == nth_weekday(y, 10, Weekday::Mon, 2)
+            || on_or_monday(11, 11)
+            || (m == 12 && (d == 25 || d == 26 || ((d == 27 || d == 28) && monday_or_tuesday)))
+    }
+
+    fn is_australia_holiday(date: NaiveDate) -> bool {
+        let (y, m, d, w) = (date.year(), date.month(), date.day(), date.weekday());
+        let on_or_monday = |month: u32, day: u32| {
+            m == month && (d == day || ((d == day + 1 || d == day + 2) && w == Weekday::Mon))
+        };
+        let monday_or_tuesday = w == Weekday::Mon || w == Weekday::Tue;
+        on_or_monday(1, 1)
+            || on_or_monday(1, 26)
+            || date == good_friday(y)
+            || date == easter_monday(y)
+            // Anzac Day is moved from a Sunday but not from a Saturday.
+            || (m == 4 && (d == 25 || (d == 26 && w == Weekday::Mon)))
+            || date == nth_weekday(y, 6, Weekday::Mon, 2)
+            || date == nth_weekday(y, 8, Weekday::Mon, 1)
+            || date == nth_weekday(y, 10, Weekday::Mon, 1)
+            || (m == 12 && (d == 25 || d == 26 || ((d == 27 || d == 28) && monday_or_tuesday)))
+            // The national day of mourning for Queen Elizabeth II.
+            || date == ymd(2022, 9, 22)
+    }
+
+    /// Brazilian holidays are not moved when they fall on a weekend. Carnival is the Monday
+    /// and Tuesday before Ash Wednesday, Black Consciousness Day a national holiday from 2024.
+    fn is_brazil_holiday(date: NaiveDate) -> bool {
+        let (y, m, d) = (date.year(), date.month(), date.day());
+        let easter = easter_sunday(y);
+        (m == 1 && d == 1)
+            || date == easter - Days::new(48)
+            || date == easter - Days::new(47)
+            || date == good_friday(y)
+            || (m == 4 && d == 21)
+            || (m == 5 && d == 1)
+            // Corpus Christi.
+            || date == easter + Days::new(60)
+            || (m == 9 && d == 7)
+            || (m == 10 && d == 12)
+            || (m == 11 && d == 2)
+            || (m == 11 && d == 15)
+            || (y >= 2024 && m == 11 && d == 20)
+            || (m == 12 && d == 25)
+    }
+
+    impl Calendar {
+        /// Whether `date` is a holiday on a weekday.
+        pub fn is_holiday(&self, date: NaiveDate) -> bool {
+            if is_weekend(date) {
+                return false;
+            }
+            match self {
+                Calendar::WeekendsOnly => false,
+                Calendar::Sifma => is_sifma_holiday(date),
+                Calendar::Nyse => is_nyse_holiday(date),
+                Calendar::UnitedKingdom => is_uk_holiday(date),
+                Calendar::Target => is_target_holiday(date),
+                Calendar::FederalReserve => is_federal_reserve_holiday(date),
+                Calendar::Japan => is_japan_holiday(date),
+                Calendar::Switzerland => is_swiss_holiday(date),
+                Calendar::Canada => is_canada_holiday(date),
+                Calendar::Australia => is_australia_holiday(date),
+                Calendar::Brazil => is_brazil_holiday(date),
+                Calendar::Joint(calendars) => calendars.iter().any(|c| c.is_holiday(date)),
+                Calendar::Custom(holidays) => holidays.contains(&date),
+            }
+        }
+
+        pub fn is_business_day(&self, date: NaiveDate) -> bool {
+            !is_weekend(date) && !self.is_holiday(date)
+        }
+
+        /// Move `date` to a business day by the `convention`.
+        pub fn adjust(&self, date: NaiveDate, convention: BusinessDayConvention) -> NaiveDate {
+            match convention {
+                BusinessDayConvention::Unadjusted => date,
+                BusinessDayConvention::Following => {
+                    let mut current = date;
+                    while !self.is_business_day(current) {
+                        current = current + Days::new(1);
+                    }
+                    current
+                }
+                BusinessDayConvention::Preceding => {
+                    let mut current = date;
+                    while !self.is_business_day(current) {
+                        current = current - Days::new(1);
+                    }
+                    current
+                }
+                BusinessDayConvention::ModifiedFollowing => {
+                    let following = self.adjust(date, BusinessDayConvention::Following);
+                    if following.month() == date.month() {
+                        following
+                    } else {
+                        self.adjust(date, BusinessDayConvention::Preceding)
+                    }
+                }
+            }
+        }
+
+        /// The business day `days` business days after `date`, or before if negative.
+        pub fn advance(&self, date: NaiveDate, days: i32) -> NaiveDate {
+            let mut current = date;
+            let mut remaining = days.abs();
+            while remaining > 0 {
+                current = if days > 0 {
+                    current + Days::new(1)
+                } else {
+                    current - Days::new(1)
+                };
+                if self.is_business_day(current) {
+                    remaining -= 1;
+                }
+            }
+            current
+        }
+
+        /// Business days in the interval (start, end].
+        pub fn business_days_between(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+            start
+                .iter_days()
+                .skip(1)
+                .take_while(|date| *date <= end)
+                .filter(|date| self.is_business_day(*date))
+                .count() as i64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calendar::calendar::*;
+    use chrono::NaiveDate;
+    use std::collections::BTreeSet;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap()
+    }
+
+    #[test]
+    fn test_us_calendars() {
+        let holidays_2024 = [
+            "01/01/2024",
+            "01/15/2024",
+            "02/19/2024",
+            "03/29/2024",
+            "05/27/2024",
+            "06/19/2024",
+            "07/04/2024",
+            "09/02/2024",
+            "11/28/2024",
+            "12/25/2024",
+        ];
+        for holiday in holidays_2024 {
+            assert!(Calendar::Sifma.is_holiday(date(holiday)), "{}", holiday);
+            assert!(Calendar::Nyse.is_holiday(date(holiday)), "{}", holiday);
+        }
+        // Columbus and Veterans Day close the bond market but not the stock market.
+        for holiday in ["10/14/2024", "11/11/2024"] {
+            assert!(Calendar::Sifma.is_holiday(date(holiday)));
+            assert!(Calendar::Nyse.is_business_day(date(holiday)));
+        }
+        // Independence Day 2026 is a Saturday, observed on the Friday.
+        assert!(Calendar::Nyse.is_holiday(date("07/03/2026")));
+        // New Year's Day 2022 was a Saturday and was not observed.
+        assert!(Calendar::Sifma.is_business_day(date("12/31/2021")));
+        assert!(Calendar::Nyse.is_holiday(date("01/09/2025")));
+    }
+
+    #[test]
+    fn test_uk_and_target_calendars() {
+        let uk_2022 = [
+            "01/03/2022",
+            "04/15/2022",
+            "04/18/2022",
+            "05/02/2022",
+            "06/02/2022",
+            "06/03/2022",
+            "08/29/2022",
+            "09/19/2022",
+            "12/26/2022",
+            "12/27/2022",
+        ];
+        for holiday in uk_2022 {
+            assert!(
+                Calendar::UnitedKingdom.is_holiday(date(holiday)),
+                "{}",
+                holiday
+            );
+        }
+        assert!(Calendar::UnitedKingdom.is_business_day(date("05/30/2022")));
+        for holiday in ["04/18/2025", "04/21/2025", "05/01/2025", "12/26/2025"] {
+            assert!(Calendar::Target.is_holiday(date(holiday)));
+        }
+        let joint = Calendar::Joint(vec![Calendar::Target, Calendar::Sifma]);
+        assert!(joint.is_holiday(date("05/01/2025")));
+        assert!(joint.is_holiday(date("05/26/2025")));
+        assert!(joint.is_business_day(date("05/27/2025")));
+        let custom = Calendar::Custom(BTreeSet::from([date("03/03/2025")]));
+        assert!(custom.is_holiday(date("03/03/2025")));
+    }
+
+    #[test]
+    fn test_fixing_calendars() {
+        // Substitute holidays follow the national holidays on a Sunday.
+        let japan_2024 = [
+            "01/01/2024",
+            "01/02/2024",
+            "01/03/2024",
+            "01/08/2024",
+            "02/12/2024",
+            "02/23/2024",
+            "03/20/2024",
+            "04/29/2024",
+            "05/03/2024",
+            "05/06/2024",
+            "07/15/2024",
+            "08/12/2024",
+            "09/16/2024",
+            "09/23/2024",
+            "10/14/2024",
+            "11/04/2024",
+            "12/31/2024",
+        ];
+        for holiday in japan_2024 {
+            assert!(Calendar::Japan.is_holiday(date(holiday)), "{}", holiday);
+        }
+        // A day between two holidays, and the enthronement of 2019.
+        assert!(Calendar::Japan.is_holiday(date("09/22/2026")));
+        assert!(Calendar::Japan.is_holiday(date("05/02/2019")));
+        assert!(Calendar::Japan.is_business_day(date("11/25/2024")));
+
+        let swiss_2025 = [
+            "01/01/2025",
+            "01/02/2025",
+            "04/18/2025",
+            "04/21/2025",
+            "05/01/2025",
+            "05/29/2025",
+            "06/09/2025",
+            "08/01/2025",
+            "12/25/2025",
+            "12/26/2025",
+        ];
+        for holiday in swiss_2025 {
+            assert!(
+                Calendar::Switzerland.is_holiday(date(holiday)),
+                "{}",
+                holiday
+            );
+        }
+
+        let canada_2024 = [
+            "01/01/2024",
+            "02/19/2024",
+            "03/29/2024",
+            "05/20/2024",
+            "07/01/2024",
+            "08/05/2024",
+            "09/02/2024",
+            "09/30/2024",
+            "10/14/2024",
+            "11/11/2024",
+            "12/25/2024",
+            "12/26/2024",
+        ];
+        for holiday in canada_2024 {
+            assert!(Calendar::Canada.is_holiday(date(holiday)), "{}", holiday);
+        }
+        assert!(Calendar::Canada.is_holiday(date("12/27/2022")));
+
+        let australia_2024 = [
+            "01/01/2024",
+            "01/26/2024",
+            "03/29/2024",
+            "04/01/2024",
+            "04/25/2024",
+            "06/10/2024",
+            "08/05/2024",
+            "10/07/2024",
+            "12/25/2024",
+            "12/26/2024",
+        ];
+        for holiday in australia_2024 {
+            assert!(Calendar::Australia.is_holiday(date(holiday)), "{}", holiday);
+        }
+        // Anzac Day 2026 is a Saturday.
+        assert!(Calendar::Australia.is_business_day(date("04/27/2026")));
+
+        let brazil_2024 = [
+            "01/01/2024",
+            "02/12/2024",
+            "02/13/2024",
+            "03/29/2024",
+            "05/01/2024",
+            "05/30/2024",
+            "11/15/2024",
+            "11/20/2024",
+            "12/25/2024",
+        ];
+        for holiday in brazil_2024 {
+            assert!(Calendar::Brazil.is_holiday(date(holiday)), "{}", holiday);
+        }
+        // Black Consciousness Day became a national holiday in 2024.
+        assert!(Calendar::Brazil.is_business_day(date("11/20/2023")));
+
+        // The Reserve Banks are open on Good Friday and on the Friday before Veterans Day
+        // on a Saturday.
+        let fed = Calendar::FederalReserve;
+        assert!(fed.is_business_day(date("03/29/2024")));
+        assert!(fed.is_business_day(date("11/10/2023")));
+        assert!(Calendar::Sifma.is_holiday(date("11/10/2023")));
+        assert!(fed.is_holiday(date("10/14/2024")));
+        assert!(fed.is_holiday(date("01/02/2023")));
+    }
+
+    #[test]
+    fn test_business_day_conventions() {
+        let calendar = Calendar::Sifma;
+        // Saturday, 31 May 2025.
+        let month_end = date("05/31/2025");
+        assert_eq!(
+            calendar.adjust(month_end, BusinessDayConvention::Following),
+            date("06/02/2025")
+        );
+        assert_eq!(
+            calendar.adjust(month_end, BusinessDayConvention::ModifiedFollowing),
+            date("05/30/2025")
+        );
+        assert_eq!(
+            calendar.adjust(month_end, BusinessDayConvention::Preceding),
+            date("05/30/2025")
+        );
+        assert_eq!(
+            calendar.adjust(month_end, BusinessDayConvention::Unadjusted),
+            month_end
+        );
+        // Saturday before Memorial Day.
+        assert_eq!(
+            calendar.adjust(date("05/24/2025"), BusinessDayConvention::ModifiedFollowing),
+            date("05/27/2025")
+        );
+        assert_eq!(calendar.advance(date("05/23/2025"), 1), date("05/27/2025"));
+        assert_eq!(calendar.advance(date("05/27/2025"), -1), date("05/23/2025"));
+        assert_eq!(
+            calendar.business_days_between(date("05/23/2025"), date("05/30/2025")),
+            4
+        );
+    }
+}
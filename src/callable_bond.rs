@@ -1,6 +1,10 @@
 pub mod callable_bonds {
-    use crate::bond::bond::Bond;
-    use chrono::{NaiveDate};
+    use crate::bond::bond::{Bond, DiscountCurve, DiscountFactor};
+    use crate::lattice::lattice::{
+        Exercise, LatticeError, LatticeErrorType, ShortRateModel, ShortRateTree,
+    };
+    use crate::solver::solver::{brent, SolverConfig, SolverError};
+    use chrono::{Days, NaiveDate};
     use serde::{Deserialize, Serialize};
     use std::cmp::Ordering;
     use std::cmp::{Eq, Ord, PartialEq, PartialOrd};
@@ -12,11 +16,18 @@ pub mod callable_bonds {
     /// issuer would exercise the call. The value of a callable bond
     /// will change depending on how the value of embedded options changes as
     /// interest rates change.
-    
+    ///
+    /// The holder may also be able to put the bond back to the issuer on the dates of the
+    /// `put_structure`, and the issuer to call it at any time before maturity at a
+    /// `make_whole` price.
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct CallableBond {
         pub underlying: Bond,
         pub callable_structure: Vec<CallPrice>,
+        #[serde(default)]
+        pub put_structure: Vec<PutPrice>,
+        #[serde(default)]
+        pub make_whole: Option<MakeWholeCall>,
     }
 
     impl PartialEq<CallableBond> for CallableBond {
@@ -46,6 +57,853 @@ pub mod callable_bonds {
     pub struct CallPrice {
         pub call_start: NaiveDate,
         pub call_end: NaiveDate,
-        pub call_price: f32,
+        pub call_price: f64,
+    }
+
+    /// The price at which the holder may sell the bond back to the issuer between the
+    /// `put_start` and the `put_end`, per 100 of principal. A put protects the holder when
+    /// rates rise, so the price of a putable bond is the price of the option-free bond plus
+    /// the price of the embedded put.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct PutPrice {
+        pub put_start: NaiveDate,
+        pub put_end: NaiveDate,
+        pub put_price: f64,
+    }
+
+    /// A make-whole call between the `call_start` and the `call_end`. The issuer redeems at
+    /// the greater of par and the value of the remaining cashflows discounted at the
+    /// Treasury rate plus the `spread`, so the call is worth exercising only when the spread
+    /// of the issuer over Treasuries has tightened below it.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct MakeWholeCall {
+        pub call_start: NaiveDate,
+        pub call_end: NaiveDate,
+        pub spread: f64,
+    }
+
+    /// How the bond is redeemed.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+    pub enum RedemptionType {
+        Call,
+        MakeWhole,
+        Put,
+        Maturity,
+    }
+
+    /// The highest short rate at which the issuer calls on a call date, the issuer calls at
+    /// the nodes of the date with a rate at or below it. On a put date it is the lowest rate
+    /// at which the holder puts.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct ExerciseBoundary {
+        pub date: NaiveDate,
+        pub time: f64,
+        pub critical_rate: f64,
+    }
+
+    /// The effective duration and convexity of a callable bond at the option-adjusted spread
+    /// `oas`, with its dirty `price` at that spread.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct EffectiveMetrics {
+        pub price: f64,
+        pub oas: f64,
+        pub effective_duration: f64,
+        pub effective_convexity: f64,
+    }
+
+    /// The yield to a redemption `date` at the `redemption_price` per 100 of principal.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct RedemptionYield {
+        pub date: NaiveDate,
+        pub redemption: RedemptionType,
+        pub redemption_price: f64,
+        pub yield_to_redemption: f64,
+    }
+
+    /// The lowest of the yields to every call date and to maturity, the date of the
+    /// redemption which gives it and the `yields` to every redemption date in date order.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct YieldToWorst {
+        pub yield_to_worst: f64,
+        pub redemption_date: NaiveDate,
+        pub yields: Vec<RedemptionYield>,
+    }
+
+    /// The value of a callable bond on a short rate tree. The prices are dirty and for the
+    /// principal of the bond, the `option_value` is the option-free price less the callable
+    /// price, negative when the puts of the holder are worth more than the calls.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct CallableValuation {
+        pub price: f64,
+        pub option_free_price: f64,
+        pub option_value: f64,
+        pub exercise_boundary: Vec<ExerciseBoundary>,
+    }
+
+    impl CallableBond {
+        /// The call price on `date`, the lowest if call windows overlap, `None` if the bond
+        /// is not callable on the date.
+        pub fn call_price_at(&self, date: NaiveDate) -> Option<f64> {
+            self.callable_structure
+                .iter()
+                .filter(|call| call.call_start <= date && date <= call.call_end)
+                .map(|call| call.call_price)
+                .reduce(f64::min)
+        }
+
+        /// The put price on `date`, the highest if put windows overlap, `None` if the bond
+        /// cannot be put on the date.
+        pub fn put_price_at(&self, date: NaiveDate) -> Option<f64> {
+            self.put_structure
+                .iter()
+                .filter(|put| put.put_start <= date && date <= put.put_end)
+                .map(|put| put.put_price)
+                .reduce(f64::max)
+        }
+
+        /// The make-whole price on `date` per 100 of the principal outstanding, the greater
+        /// of par and the cashflows after the date discounted to it off the `treasury` curve
+        /// plus the spread. Terms of the curve are measured from `settlement`. `None` if the
+        /// date is outside the make-whole window.
+        pub fn make_whole_price_at<C: DiscountCurve + ?Sized>(
+            &self,
+            settlement: NaiveDate,
+            date: NaiveDate,
+            treasury: &C,
+        ) -> Option<f64> {
+            let make_whole = self
+                .make_whole
+                .filter(|call| call.call_start <= date && date <= call.call_end)?;
+            let principal = self.underlying.outstanding(date);
+            if principal <= 0.0 {
+                return None;
+            }
+            let curve = ShiftedCurve {
+                curve: treasury,
+                shift: make_whole.spread,
+            };
+            let cashflows = self.underlying.cashflow_times(settlement);
+            let start = cashflows
+                .iter()
+                .find(|(payment, _, _)| *payment == date)
+                .map_or_else(|| self.underlying.year_fraction(settlement, date), |c| c.1);
+            let value: f64 = cashflows
+                .iter()
+                .filter(|(payment, _, _)| *payment > date)
+                .map(|(_, time, amount)| amount * curve.discount(*time))
+                .sum::<f64>()
+                / curve.discount(start);
+            Some(f64::max(100.0, value / principal * 100.0))
+        }
+
+        /// The price at which the issuer redeems on `date`, the lower of the call price and
+        /// the make-whole price off the `treasury` curve when there is one.
+        fn call_at<C: DiscountCurve + ?Sized>(
+            &self,
+            settlement: NaiveDate,
+            date: NaiveDate,
+            treasury: Option<&C>,
+        ) -> Option<(f64, RedemptionType)> {
+            let call = self
+                .call_price_at(date)
+                .map(|price| (price, RedemptionType::Call));
+            let make_whole = treasury
+                .and_then(|curve| self.make_whole_price_at(settlement, date, curve))
+                .map(|price| (price, RedemptionType::MakeWhole));
+            match (call, make_whole) {
+                (Some(call), Some(make_whole)) if make_whole.0 < call.0 => Some(make_whole),
+                (Some(call), _) => Some(call),
+                (None, make_whole) => make_whole,
+            }
+        }
+
+        /// The yields for a quoted `clean_price` at `settlement` to the start of every call
+        /// and put window, clamped to after settlement, and to every coupon date in the
+        /// windows, at the call or put price of the date, and to maturity at par. A
+        /// redemption between coupon dates pays the interest accrued to it. The yield to
+        /// worst is the lowest yield of the redemptions the issuer chooses, the calls and
+        /// maturity; the yields to the puts, which the holder chooses, are in the table.
+        /// Make-whole calls need a Treasury curve, see
+        /// [`CallableBond::yield_to_worst_with_treasury`].
+        pub fn yield_to_worst(
+            &self,
+            settlement: NaiveDate,
+            clean_price: f64,
+        ) -> Result<YieldToWorst, SolverError> {
+            self.redemption_yields::<[DiscountFactor]>(settlement, clean_price, None)
+        }
+
+        /// The yield to worst including the make-whole calls, priced off the `treasury`
+        /// curve with terms from `settlement`.
+        pub fn yield_to_worst_with_treasury<C: DiscountCurve + ?Sized>(
+            &self,
+            settlement: NaiveDate,
+            clean_price: f64,
+            treasury: &C,
+        ) -> Result<YieldToWorst, SolverError> {
+            self.redemption_yields(settlement, clean_price, Some(treasury))
+        }
+
+        fn redemption_yields<C: DiscountCurve + ?Sized>(
+            &self,
+            settlement: NaiveDate,
+            clean_price: f64,
+            treasury: Option<&C>,
+        ) -> Result<YieldToWorst, SolverError> {
+            let dates: Vec<NaiveDate> = self
+                .underlying
+                .cashflow_times(settlement)
+                .iter()
+                .map(|(date, _, _)| *date)
+                .collect();
+            let maturity = dates
+                .last()
+                .copied()
+                .unwrap_or(self.underlying.maturity_date);
+            // Each window can be exercised from its start, clamped to after settlement, and
+            // on the coupon dates within it.
+            let mut windows: Vec<(NaiveDate, NaiveDate)> = self
+                .callable_structure
+                .iter()
+                .map(|call| (call.call_start, call.call_end))
+                .chain(
+                    self.put_structure
+                        .iter()
+                        .map(|put| (put.put_start, put.put_end)),
+                )
+                .collect();
+            if let (Some(make_whole), Some(_)) = (self.make_whole, treasury) {
+                windows.push((make_whole.call_start, make_whole.call_end));
+            }
+            let mut candidates: Vec<NaiveDate> = dates.clone();
+            for (start, end) in windows {
+                let start = start.max(settlement + Days::new(1));
+                if start <= end {
+                    candidates.push(start);
+                }
+            }
+            candidates.sort();
+            candidates.dedup();
+            let mut redemptions = Vec::new();
+            for date in candidates.iter().filter(|date| **date < maturity) {
+                if let Some((price, redemption)) = self.call_at(settlement, *date, treasury) {
+                    redemptions.push((*date, redemption, price));
+                }
+                if let Some(price) = self.put_price_at(*date) {
+                    redemptions.push((*date, RedemptionType::Put, price));
+                }
+            }
+            redemptions.push((maturity, RedemptionType::Maturity, 100.0));
+            let mut yields = Vec::with_capacity(redemptions.len());
+            for (date, redemption, redemption_price) in redemptions {
+                yields.push(RedemptionYield {
+                    date,
+                    redemption,
+                    redemption_price,
+                    yield_to_redemption: self.underlying.yield_to_redemption(
+                        settlement,
+                        clean_price,
+                        date,
+                        redemption_price,
+                    )?,
+                });
+            }
+            let worst = yields
+                .iter()
+                .copied()
+                .filter(|y| y.redemption != RedemptionType::Put)
+                .min_by(|a, b| a.yield_to_redemption.total_cmp(&b.yield_to_redemption))
+                .unwrap();
+            Ok(YieldToWorst {
+                yield_to_worst: worst.yield_to_redemption,
+                redemption_date: worst.date,
+                yields,
+            })
+        }
+
+        /// The value at `settlement` on a tree of the `model` calibrated to the `curve`, with
+        /// terms measured from the settlement date and `steps_per_year` steps a year. Each
+        /// cashflow is paid at the nearest step of the tree. The bond is called or put on the
+        /// coupon dates in the windows, the usual approximation of a bond callable at any
+        /// time, at the price per 100 of the principal outstanding. The make-whole price at
+        /// a node is the value of the remaining cashflows on the rates of the tree plus the
+        /// make-whole spread, and at least par.
+        pub fn price_on_lattice<C: DiscountCurve + ?Sized>(
+            &self,
+            settlement: NaiveDate,
+            curve: &C,
+            model: ShortRateModel,
+            steps_per_year: usize,
+        ) -> Result<CallableValuation, LatticeError> {
+            self.price_with_oas(settlement, curve, model, steps_per_year, 0.0)
+        }
+
+        /// The value with the option-adjusted spread `oas` added to every rate of the tree,
+        /// see [`CallableBond::price_on_lattice`].
+        pub fn price_with_oas<C: DiscountCurve + ?Sized>(
+            &self,
+            settlement: NaiveDate,
+            curve: &C,
+            model: ShortRateModel,
+            steps_per_year: usize,
+            oas: f64,
+        ) -> Result<CallableValuation, LatticeError> {
+            let inputs = self.lattice_inputs(settlement, curve, model, steps_per_year)?;
+            Ok(inputs.value(oas))
+        }
+
+        fn lattice_inputs<C: DiscountCurve + ?Sized>(
+            &self,
+            settlement: NaiveDate,
+            curve: &C,
+            model: ShortRateModel,
+            steps_per_year: usize,
+        ) -> Result<LatticeInputs, LatticeError> {
+            let cashflows = self.underlying.cashflow_times(settlement);
+            let horizon = match cashflows.last() {
+                Some((_, time, _)) => *time,
+                None => {
+                    return Err(LatticeError {
+                        message: "The bond has no cashflows after the settlement date.",
+                        message_code: LatticeErrorType::InvalidParameters,
+                    })
+                }
+            };
+            let steps = ((horizon * steps_per_year as f64).ceil() as usize).max(1);
+            let tree = ShortRateTree::calibrate(model, curve, horizon, steps)?;
+            let mut amounts = vec![0.0; steps + 1];
+            let mut exercise = vec![None; steps + 1];
+            let mut make_whole_floor = vec![None; steps + 1];
+            let mut dates = vec![None; steps + 1];
+            let payment_dates: Vec<NaiveDate> =
+                cashflows.iter().map(|(date, _, _)| *date).collect();
+            let outstanding = self.underlying.outstanding_on(&payment_dates);
+            for ((date, time, amount), outstanding) in cashflows.into_iter().zip(outstanding) {
+                let step = ((time / tree.dt).round() as usize).clamp(1, steps);
+                amounts[step] += amount;
+                dates[step] = Some(date);
+                let principal = outstanding / 100.0;
+                if let Some(make_whole) = self.make_whole {
+                    if make_whole.call_start <= date && date <= make_whole.call_end {
+                        make_whole_floor[step] = Some(100.0 * principal);
+                    }
+                }
+                let call = self
+                    .call_at::<C>(settlement, date, None)
+                    .map(|(price, _)| price * principal);
+                let put = self.put_price_at(date).map(|price| price * principal);
+                exercise[step] = match (call, put) {
+                    (Some(call), Some(put)) => Some(Exercise::CallAndPut { call, put }),
+                    (Some(call), None) => Some(Exercise::Call(call)),
+                    (None, Some(put)) => Some(Exercise::Put(put)),
+                    (None, None) => None,
+                };
+            }
+            // The make-whole price at a node discounts the remaining cashflows on the rates
+            // of the node plus the spread, so it rises as the rates fall.
+            let make_whole = match self.make_whole {
+                Some(call) => {
+                    let values = tree.shifted(call.spread).values_after(&amounts);
+                    make_whole_floor
+                        .iter()
+                        .zip(values)
+                        .map(|(floor, values)| {
+                            floor.map(|floor| values.iter().map(|v| v.max(floor)).collect())
+                        })
+                        .collect()
+                }
+                None => vec![None; steps + 1],
+            };
+            Ok(LatticeInputs {
+                tree,
+                amounts,
+                exercise,
+                make_whole,
+                dates,
+            })
+        }
+
+        /// The option-adjusted spread, the spread over the rates of the tree at which the
+        /// callable bond is worth the dirty `market_price`.
+        pub fn option_adjusted_spread<C: DiscountCurve + ?Sized>(
+            &self,
+            settlement: NaiveDate,
+            curve: &C,
+            model: ShortRateModel,
+            steps_per_year: usize,
+            market_price: f64,
+        ) -> Result<f64, LatticeError> {
+            let inputs = self.lattice_inputs(settlement, curve, model, steps_per_year)?;
+            brent(
+                |oas| inputs.value(oas).price - market_price,
+                -0.1,
+                0.5,
+                &SolverConfig::default(),
+            )
+            .map_err(|_| LatticeError {
+                message: "No option-adjusted spread prices the bond at the market price.",
+                message_code: LatticeErrorType::NoSolution,
+            })
+        }
+
+        /// The effective duration and convexity, repricing on trees calibrated to the `curve`
+        /// with its continuously compounded zero rates shifted up and down by `shift`, holding
+        /// the `oas` constant. Unlike the yield duration these measures follow the change in
+        /// the cashflows when the call is exercised.
+        pub fn effective_metrics<C: DiscountCurve + ?Sized>(
+            &self,
+            settlement: NaiveDate,
+            curve: &C,
+            model: ShortRateModel,
+            steps_per_year: usize,
+            oas: f64,
+            shift: f64,
+        ) -> Result<EffectiveMetrics, LatticeError> {
+            let price_at = |shift: f64| -> Result<f64, LatticeError> {
+                let shifted = ShiftedCurve { curve, shift };
+                Ok(self
+                    .price_with_oas(settlement, &shifted, model, steps_per_year, oas)?
+                    .price)
+            };
+            let price = price_at(0.0)?;
+            let down = price_at(-shift)?;
+            let up = price_at(shift)?;
+            Ok(EffectiveMetrics {
+                price,
+                oas,
+                effective_duration: (down - up) / (2.0 * price * shift),
+                effective_convexity: (down + up - 2.0 * price) / (price * shift * shift),
+            })
+        }
+    }
+
+    /// The curve with its continuously compounded zero rates shifted by `shift`.
+    struct ShiftedCurve<'a, C: DiscountCurve + ?Sized> {
+        curve: &'a C,
+        shift: f64,
+    }
+
+    impl<C: DiscountCurve + ?Sized> DiscountCurve for ShiftedCurve<'_, C> {
+        fn discount(&self, term: f64) -> f64 {
+            self.curve.discount(term) * f64::exp(-self.shift * term)
+        }
+    }
+
+    /// The tree, the cashflows and the call and put prices at each step, the make-whole
+    /// price at each node of the steps it can be called on, and the dates of the steps with
+    /// a cashflow.
+    struct LatticeInputs {
+        tree: ShortRateTree,
+        amounts: Vec<f64>,
+        exercise: Vec<Option<Exercise>>,
+        make_whole: Vec<Option<Vec<f64>>>,
+        dates: Vec<Option<NaiveDate>>,
+    }
+
+    impl LatticeInputs {
+        /// The valuation at the option-adjusted spread `oas`. The critical rates of the
+        /// exercise boundary are the rates of the tree, without the spread.
+        fn value(&self, oas: f64) -> CallableValuation {
+            let tree = self.tree.shifted(oas);
+            let (price, boundary) = tree.backward_induction_by_node(&self.amounts, |i, j| {
+                let (call, put) = self.exercise[i].map_or((None, None), |e| e.prices());
+                let make_whole = self.make_whole[i].as_ref().map(|prices| prices[j]);
+                let call = match (call, make_whole) {
+                    (Some(call), Some(make_whole)) => Some(call.min(make_whole)),
+                    (call, make_whole) => call.or(make_whole),
+                };
+                (call, put)
+            });
+            let (option_free_price, _) = tree.backward_induction(&self.amounts, &[]);
+            let exercise_boundary = boundary
+                .iter()
+                .enumerate()
+                .filter_map(|(step, rate)| match (rate, self.dates[step]) {
+                    (Some(rate), Some(date)) => Some(ExerciseBoundary {
+                        date,
+                        time: step as f64 * tree.dt,
+                        critical_rate: rate - oas,
+                    }),
+                    _ => None,
+                })
+                .collect();
+            CallableValuation {
+                price,
+                option_free_price,
+                option_value: option_free_price - price,
+                exercise_boundary,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bond::bond::{create_bond, DiscountFactor};
+    use crate::callable_bond::callable_bonds::*;
+    use crate::lattice::lattice::{LatticeErrorType, ShortRateModel};
+    use assert_approx_eq::assert_approx_eq;
+    use chrono::NaiveDate;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap()
+    }
+
+    /// Semiannual discount factors out to ten and a half years at a continuous `rate`.
+    fn flat_curve(rate: f64) -> Vec<DiscountFactor> {
+        (1..=21)
+            .map(|i| {
+                let term = i as f64 * 0.5;
+                DiscountFactor {
+                    term,
+                    discount: f64::exp(-rate * term),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_callable_bond_on_lattice() {
+        let bond = create_bond(100.0, "01/15/2024", "01/15/2034", 0.05, "%m/%d/%Y").unwrap();
+        let settlement = bond.issue_date;
+        let curve = flat_curve(0.04);
+        let mut callable = CallableBond {
+            underlying: *bond.clone(),
+            callable_structure: vec![CallPrice {
+                call_start: date("01/15/2026"),
+                call_end: date("01/15/2034"),
+                call_price: 100.0,
+            }],
+            put_structure: Vec::new(),
+            make_whole: None,
+        };
+        assert_eq!(callable.call_price_at(date("01/15/2025")), None);
+        assert_eq!(callable.call_price_at(date("07/15/2030")), Some(100.0));
+
+        let ho_lee = ShortRateModel::HoLee { volatility: 0.01 };
+        let valuation = callable
+            .price_on_lattice(settlement, &curve, ho_lee, 24)
+            .unwrap();
+        assert_approx_eq!(
+            valuation.option_free_price,
+            bond.price_from_curve(settlement, &curve),
+            1e-9
+        );
+        assert!(valuation.option_value > 0.0);
+        assert_approx_eq!(
+            valuation.price,
+            valuation.option_free_price - valuation.option_value,
+            1e-12
+        );
+        let boundary = &valuation.exercise_boundary;
+        assert!(!boundary.is_empty());
+        assert!(boundary.iter().all(|b| b.date >= date("01/15/2026")));
+        assert!(boundary.iter().all(|b| b.critical_rate < 0.05));
+
+        let volatile = ShortRateModel::HoLee { volatility: 0.02 };
+        let more = callable
+            .price_on_lattice(settlement, &curve, volatile, 24)
+            .unwrap();
+        assert!(more.option_value > valuation.option_value);
+        let bdt = ShortRateModel::BlackDermanToy { volatility: 0.2 };
+        let lognormal = callable
+            .price_on_lattice(settlement, &curve, bdt, 24)
+            .unwrap();
+        assert_approx_eq!(
+            lognormal.option_free_price,
+            valuation.option_free_price,
+            1e-9
+        );
+        assert!(lognormal.option_value > 0.0);
+
+        // Rates stay positive on the lognormal tree, the bond is never worth more than its
+        // undiscounted cashflows.
+        callable.callable_structure[0].call_price = 150.0;
+        let out_of_the_money = callable
+            .price_on_lattice(settlement, &curve, bdt, 24)
+            .unwrap();
+        assert_approx_eq!(out_of_the_money.option_value, 0.0, 1e-12);
+        assert!(out_of_the_money.exercise_boundary.is_empty());
+    }
+
+    #[test]
+    fn test_option_adjusted_spread() {
+        let bond = create_bond(100.0, "01/15/2024", "01/15/2034", 0.05, "%m/%d/%Y").unwrap();
+        let settlement = bond.issue_date;
+        let curve = flat_curve(0.04);
+        let model = ShortRateModel::HoLee { volatility: 0.01 };
+        let mut callable = CallableBond {
+            underlying: *bond.clone(),
+            callable_structure: vec![CallPrice {
+                call_start: date("01/15/2026"),
+                call_end: date("01/15/2034"),
+                call_price: 100.0,
+            }],
+            put_structure: Vec::new(),
+            make_whole: None,
+        };
+        let at_spread = callable
+            .price_with_oas(settlement, &curve, model, 24, 0.01)
+            .unwrap();
+        let zero_spread = callable
+            .price_on_lattice(settlement, &curve, model, 24)
+            .unwrap();
+        assert!(at_spread.price < zero_spread.price);
+        let oas = callable
+            .option_adjusted_spread(settlement, &curve, model, 24, at_spread.price)
+            .unwrap();
+        assert_approx_eq!(oas, 0.01, 1e-8);
+        let error = callable
+            .option_adjusted_spread(settlement, &curve, model, 24, 500.0)
+            .unwrap_err();
+        assert_eq!(error.message_code, LatticeErrorType::NoSolution);
+
+        // The call shortens the bond, and the price is capped near the call price as rates
+        // fall.
+        let metrics = callable
+            .effective_metrics(settlement, &curve, model, 24, oas, 0.0001)
+            .unwrap();
+        assert_approx_eq!(metrics.price, at_spread.price, 1e-9);
+        let option_free = bond.metrics_from_curve(settlement, &curve);
+        assert!(metrics.effective_duration < option_free.modified_duration);
+        assert!(metrics.effective_convexity < option_free.convexity);
+
+        // Without calls the effective duration is the duration off the curve.
+        callable.callable_structure.clear();
+        let metrics = callable
+            .effective_metrics(settlement, &curve, model, 24, 0.0, 0.0001)
+            .unwrap();
+        assert_approx_eq!(
+            metrics.effective_duration,
+            option_free.modified_duration,
+            1e-5
+        );
+        assert_approx_eq!(metrics.effective_convexity, option_free.convexity, 1e-3);
+    }
+
+    #[test]
+    fn test_yield_to_worst() {
+        let bond = create_bond(100.0, "01/15/2024", "01/15/2034", 0.05, "%m/%d/%Y").unwrap();
+        let settlement = date("03/15/2024");
+        let callable = CallableBond {
+            underlying: *bond.clone(),
+            callable_structure: vec![
+                CallPrice {
+                    call_start: date("01/15/2027"),
+                    call_end: date("01/14/2029"),
+                    call_price: 102.0,
+                },
+                CallPrice {
+                    call_start: date("01/15/2029"),
+                    call_end: date("01/15/2034"),
+                    call_price: 100.0,
+                },
+            ],
+            put_structure: Vec::new(),
+            make_whole: None,
+        };
+        // At a premium the earliest call is the worst, even above par.
+        let premium = callable.yield_to_worst(settlement, 108.0).unwrap();
+        assert_eq!(premium.yields.len(), 15);
+        assert_eq!(premium.yields[0].date, date("01/15/2027"));
+        assert_eq!(premium.yields[0].redemption_price, 102.0);
+        let maturity = premium.yields.last().unwrap();
+        assert_eq!(maturity.date, date("01/15/2034"));
+        assert_approx_eq!(
+            maturity.yield_to_redemption,
+            bond.yield_from_clean_price(settlement, 108.0).unwrap(),
+            1e-9
+        );
+        assert_eq!(premium.redemption_date, date("01/15/2027"));
+        assert!(premium
+            .yields
+            .iter()
+            .all(|y| premium.yield_to_worst <= y.yield_to_redemption));
+        // The yield to call prices the bond redeemed at the call price.
+        let call = premium.yields[0];
+        let rate = call.yield_to_redemption / 2.0;
+        let coupons: f64 = (1..=6)
+            .map(|i| 2.5 / f64::powf(1.0 + rate, i as f64 - 1.0 + 122.0 / 182.0))
+            .sum();
+        let redemption = 102.0 / f64::powf(1.0 + rate, 5.0 + 122.0 / 182.0);
+        let accrued = bond.accrued_interest(settlement);
+        assert_approx_eq!(coupons + redemption, 108.0 + accrued, 1e-9);
+
+        // At a discount the bond is not expected to be called.
+        let discount = callable.yield_to_worst(settlement, 95.0).unwrap();
+        assert_eq!(discount.redemption_date, date("01/15/2034"));
+    }
+
+    #[test]
+    fn test_off_cycle_call_windows() {
+        let bond = create_bond(100.0, "01/15/2024", "01/15/2034", 0.05, "%m/%d/%Y").unwrap();
+        let settlement = date("03/15/2024");
+        let callable = CallableBond {
+            underlying: *bond.clone(),
+            callable_structure: vec![
+                CallPrice {
+                    call_start: date("03/01/2027"),
+                    call_end: date("12/31/2027"),
+                    call_price: 102.0,
+                },
+                // No coupon date falls in the window.
+                CallPrice {
+                    call_start: date("02/01/2030"),
+                    call_end: date("06/30/2030"),
+                    call_price: 101.0,
+                },
+                // Closed before settlement.
+                CallPrice {
+                    call_start: date("01/15/2024"),
+                    call_end: date("03/01/2024"),
+                    call_price: 104.0,
+                },
+            ],
+            put_structure: Vec::new(),
+            make_whole: None,
+        };
+        let worst = callable.yield_to_worst(settlement, 108.0).unwrap();
+        let dates: Vec<NaiveDate> = worst.yields.iter().map(|y| y.date).collect();
+        assert_eq!(
+            dates,
+            vec![
+                date("03/01/2027"),
+                date("07/15/2027"),
+                date("02/01/2030"),
+                date("01/15/2034"),
+            ]
+        );
+        assert_eq!(worst.yields[2].redemption_price, 101.0);
+        assert_eq!(worst.yields[2].redemption, RedemptionType::Call);
+
+        // Called on 03/01/2027 the holder receives the six coupons to 01/15/2027 and then
+        // the call price with the interest accrued for 45 of the 181 days of the period.
+        let first_call = worst.yields[0].yield_to_redemption;
+        let rate = first_call / 2.0;
+        let first = 122.0 / 182.0;
+        let coupons: f64 = (0..6)
+            .map(|i| 2.5 / f64::powf(1.0 + rate, first + i as f64))
+            .sum();
+        let call = (2.5 * 45.0 / 181.0 + 102.0) / f64::powf(1.0 + rate, first + 5.0 + 45.0 / 181.0);
+        assert_approx_eq!(
+            coupons + call - bond.accrued_interest(settlement),
+            108.0,
+            1e-9
+        );
+    }
+
+    #[test]
+    fn test_putable_and_make_whole() {
+        let bond = create_bond(100.0, "01/15/2024", "01/15/2034", 0.05, "%m/%d/%Y").unwrap();
+        let settlement = bond.issue_date;
+        let curve = flat_curve(0.04);
+        let model = ShortRateModel::HoLee { volatility: 0.01 };
+        let putable = CallableBond {
+            underlying: *bond.clone(),
+            callable_structure: Vec::new(),
+            put_structure: vec![PutPrice {
+                put_start: date("01/15/2026"),
+                put_end: date("01/15/2033"),
+                put_price: 100.0,
+            }],
+            make_whole: None,
+        };
+        assert_eq!(putable.put_price_at(date("07/15/2030")), Some(100.0));
+        let valuation = putable
+            .price_on_lattice(settlement, &curve, model, 24)
+            .unwrap();
+        assert!(valuation.option_value < 0.0);
+        assert!(valuation.price > valuation.option_free_price);
+        let boundary = &valuation.exercise_boundary;
+        assert!(!boundary.is_empty());
+        assert!(boundary.iter().all(|b| b.critical_rate > 0.04));
+        // The puts are in the table, the holder does not lower the worst yield.
+        let at_discount = putable.yield_to_worst(settlement, 95.0).unwrap();
+        let first_put = at_discount.yields[0];
+        assert_eq!(first_put.redemption, RedemptionType::Put);
+        assert_eq!(first_put.date, date("01/15/2026"));
+        assert!(first_put.yield_to_redemption > at_discount.yield_to_worst);
+        assert_eq!(at_discount.redemption_date, date("01/15/2034"));
+
+        // The make-whole price discounts the remaining flows at the curve plus the spread.
+        let make_whole = CallableBond {
+            underlying: *bond.clone(),
+            callable_structure: Vec::new(),
+            put_structure: Vec::new(),
+            make_whole: Some(MakeWholeCall {
+                call_start: date("01/15/2025"),
+                call_end: date("01/15/2034"),
+                spread: 0.005,
+            }),
+        };
+        assert_eq!(
+            make_whole.make_whole_price_at(settlement, date("07/15/2024"), &curve),
+            None
+        );
+        let price = make_whole
+            .make_whole_price_at(settlement, date("01/15/2029"), &curve)
+            .unwrap();
+        let expected: f64 = (1..=10)
+            .map(|i| 2.5 * f64::exp(-0.045 * 0.5 * i as f64))
+            .sum::<f64>()
+            + 100.0 * f64::exp(-0.045 * 5.0);
+        assert_approx_eq!(price, expected, 1e-9);
+        // A wide spread over the curve leaves the make-whole call out of the money.
+        let at_spread = make_whole
+            .price_with_oas(settlement, &curve, model, 24, 0.01)
+            .unwrap();
+        let callable = CallableBond {
+            callable_structure: vec![CallPrice {
+                call_start: date("01/15/2025"),
+                call_end: date("01/15/2034"),
+                call_price: 100.0,
+            }],
+            make_whole: None,
+            ..make_whole.clone()
+        };
+        let par_call = callable
+            .price_with_oas(settlement, &curve, model, 24, 0.01)
+            .unwrap();
+        assert!(at_spread.option_value >= 0.0);
+        assert!(at_spread.option_value < par_call.option_value);
+        // The make-whole price follows the rates of each node, so a make-whole at a tight
+        // spread is worth next to nothing to the issuer however far rates fall: nothing at
+        // no spread, and less than the spread over the ten years of the bond at five basis
+        // points.
+        for (spread, bound) in [(0.0, 1e-9), (0.0005, 0.5)] {
+            let tight = CallableBond {
+                make_whole: Some(MakeWholeCall {
+                    spread,
+                    ..make_whole.make_whole.unwrap()
+                }),
+                ..make_whole.clone()
+            };
+            let valuation = tight
+                .price_on_lattice(settlement, &curve, model, 24)
+                .unwrap();
+            assert!(valuation.option_value >= 0.0);
+            assert!(valuation.option_value < bound);
+        }
+        let yields = make_whole
+            .yield_to_worst_with_treasury(settlement, 104.0, &curve)
+            .unwrap();
+        assert_eq!(yields.yields[0].redemption, RedemptionType::MakeWhole);
+        assert_eq!(yields.yields[0].date, date("01/15/2025"));
+        assert!(make_whole
+            .yield_to_worst(settlement, 104.0)
+            .unwrap()
+            .yields
+            .iter()
+            .all(|y| y.redemption == RedemptionType::Maturity));
+
+        // Bonds serialized before puts and make-whole calls still load.
+        let json = serde_json::to_string(&putable).unwrap();
+        let restored: CallableBond = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.put_structure[0].put_price, 100.0);
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value.as_object_mut().unwrap().remove("put_structure");
+        value.as_object_mut().unwrap().remove("make_whole");
+        let legacy: CallableBond = serde_json::from_value(value).unwrap();
+        assert!(legacy.put_structure.is_empty() && legacy.make_whole.is_none());
     }
 }
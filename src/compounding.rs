@@ -0,0 +1,341 @@
+//! Compounding of overnight rates over an interest period by the conventions of the 2021
+//! ISDA Interest Rate Derivatives Definitions. The rate of each business day accrues for
+//! the calendar days until the next business day, so the rate of a Friday accrues over the
+//! weekend:
+//!
+//!     rate = (prod(1 + r_i * n_i / D) - 1) * D / d
+//!
+//! where `n_i` is the number of days weighted on business day `i`, `D` the day count basis
+//! (360 for SOFR, 365 for SONIA) and `d` the days of the period.
+//!
+//! The index published by the administrator of the rate, the SOFR Index and the SONIA
+//! Compounded Index, compounds the same daily rates from a base date. The compounded rate
+//! of a period with an observation shift is the ratio of the index on the shifted dates.
+//!
+//! References :
+//!
+//!     . "2021 ISDA Interest Rate Derivatives Definitions", section 6.9
+//!     . "Additional Information about Reference Rates Administered by the New York Fed",
+//!       SOFR Averages and Index
+pub mod compounding {
+    use crate::calendar::calendar::{BusinessDayConvention, Calendar};
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::interest_rate_swap::interest_rate_swap::InterestRateData;
+    use crate::rate_index::rate_index::RateIndex;
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    /// The conventions for compounding an overnight rate over an interest period.
+    ///
+    /// * `lookback` - the rate of each business day is observed `lookback` business days
+    ///   earlier. Without an `observation_shift` the rates are weighted by the days of the
+    ///   interest period (ISDA *Compounding with Lookback*); with it the whole observation
+    ///   period is shifted and the rates are weighted by its own days (ISDA *Compounding
+    ///   with Observation Period Shift*).
+    /// * `lockout` - the rate stops resetting `lockout` business days before the end of the
+    ///   period, the rate of that business day applies to the rest of the period (ISDA
+    ///   *Compounding with Lockout*, the rate cut-off of a floating rate note).
+    ///
+    /// A payment delay moves the payment date, not the rate; see the schedules of
+    /// [`crate::interest_rate_swap::interest_rate_swap::LegTerms`] and
+    /// [`crate::frn::frn::FloatingRateNote`].
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct OvernightCompounding {
+        pub calendar: Calendar,
+        pub day_count: DayCountConvention,
+        pub lookback: u32,
+        pub observation_shift: bool,
+        pub lockout: u32,
+    }
+
+    /// Indices are published to eight decimals.
+    fn round8(value: f64) -> f64 {
+        (value * 1e8).round() / 1e8
+    }
+
+    impl OvernightCompounding {
+        /// Compounding without lookback or lockout on the fixing calendar and day count basis
+        /// of the `index`, US Government Securities and *ACT/360* for SOFR, the United
+        /// Kingdom and *ACT/365 Fixed* for SONIA.
+        pub fn new(index: &RateIndex) -> OvernightCompounding {
+            OvernightCompounding {
+                calendar: index.calendar.clone(),
+                day_count: index.day_count.clone(),
+                lookback: 0,
+                observation_shift: false,
+                lockout: 0,
+            }
+        }
+
+        /// The compounded rate for the period from `start` to `end` accrued up to `to`, no
+        /// later than `end`. The `rate` of an observation date returns the rate, a decimal,
+        /// and whether it is published; the result is published when every rate is.
+        pub fn compounded_rate<E, F>(
+            &self,
+            start: NaiveDate,
+            end: NaiveDate,
+            to: NaiveDate,
+            mut rate: F,
+        ) -> Result<(f64, bool), E>
+        where
+            F: FnMut(NaiveDate) -> Result<(f64, bool), E>,
+        {
+            let lag = self.lookback as i32;
+            let (from, to, end) = if self.observation_shift {
+                (
+                    self.calendar.advance(start, -lag),
+                    self.calendar.advance(to, -lag),
+                    self.calendar.advance(end, -lag),
+                )
+            } else {
+                (start, to, end)
+            };
+            let lockout_date =
+                (self.lockout > 0).then(|| self.calendar.advance(end, -(self.lockout as i32)));
+            let mut growth = 1.0;
+            let mut fixed = true;
+            let mut day = from;
+            while day < to {
+                let next = self.calendar.advance(day, 1).min(to);
+                let reset = match lockout_date {
+                    Some(lockout_date) if day >= lockout_date => lockout_date,
+                    _ => self.calendar.adjust(day, BusinessDayConvention::Preceding),
+                };
+                let observation = if self.observation_shift {
+                    reset
+                } else {
+                    self.calendar.advance(reset, -lag)
+                };
+                let (value, known) = rate(observation)?;
+                fixed &= known;
+                growth *= 1.0 + value * self.day_count.year_fraction(day, next);
+                day = next;
+            }
+            let year_fraction = self.day_count.year_fraction(from, to);
+            if year_fraction <= 0.0 {
+                return Ok((0.0, fixed));
+            }
+            Ok(((growth - 1.0) / year_fraction, fixed))
+        }
+
+        /// The index compounding the `fixings` from `base_value` on `base_date`, by date of
+        /// publication. The value of a business day compounds the rates up to the business
+        /// day before it, rounded to eight decimals like the SOFR Index. The index stops at
+        /// the first business day without a fixing.
+        pub fn index(
+            &self,
+            fixings: &[InterestRateData],
+            base_date: NaiveDate,
+            base_value: f64,
+        ) -> BTreeMap<NaiveDate, f64> {
+            let rates: BTreeMap<NaiveDate, f64> = fixings
+                .iter()
+                .map(|fixing| (fixing.time, fixing.rate / 100.0))
+                .collect();
+            let mut result = BTreeMap::new();
+            let mut day = self
+                .calendar
+                .adjust(base_date, BusinessDayConvention::Following);
+            let mut value = base_value;
+            result.insert(day, value);
+            while let Some(rate) = rates.get(&day) {
+                let next = self.calendar.advance(day, 1);
+                value = round8(value * (1.0 + rate * self.day_count.year_fraction(day, next)));
+                result.insert(next, value);
+                day = next;
+            }
+            result
+        }
+
+        /// The compounded rate from the index values at `start` and `end`:
+        ///
+        ///     rate = (index_end / index_start - 1) * D / d
+        pub fn rate_from_index(
+            &self,
+            start: NaiveDate,
+            start_value: f64,
+            end: NaiveDate,
+            end_value: f64,
+        ) -> f64 {
+            (end_value / start_value - 1.0) / self.day_count.year_fraction(start, end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calendar::calendar::Calendar;
+    use crate::compounding::compounding::*;
+    use crate::interest_rate_swap::interest_rate_swap::InterestRateData;
+    use crate::rate_index::rate_index::RateIndexRegistry;
+    use crate::rates::rates::OvernightRateType;
+    use assert_approx_eq::assert_approx_eq;
+    use chrono::{Days, NaiveDate};
+    use std::collections::BTreeMap;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap()
+    }
+
+    /// Illustrative daily fixings in percent on the business days of the `calendar`, a
+    /// level which steps down after a quarter of the days and spikes at each month end,
+    /// the pattern of overnight rates around a rate cut.
+    fn fixings(
+        rate_type: OvernightRateType,
+        calendar: &Calendar,
+        start: &str,
+        days: u64,
+        level: f64,
+    ) -> Vec<InterestRateData> {
+        (0..days)
+            .map(|i| date(start) + Days::new(i))
+            .filter(|day| calendar.is_business_day(*day))
+            .enumerate()
+            .map(|(i, time)| {
+                let month_end =
+                    (time + Days::new(3)).format("%m").to_string() != time.format("%m").to_string();
+                let rate = if i < days as usize / 4 {
+                    level
+                } else {
+                    level - 0.25
+                };
+                InterestRateData {
+                    time,
+                    rate: if month_end { rate + 0.05 } else { rate },
+                    overnight_rate_type: rate_type,
+                }
+            })
+            .collect()
+    }
+
+    fn lookup(fixings: &[InterestRateData]) -> BTreeMap<NaiveDate, f64> {
+        fixings.iter().map(|f| (f.time, f.rate / 100.0)).collect()
+    }
+
+    #[test]
+    fn test_compounding_matches_index() {
+        let registry = RateIndexRegistry::default();
+        for (rate_type, level) in [
+            (OvernightRateType::SOFR, 5.31),
+            (OvernightRateType::SONIA, 5.19),
+        ] {
+            let conventions = OvernightCompounding::new(registry.get(rate_type).unwrap());
+            let history = fixings(rate_type, &conventions.calendar, "10/02/2023", 180, level);
+            let rates = lookup(&history);
+            let index = conventions.index(&history, date("10/02/2023"), 1.0);
+            // A 30 day average and a quarterly interest period with a two day shift.
+            for (start, end) in [("11/15/2023", "12/15/2023"), ("11/01/2023", "02/01/2024")] {
+                let shifted = OvernightCompounding {
+                    lookback: 2,
+                    observation_shift: true,
+                    ..conventions.clone()
+                };
+                let (start, end) = (date(start), date(end));
+                let (rate, fixed) = shifted
+                    .compounded_rate(start, end, end, |day| {
+                        rates.get(&day).map(|r| (*r, true)).ok_or(day)
+                    })
+                    .unwrap();
+                assert!(fixed);
+                let calendar = &conventions.calendar;
+                let (from, to) = (calendar.advance(start, -2), calendar.advance(end, -2));
+                let from_index = conventions.rate_from_index(from, index[&from], to, index[&to]);
+                // The index is rounded to eight decimals.
+                assert_approx_eq!(rate, from_index, 1e-6);
+
+                // Without the shift the rates are weighted by the days of the interest
+                // period, which differs when the weekends and month ends do not line up.
+                let lookback = OvernightCompounding {
+                    lookback: 2,
+                    ..conventions.clone()
+                };
+                let (unshifted, _) = lookback
+                    .compounded_rate(start, end, end, |day| {
+                        rates.get(&day).map(|r| (*r, true)).ok_or(day)
+                    })
+                    .unwrap();
+                assert!((unshifted - rate).abs() > 1e-6, "{} {}", unshifted, rate);
+                assert_approx_eq!(unshifted, rate, 5e-3);
+            }
+        }
+        // A missing fixing is reported with its date.
+        let sofr = OvernightCompounding::new(registry.get(OvernightRateType::SOFR).unwrap());
+        let missing = sofr
+            .compounded_rate(
+                date("01/02/2024"),
+                date("01/09/2024"),
+                date("01/09/2024"),
+                |day| {
+                    if day < date("01/05/2024") {
+                        Ok((0.053, true))
+                    } else {
+                        Err(day)
+                    }
+                },
+            )
+            .unwrap_err();
+        assert_eq!(missing, date("01/05/2024"));
+    }
+
+    #[test]
+    fn test_weighting_and_lockout() {
+        let registry = RateIndexRegistry::default();
+        let sofr = OvernightCompounding::new(registry.get(OvernightRateType::SOFR).unwrap());
+        // Friday 01/12/2024 accrues over the weekend and Martin Luther King Day.
+        let rates = BTreeMap::from([
+            (date("01/10/2024"), 0.0531),
+            (date("01/11/2024"), 0.0532),
+            (date("01/12/2024"), 0.0533),
+            (date("01/16/2024"), 0.0534),
+            (date("01/17/2024"), 0.0535),
+        ]);
+        let observe = |day: NaiveDate| rates.get(&day).map(|r| (*r, true)).ok_or(day);
+        let (start, end) = (date("01/10/2024"), date("01/18/2024"));
+        let (rate, _) = sofr.compounded_rate(start, end, end, observe).unwrap();
+        let growth = (1.0 + 0.0531 / 360.0)
+            * (1.0 + 0.0532 / 360.0)
+            * (1.0 + 0.0533 * 4.0 / 360.0)
+            * (1.0 + 0.0534 / 360.0)
+            * (1.0 + 0.0535 / 360.0);
+        assert_approx_eq!(rate, (growth - 1.0) * 360.0 / 8.0, 1e-14);
+
+        // With a two day lockout the rate of 01/16 also applies on 01/17.
+        let lockout = OvernightCompounding {
+            lockout: 2,
+            ..sofr.clone()
+        };
+        let (locked, _) = lockout.compounded_rate(start, end, end, observe).unwrap();
+        let growth = (1.0 + 0.0531 / 360.0)
+            * (1.0 + 0.0532 / 360.0)
+            * (1.0 + 0.0533 * 4.0 / 360.0)
+            * (1.0 + 0.0534 / 360.0)
+            * (1.0 + 0.0534 / 360.0);
+        assert_approx_eq!(locked, (growth - 1.0) * 360.0 / 8.0, 1e-14);
+
+        // Accrued part way the rate compounds to the date.
+        let (partial, _) = sofr
+            .compounded_rate(start, end, date("01/12/2024"), observe)
+            .unwrap();
+        let growth = (1.0 + 0.0531 / 360.0) * (1.0 + 0.0532 / 360.0);
+        assert_approx_eq!(partial, (growth - 1.0) * 360.0 / 2.0, 1e-14);
+
+        // SONIA accrues on ACT/365 and 01/15/2024 is a business day in London.
+        let sonia = OvernightCompounding::new(registry.get(OvernightRateType::SONIA).unwrap());
+        let sonia_rates = BTreeMap::from([
+            (date("01/11/2024"), 0.0519),
+            (date("01/12/2024"), 0.0520),
+            (date("01/15/2024"), 0.0518),
+        ]);
+        let (sonia_rate, _) = sonia
+            .compounded_rate(
+                date("01/11/2024"),
+                date("01/16/2024"),
+                date("01/16/2024"),
+                |day| sonia_rates.get(&day).map(|r| (*r, true)).ok_or(day),
+            )
+            .unwrap();
+        let growth = (1.0 + 0.0519 / 365.0) * (1.0 + 0.0520 * 3.0 / 365.0) * (1.0 + 0.0518 / 365.0);
+        assert_approx_eq!(sonia_rate, (growth - 1.0) * 365.0 / 5.0, 1e-14);
+    }
+}
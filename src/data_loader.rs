@@ -4,14 +4,21 @@ pub mod data_loader {
     use crate::bond::bond::DiscountFactor;
     use crate::bond::bond::MarketData;
     use crate::bond::bond::Periodicity;
+    use crate::calendar::calendar::Calendar;
+    use crate::inflation::inflation::CpiSeries;
+    use crate::interest_rate_swap::interest_rate_swap::InterestRateData;
+    use crate::rate_index::rate_index::RateIndexRegistry;
     use crate::rates::rates::NextSettlementDate;
     use crate::rates::rates::OvernightRateType;
     use crate::rates::rates::SwapRate;
     use chrono::NaiveDate;
     use datafusion::common::arrow::array::*;
+    use datafusion::common::arrow::compute::cast;
+    use datafusion::common::arrow::datatypes::DataType;
     use datafusion::error::*;
     use datafusion::prelude::*;
     use log::debug;
+    use std::collections::BTreeSet;
     use std::str::FromStr;
 
     pub fn parse_date(input: &str, format: &str) -> NaiveDate {
@@ -58,7 +65,7 @@ pub mod data_loader {
                         None => panic!("Missing date"),
                     },
                     term: match terms {
-                        Some(v) => f32::from_str(v.value(i).trim()).unwrap(),
+                        Some(v) => f64::from_str(v.value(i).trim()).unwrap(),
                         None => panic!("Missing term"),
                     },
                     next_settlement_date: match next_settlement_dates {
@@ -109,11 +116,11 @@ pub mod data_loader {
                         None => panic!("Missing date"),
                     },
                     term: match terms {
-                        Some(v) => f32::from_str(v.value(i).trim()).unwrap(),
+                        Some(v) => f64::from_str(v.value(i).trim()).unwrap(),
                         None => panic!("Missing term"),
                     },
                     rate: match rates {
-                        Some(v) => f32::from_str(v.value(i).trim()).unwrap(),
+                        Some(v) => f64::from_str(v.value(i).trim()).unwrap(),
                         None => panic!("Missing rates."),
                     },
                     swap_rate_type,
@@ -125,6 +132,142 @@ pub mod data_loader {
         Ok(result)
     }
 
+    /// Load fixings in percent from a file with the fixing date in a `Date` column, the name
+    /// of the index in an `Index` column and the rate in a `Rate` column. The names are
+    /// looked up in the `registry`, an unknown name, a missing column or a date which does not
+    /// parse is an error.
+    pub async fn load_fixings(
+        file_name: String,
+        registry: &RateIndexRegistry,
+    ) -> Result<Vec<InterestRateData>> {
+        const DATE_COLUMN: &str = "Date";
+        const INDEX_COLUMN: &str = "Index";
+        const RATE_COLUMN: &str = "Rate";
+        const DATE_FORMAT: &str = "%m/%d/%Y";
+
+        let ctx = SessionContext::new();
+        let data_frame = ctx.read_csv(file_name, CsvReadOptions::new()).await?;
+        let batches: Vec<RecordBatch> = data_frame.collect().await?;
+        let mut result = Vec::new();
+        let column = |batch: &RecordBatch, name: &str| {
+            batch
+                .column_by_name(name)
+                .cloned()
+                .ok_or_else(|| DataFusionError::Execution(format!("Column not found {}", name)))
+        };
+        for batch in batches {
+            let dates = column(&batch, DATE_COLUMN)?;
+            let dates = dates
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!("Column {} is not text", DATE_COLUMN))
+                })?;
+            let indices = column(&batch, INDEX_COLUMN)?;
+            let indices = indices
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!("Column {} is not text", INDEX_COLUMN))
+                })?;
+            // A column of whole numbers is read as integers.
+            let rates = cast(&column(&batch, RATE_COLUMN)?, &DataType::Float64)?;
+            let rates = rates
+                .as_any()
+                .downcast_ref::<array::Float64Array>()
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!("Column {} is not numeric", RATE_COLUMN))
+                })?;
+            for i in 0..batch.num_rows() {
+                let name = indices.value(i);
+                let index = registry.find(name).map_err(|error| {
+                    DataFusionError::Execution(format!("{} {}", error.message, name))
+                })?;
+                let date = dates.value(i).trim();
+                let time = NaiveDate::parse_from_str(date, DATE_FORMAT).map_err(|_| {
+                    DataFusionError::Execution(format!("Failed to parse date {}", date))
+                })?;
+                result.push(InterestRateData {
+                    time,
+                    rate: rates.value(i),
+                    overnight_rate_type: index.rate_type,
+                });
+            }
+        }
+        debug!("Loaded {} fixings", result.len());
+        Ok(result)
+    }
+
+    /// Load a `Custom` calendar from a file of holidays, one per row in a `Date` column.
+    pub async fn load_holidays(file_name: String) -> Result<Calendar> {
+        const DATE_COLUMN: &str = "Date";
+        const DATE_FORMAT: &str = "%m/%d/%Y";
+
+        let ctx = SessionContext::new();
+        let data_frame = ctx.read_csv(file_name, CsvReadOptions::new()).await?;
+        let batches: Vec<RecordBatch> = data_frame.collect().await?;
+        let mut holidays = BTreeSet::new();
+        for batch in batches {
+            let dates = match batch.column_by_name(DATE_COLUMN) {
+                Some(col) => col.as_any().downcast_ref::<StringArray>(),
+                None => panic!("Column not found {}", DATE_COLUMN),
+            };
+            for i in 0..batch.num_rows() {
+                match dates {
+                    Some(v) => holidays.insert(parse_date(v.value(i).trim(), DATE_FORMAT)),
+                    None => panic!("Missing date"),
+                };
+            }
+        }
+        debug!("Loaded {} holidays", holidays.len());
+        Ok(Calendar::Custom(holidays))
+    }
+
+    /// Load a monthly CPI series, such as the CPI-U, from a file with the first day of the
+    /// month in a `Date` column and the index in a `CPI` column.
+    pub async fn load_cpi(file_name: String) -> Result<CpiSeries> {
+        const DATE_COLUMN: &str = "Date";
+        const CPI_COLUMN: &str = "CPI";
+        const DATE_FORMAT: &str = "%m/%d/%Y";
+
+        let ctx = SessionContext::new();
+        let data_frame = ctx.read_csv(file_name, CsvReadOptions::new()).await?;
+        let batches: Vec<RecordBatch> = data_frame.collect().await?;
+        let mut observations = Vec::new();
+        let column = |batch: &RecordBatch, name: &str| {
+            batch
+                .column_by_name(name)
+                .cloned()
+                .ok_or_else(|| DataFusionError::Execution(format!("Column not found {}", name)))
+        };
+        for batch in batches {
+            let dates = column(&batch, DATE_COLUMN)?;
+            let dates = dates
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!("Column {} is not text", DATE_COLUMN))
+                })?;
+            // A column of whole numbers is read as integers.
+            let values = cast(&column(&batch, CPI_COLUMN)?, &DataType::Float64)?;
+            let values = values
+                .as_any()
+                .downcast_ref::<array::Float64Array>()
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!("Column {} is not numeric", CPI_COLUMN))
+                })?;
+            for i in 0..batch.num_rows() {
+                let date = dates.value(i).trim();
+                let month = NaiveDate::parse_from_str(date, DATE_FORMAT).map_err(|_| {
+                    DataFusionError::Execution(format!("Failed to parse date {}", date))
+                })?;
+                observations.push((month, values.value(i)));
+            }
+        }
+        debug!("Loaded {} CPI observations", observations.len());
+        Ok(CpiSeries::new(&observations))
+    }
+
     pub async fn load_market_data(file_name: String) -> Result<Vec<MarketData>> {
         let ctx = SessionContext::new();
         let df = ctx.read_csv(file_name, CsvReadOptions::new()).await?;
@@ -148,15 +291,15 @@ pub mod data_loader {
             for i in 0..num_rows {
                 let m = MarketData {
                     coupon_rate: match coupons {
-                        Some(v) => v.value(i) as f32,
+                        Some(v) => v.value(i),
                         None => 0.0,
                     },
                     term: match maturity {
-                        Some(v) => f32::from_str(v.value(i).trim()).unwrap(),
+                        Some(v) => f64::from_str(v.value(i).trim()).unwrap(),
                         None => 0.0,
                     },
                     market_price: match price {
-                        Some(v) => f32::from_str(v.value(i).trim()).unwrap(),
+                        Some(v) => f64::from_str(v.value(i).trim()).unwrap(),
                         None => 0.0,
                     },
                 };
@@ -180,9 +323,15 @@ pub mod data_loader {
 #[cfg(test)]
 mod tests {
 
+    use crate::calendar::calendar::Calendar;
+    use crate::data_loader::data_loader::load_cpi;
+    use crate::data_loader::data_loader::load_fixings;
+    use crate::data_loader::data_loader::load_holidays;
     use crate::data_loader::data_loader::load_market_data;
     use crate::data_loader::data_loader::load_spot_rates;
+    use crate::rate_index::rate_index::RateIndexRegistry;
     use crate::rates::rates::OvernightRateType;
+    use chrono::NaiveDate;
 
     #[actix_web::main]
     async fn test_load_market_data() {
@@ -199,4 +348,54 @@ mod tests {
         .await;
         println!("Spot rates {:?}", spot_rates);
     }
+
+    #[actix_web::test]
+    async fn test_load_holidays() {
+        let calendar = load_holidays(String::from("tests/holidays.csv"))
+            .await
+            .unwrap();
+        let carnival = NaiveDate::parse_from_str("03/04/2025", "%m/%d/%Y").unwrap();
+        assert!(calendar.is_holiday(carnival));
+        let joint = Calendar::Joint(vec![Calendar::Target, calendar]);
+        assert!(joint.is_holiday(carnival));
+    }
+
+    #[actix_web::test]
+    async fn test_load_cpi() {
+        let cpi = load_cpi(String::from("tests/cpi_u.csv")).await.unwrap();
+        assert_eq!(cpi.values.len(), 7);
+        let january = NaiveDate::parse_from_str("01/15/2024", "%m/%d/%Y").unwrap();
+        assert!((cpi.reference_cpi(january).unwrap() - 307.391).abs() < 1e-9);
+        // Index levels which are all whole numbers.
+        let cpi = load_cpi(String::from("tests/cpi_whole.csv")).await.unwrap();
+        assert_eq!(cpi.values.len(), 3);
+        let values: Vec<f64> = cpi.values.values().copied().collect();
+        assert_eq!(values, vec![307.0, 308.0, 309.0]);
+        let missing = load_cpi(String::from("tests/fixings.csv")).await;
+        assert!(missing.is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_load_fixings() {
+        let registry = RateIndexRegistry::default();
+        let fixings = load_fixings(String::from("tests/fixings.csv"), &registry)
+            .await
+            .unwrap();
+        assert_eq!(fixings.len(), 6);
+        assert_eq!(fixings[0].overnight_rate_type, OvernightRateType::SOFR);
+        assert_eq!(fixings[4].overnight_rate_type, OvernightRateType::ESTR);
+        assert!((fixings[4].rate - 3.907).abs() < 1e-12);
+        assert!((fixings[5].rate - 5.0).abs() < 1e-12);
+        // Rates which are all whole numbers.
+        let whole = load_fixings(String::from("tests/fixings_whole.csv"), &registry)
+            .await
+            .unwrap();
+        let rates: Vec<f64> = whole.iter().map(|f| f.rate).collect();
+        assert_eq!(rates, vec![5.0, 4.0]);
+        let missing = load_fixings(String::from("tests/cpi_u.csv"), &registry).await;
+        assert!(missing.is_err());
+        let unknown =
+            load_fixings(String::from("tests/fixings.csv"), &RateIndexRegistry::new()).await;
+        assert!(unknown.is_err());
+    }
 }
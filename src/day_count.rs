@@ -0,0 +1,249 @@
+//! Day count conventions determine how interest accrues between two dates. Every
+//! product in the library (bonds, swaps and bills) converts a pair of dates into a
+//! *year fraction* through the conventions defined here.
+pub mod day_count {
+    use crate::bond::bond::Periodicity;
+    use crate::calendar::calendar::Calendar;
+    use chrono::{Datelike, Months, NaiveDate};
+    use serde::{Deserialize, Serialize};
+
+    /// The commonly used day count conventions.
+    ///
+    /// * `Act360` - actual days over 360. Money markets, T-Bills, SOFR.
+    /// * `Act365Fixed` - actual days over 365. SONIA, sterling money markets.
+    /// * `ActActIsda` - actual days split across calendar years, each divided by the days in that year.
+    /// * `ActActIcma` - actual days over the actual days of the coupon (reference) period times the
+    ///   coupon frequency. US Treasuries.
+    /// * `Thirty360Us` - 30/360 with the US (SIA) end of February rules. US corporates and agencies.
+    /// * `ThirtyE360` - 30E/360 (Eurobond basis).
+    /// * `ThirtyE360Isda` - 30E/360 ISDA (German), month ends are always treated as the 30th.
+    /// * `Bus252` - business days of the calendar over 252. Brazilian market, on
+    ///   [`Calendar::Brazil`].
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    pub enum DayCountConvention {
+        Act360,
+        Act365Fixed,
+        ActActIsda,
+        ActActIcma,
+        Thirty360Us,
+        ThirtyE360,
+        ThirtyE360Isda,
+        Bus252(Calendar),
+    }
+
+    fn is_leap_year(year: i32) -> bool {
+        NaiveDate::from_ymd_opt(year, 2, 29).is_some()
+    }
+
+    fn days_in_year(year: i32) -> f64 {
+        if is_leap_year(year) {
+            366.0
+        } else {
+            365.0
+        }
+    }
+
+    /// Whether `date` is the last day of its month.
+    pub(crate) fn is_last_day_of_month(date: NaiveDate) -> bool {
+        date.succ_opt()
+            .is_none_or(|next| next.month() != date.month())
+    }
+
+    fn is_last_day_of_february(date: NaiveDate) -> bool {
+        date.month() == 2 && is_last_day_of_month(date)
+    }
+
+    fn thirty_360_days(y1: i32, m1: u32, d1: u32, y2: i32, m2: u32, d2: u32) -> i64 {
+        360 * (y2 - y1) as i64 + 30 * (m2 as i64 - m1 as i64) + (d2 as i64 - d1 as i64)
+    }
+
+    impl DayCountConvention {
+        /// The number of days between `start` and `end` as counted by the convention.
+        pub fn day_count(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+            if end < start {
+                return -self.day_count(end, start);
+            }
+            let (y1, m1, y2, m2) = (start.year(), start.month(), end.year(), end.month());
+            let (mut d1, mut d2) = (start.day(), end.day());
+            match self {
+                DayCountConvention::Thirty360Us => {
+                    if is_last_day_of_february(start) && is_last_day_of_february(end) {
+                        d2 = 30;
+                    }
+                    if is_last_day_of_february(start) {
+                        d1 = 30;
+                    }
+                    if d2 == 31 && d1 >= 30 {
+                        d2 = 30;
+                    }
+                    if d1 == 31 {
+                        d1 = 30;
+                    }
+                    thirty_360_days(y1, m1, d1, y2, m2, d2)
+                }
+                DayCountConvention::ThirtyE360 => {
+                    thirty_360_days(y1, m1, d1.min(30), y2, m2, d2.min(30))
+                }
+                DayCountConvention::ThirtyE360Isda => {
+                    if is_last_day_of_month(start) {
+                        d1 = 30;
+                    }
+                    if is_last_day_of_month(end) {
+                        d2 = 30;
+                    }
+                    thirty_360_days(y1, m1, d1, y2, m2, d2)
+                }
+                DayCountConvention::Bus252(calendar) => calendar.business_days_between(start, end),
+                _ => (end - start).num_days(),
+            }
+        }
+
+        /// The year fraction between `start` and `end`.
+        ///
+        /// `ActActIcma` needs a reference period; without one, annual reference periods are
+        /// rolled back from `end`, which gives whole years between anniversary dates.
+        /// Use [`DayCountConvention::year_fraction_in_period`] for coupon accruals.
+        pub fn year_fraction(&self, start: NaiveDate, end: NaiveDate) -> f64 {
+            if end < start {
+                return -self.year_fraction(end, start);
+            }
+            match self {
+                DayCountConvention::Act360 => self.day_count(start, end) as f64 / 360.0,
+                DayCountConvention::Act365Fixed => self.day_count(start, end) as f64 / 365.0,
+                DayCountConvention::ActActIsda => {
+                    if start.year() == end.year() {
+                        return (end - start).num_days() as f64 / days_in_year(start.year());
+                    }
+                    let start_next = NaiveDate::from_ymd_opt(start.year() + 1, 1, 1).unwrap();
+                    let end_begin = NaiveDate::from_ymd_opt(end.year(), 1, 1).unwrap();
+                    (start_next - start).num_days() as f64 / days_in_year(start.year())
+                        + (end.year() - start.year() - 1) as f64
+                        + (end - end_begin).num_days() as f64 / days_in_year(end.year())
+                }
+                DayCountConvention::ActActIcma => {
+                    let mut years = 0.0;
+                    let mut period_end = end;
+                    let mut period_start = end.checked_sub_months(Months::new(12)).unwrap();
+                    while period_start >= start {
+                        years += 1.0;
+                        period_end = period_start;
+                        period_start = period_end.checked_sub_months(Months::new(12)).unwrap();
+                    }
+                    years
+                        + (period_end - start).num_days() as f64
+                            / (period_end - period_start).num_days() as f64
+                }
+                DayCountConvention::Thirty360Us
+                | DayCountConvention::ThirtyE360
+                | DayCountConvention::ThirtyE360Isda => self.day_count(start, end) as f64 / 360.0,
+                DayCountConvention::Bus252(_) => self.day_count(start, end) as f64 / 252.0,
+            }
+        }
+
+        /// The year fraction between `start` and `end` that fall in the coupon period
+        /// `period_start` to `period_end`. Only `ActActIcma` depends on the reference period,
+        /// the other conventions return the same value as [`DayCountConvention::year_fraction`].
+        pub fn year_fraction_in_period(
+            &self,
+            start: NaiveDate,
+            end: NaiveDate,
+            period_start: NaiveDate,
+            period_end: NaiveDate,
+            periodicity: Periodicity,
+        ) -> f64 {
+            match self {
+                DayCountConvention::ActActIcma => {
+                    let period_days = (period_end - period_start).num_days() as f64;
+                    (end - start).num_days() as f64 / (periodicity.periods_per_year() * period_days)
+                }
+                _ => self.year_fraction(start, end),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bond::bond::Periodicity;
+    use crate::calendar::calendar::Calendar;
+    use crate::day_count::day_count::DayCountConvention;
+    use assert_approx_eq::assert_approx_eq;
+    use chrono::NaiveDate;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap()
+    }
+
+    #[test]
+    fn test_actual_conventions() {
+        let start = date("01/13/2025");
+        let end = date("07/14/2025");
+        assert_eq!(182, DayCountConvention::Act360.day_count(start, end));
+        assert_approx_eq!(
+            DayCountConvention::Act360.year_fraction(start, end),
+            182.0 / 360.0
+        );
+        assert_approx_eq!(
+            DayCountConvention::Act365Fixed.year_fraction(start, end),
+            182.0 / 365.0
+        );
+        assert_approx_eq!(
+            DayCountConvention::Act360.year_fraction(end, start),
+            -182.0 / 360.0
+        );
+    }
+
+    #[test]
+    fn test_act_act_isda() {
+        let yf =
+            DayCountConvention::ActActIsda.year_fraction(date("11/01/2003"), date("05/01/2004"));
+        assert_approx_eq!(yf, 61.0 / 365.0 + 121.0 / 366.0, 1e-6);
+    }
+
+    #[test]
+    fn test_act_act_icma() {
+        let yf = DayCountConvention::ActActIcma.year_fraction_in_period(
+            date("11/15/2020"),
+            date("02/01/2021"),
+            date("11/15/2020"),
+            date("05/15/2021"),
+            Periodicity::SemiAnnual,
+        );
+        assert_approx_eq!(yf, 78.0 / (2.0 * 181.0), 1e-6);
+        let years =
+            DayCountConvention::ActActIcma.year_fraction(date("04/15/2021"), date("04/15/2051"));
+        assert_approx_eq!(years, 30.0, f64::EPSILON);
+    }
+
+    #[test]
+    fn test_thirty_360() {
+        let start = date("02/28/2007");
+        let end = date("03/31/2007");
+        assert_eq!(30, DayCountConvention::Thirty360Us.day_count(start, end));
+        assert_eq!(32, DayCountConvention::ThirtyE360.day_count(start, end));
+        assert_eq!(30, DayCountConvention::ThirtyE360Isda.day_count(start, end));
+        assert_eq!(
+            360,
+            DayCountConvention::Thirty360Us.day_count(date("01/31/2020"), date("01/31/2021"))
+        );
+    }
+
+    #[test]
+    fn test_bus_252() {
+        let bus_252 = DayCountConvention::Bus252(Calendar::Brazil);
+        // Friday to the following Friday.
+        let start = date("01/03/2025");
+        let end = date("01/10/2025");
+        assert_eq!(5, bus_252.day_count(start, end));
+        assert_approx_eq!(bus_252.year_fraction(start, end), 5.0 / 252.0);
+        // Carnival Monday and Tuesday (March 3rd and 4th 2025) are not counted.
+        let start = date("02/28/2025");
+        let end = date("03/07/2025");
+        assert_eq!(3, bus_252.day_count(start, end));
+        assert_approx_eq!(bus_252.year_fraction(start, end), 3.0 / 252.0);
+        assert_eq!(
+            5,
+            DayCountConvention::Bus252(Calendar::WeekendsOnly).day_count(start, end)
+        );
+    }
+}
@@ -0,0 +1,739 @@
+//! Floating rate notes. The coupon of a floating rate note is an index rate plus a quoted
+//! margin, reset every period. Coupons which have not fixed yet are projected from the
+//! forward rates of a projection curve, the fixings already published are taken from the
+//! overnight rate history.
+pub mod frn {
+    use crate::bond::bond::Periodicity;
+    use crate::calendar::calendar::{BusinessDayConvention, Calendar};
+    use crate::compounding::compounding::OvernightCompounding;
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::interest_rate_swap::interest_rate_swap::InterestRateData;
+    use crate::rate_index::rate_index::{RateIndex, RateIndexRegistry, Tenor};
+    use crate::rates::rates::{OvernightRateType, YieldCurve};
+    use crate::schedule::schedule::{Schedule, SchedulePeriod};
+    use crate::solver::solver::{brent, SolverConfig};
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    /// The index of a floating rate note.
+    ///
+    /// * `CompoundedInArrears` - the overnight rate compounded daily over the interest
+    ///   period, known only at the end of the period. SOFR and SONIA notes pay this index.
+    /// * `TermRate` - a rate for the length of the period fixed before the period starts,
+    ///   such as term SOFR.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+    pub enum FloatingIndex {
+        CompoundedInArrears(OvernightRateType),
+        TermRate(OvernightRateType),
+    }
+
+    impl FloatingIndex {
+        pub fn rate_type(&self) -> OvernightRateType {
+            match self {
+                FloatingIndex::CompoundedInArrears(rate_type) => *rate_type,
+                FloatingIndex::TermRate(rate_type) => *rate_type,
+            }
+        }
+
+        /// The conventions of the index in the `registry`. A compounded index must be an
+        /// overnight rate and a term rate must have a tenor.
+        pub fn lookup<'a>(
+            &self,
+            registry: &'a RateIndexRegistry,
+        ) -> Result<&'a RateIndex, FrnError> {
+            let rate_index = registry.get(self.rate_type()).map_err(|_| FrnError {
+                message: "The index is not in the registry.",
+                message_code: FrnErrorType::UnknownIndex,
+            })?;
+            match (self, rate_index.tenor) {
+                (FloatingIndex::CompoundedInArrears(_), Tenor::Overnight)
+                | (FloatingIndex::TermRate(_), Tenor::Months(_)) => Ok(rate_index),
+                _ => Err(FrnError {
+                    message: "Only overnight rates compound in arrears, term rates need a tenor.",
+                    message_code: FrnErrorType::MismatchedIndex,
+                }),
+            }
+        }
+
+        /// The periods of a leg paying the index, the tenor of a term rate. Overnight rates
+        /// compound over any period and return `None`.
+        pub fn periodicity(&self, rate_index: &RateIndex) -> Result<Option<Periodicity>, FrnError> {
+            match rate_index.tenor {
+                Tenor::Overnight => Ok(None),
+                tenor => tenor.periodicity().map(Some).ok_or(FrnError {
+                    message: "No schedule resets at the tenor of the index.",
+                    message_code: FrnErrorType::MismatchedIndex,
+                }),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum FrnErrorType {
+        MissingFixing,
+        MismatchedIndex,
+        UnknownIndex,
+        NoSolution,
+    }
+
+    #[derive(Debug, Copy, Clone)]
+    pub struct FrnError {
+        pub message: &'static str,
+        pub message_code: FrnErrorType,
+    }
+
+    /// A floating rate note paying the `index` plus the `quoted_margin` on the `principal`.
+    /// The margin, `cap` and `floor` are decimals, the cap and floor apply to the coupon
+    /// rate, index plus margin.
+    ///
+    /// ### Fixing and reset lags
+    /// A term rate is fixed `fixing_lag` business days before the start of the period. For
+    /// a compounded rate the overnight rate of each day is observed `fixing_lag` business
+    /// days earlier (a lookback), so the coupon is known before it is paid. The rate stops
+    /// resetting `reset_lag` business days before the end of the period (the rate cut-off),
+    /// the remaining days accrue at the rate of the cut-off date. With an
+    /// `observation_shift` the whole observation period is shifted back by the `fixing_lag`
+    /// and the rates are weighted by the days of the shifted period, see
+    /// [`OvernightCompounding`]. Term rates reset once a period and ignore the `reset_lag`.
+    ///
+    /// Coupons are paid `payment_delay` business days after the end of the period, the
+    /// payment delay of SOFR swaps and notes.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct FloatingRateNote {
+        pub principal: f64,
+        pub issue_date: NaiveDate,
+        pub maturity_date: NaiveDate,
+        pub periodicity: Periodicity,
+        pub index: FloatingIndex,
+        pub quoted_margin: f64,
+        pub cap: Option<f64>,
+        pub floor: Option<f64>,
+        pub fixing_lag: u32,
+        pub reset_lag: u32,
+        #[serde(default)]
+        pub observation_shift: bool,
+        #[serde(default)]
+        pub payment_delay: u32,
+        pub day_count: DayCountConvention,
+        pub calendar: Calendar,
+        pub payment_convention: BusinessDayConvention,
+        pub accrual_convention: BusinessDayConvention,
+    }
+
+    /// A coupon of a floating rate note. `index_rate` is the fixed or projected rate of the
+    /// index for the period, `fixed` is true when every fixing it depends on is published.
+    /// The `amount` of the last coupon includes the principal.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct FloatingCashFlow {
+        pub accrual_start: NaiveDate,
+        pub accrual_end: NaiveDate,
+        pub payment_date: NaiveDate,
+        pub year_fraction: f64,
+        pub index_rate: f64,
+        pub coupon_rate: f64,
+        pub amount: f64,
+        pub fixed: bool,
+    }
+
+    impl FloatingRateNote {
+        /// A note paying quarterly on *ACT/360* with the US Government Securities calendar,
+        /// modified following, a fixing lag of two business days without observation shift, no
+        /// payment delay and no cap or floor.
+        pub fn new(
+            principal: f64,
+            issue_date: NaiveDate,
+            maturity_date: NaiveDate,
+            index: FloatingIndex,
+            quoted_margin: f64,
+        ) -> FloatingRateNote {
+            FloatingRateNote {
+                principal,
+                issue_date,
+                maturity_date,
+                periodicity: Periodicity::Quarterly,
+                index,
+                quoted_margin,
+                cap: None,
+                floor: None,
+                fixing_lag: 2,
+                reset_lag: 0,
+                observation_shift: false,
+                payment_delay: 0,
+                day_count: DayCountConvention::Act360,
+                calendar: Calendar::Sifma,
+                payment_convention: BusinessDayConvention::ModifiedFollowing,
+                accrual_convention: BusinessDayConvention::ModifiedFollowing,
+            }
+        }
+
+        /// A note on the conventions of the `index` in the `registry`, its day count and
+        /// fixing calendar and, for a term rate, periods of its tenor. Otherwise as
+        /// [`FloatingRateNote::new`].
+        pub fn for_index(
+            principal: f64,
+            issue_date: NaiveDate,
+            maturity_date: NaiveDate,
+            index: FloatingIndex,
+            quoted_margin: f64,
+            registry: &RateIndexRegistry,
+        ) -> Result<FloatingRateNote, FrnError> {
+            let rate_index = index.lookup(registry)?;
+            let mut note =
+                FloatingRateNote::new(principal, issue_date, maturity_date, index, quoted_margin);
+            note.day_count = rate_index.day_count.clone();
+            note.calendar = rate_index.calendar.clone();
+            if let Some(periodicity) = index.periodicity(rate_index)? {
+                note.periodicity = periodicity;
+            }
+            Ok(note)
+        }
+
+        pub fn schedule(&self) -> Schedule {
+            let mut schedule = Schedule::new(self.issue_date, self.maturity_date, self.periodicity);
+            schedule.calendar = self.calendar.clone();
+            schedule.accrual_convention = self.accrual_convention;
+            schedule.payment_convention = self.payment_convention;
+            schedule
+        }
+
+        /// The fixings of the index of the note by date, as decimals.
+        fn fixings_by_date(
+            &self,
+            fixings: &[InterestRateData],
+        ) -> Result<BTreeMap<NaiveDate, f64>, FrnError> {
+            let rate_type = self.index.rate_type();
+            let mut result = BTreeMap::new();
+            for fixing in fixings {
+                if fixing.overnight_rate_type != rate_type {
+                    return Err(FrnError {
+                        message: "The fixings are for a different index.",
+                        message_code: FrnErrorType::MismatchedIndex,
+                    });
+                }
+                result.insert(fixing.time, fixing.rate / 100.0);
+            }
+            Ok(result)
+        }
+
+        /// The simply compounded forward rate from `start` to `end` off the `projection`.
+        fn forward_rate(&self, projection: &YieldCurve, start: NaiveDate, end: NaiveDate) -> f64 {
+            let year_fraction = self.day_count.year_fraction(start, end);
+            (projection.discount_at(start) / projection.discount_at(end) - 1.0) / year_fraction
+        }
+
+        /// The rate fixed on `date` for the period to `end`, and whether it is published.
+        /// Fixings before the date of the `projection` must be in the history.
+        fn observe(
+            &self,
+            date: NaiveDate,
+            end: NaiveDate,
+            fixings: &BTreeMap<NaiveDate, f64>,
+            projection: &YieldCurve,
+        ) -> Result<(f64, bool), FrnError> {
+            match fixings.get(&date) {
+                Some(rate) => Ok((*rate, true)),
+                None if date < projection.as_of => Err(FrnError {
+                    message: "No fixing for a date before the projection curve.",
+                    message_code: FrnErrorType::MissingFixing,
+                }),
+                None => Ok((self.forward_rate(projection, date, end), false)),
+            }
+        }
+
+        /// The index rate for the `period` accrued to `end`.
+        fn index_rate(
+            &self,
+            period: &SchedulePeriod,
+            end: NaiveDate,
+            fixings: &BTreeMap<NaiveDate, f64>,
+            projection: &YieldCurve,
+        ) -> Result<(f64, bool), FrnError> {
+            let lag = self.fixing_lag as i32;
+            match self.index {
+                FloatingIndex::TermRate(_) => {
+                    let fixing_date = self.calendar.advance(period.accrual_start, -lag);
+                    match fixings.get(&fixing_date) {
+                        Some(rate) => Ok((*rate, true)),
+                        None if fixing_date < projection.as_of => Err(FrnError {
+                            message: "No fixing for a date before the projection curve.",
+                            message_code: FrnErrorType::MissingFixing,
+                        }),
+                        None => Ok((
+                            self.forward_rate(projection, period.accrual_start, period.accrual_end),
+                            false,
+                        )),
+                    }
+                }
+                FloatingIndex::CompoundedInArrears(_) => {
+                    let conventions = OvernightCompounding {
+                        calendar: self.calendar.clone(),
+                        day_count: self.day_count.clone(),
+                        lookback: self.fixing_lag,
+                        observation_shift: self.observation_shift,
+                        lockout: self.reset_lag,
+                    };
+                    conventions.compounded_rate(
+                        period.accrual_start,
+                        period.accrual_end,
+                        end,
+                        |observation| {
+                            self.observe(
+                                observation,
+                                self.calendar.advance(observation, 1),
+                                fixings,
+                                projection,
+                            )
+                        },
+                    )
+                }
+            }
+        }
+
+        /// The index rate plus the margin, between the floor and the cap.
+        pub fn coupon_rate(&self, index_rate: f64) -> f64 {
+            let mut rate = index_rate + self.quoted_margin;
+            if let Some(floor) = self.floor {
+                rate = rate.max(floor);
+            }
+            if let Some(cap) = self.cap {
+                rate = rate.min(cap);
+            }
+            rate
+        }
+
+        /// The coupons paid after `settlement`, with the published `fixings` of the index
+        /// and the forward rates of the `projection` curve for the rest. Caps and floors are
+        /// applied to the projected rates, which leaves out the time value of the options.
+        pub fn projected_cashflows(
+            &self,
+            settlement: NaiveDate,
+            fixings: &[InterestRateData],
+            projection: &YieldCurve,
+        ) -> Result<Vec<FloatingCashFlow>, FrnError> {
+            let fixings = self.fixings_by_date(fixings)?;
+            let periods = self.schedule().periods();
+            let last = periods.len().saturating_sub(1);
+            let mut result = Vec::new();
+            for (i, period) in periods.iter().enumerate() {
+                let payment_date = self
+                    .calendar
+                    .advance(period.payment_date, self.payment_delay as i32);
+                if payment_date <= settlement {
+                    continue;
+                }
+                let (index_rate, fixed) =
+                    self.index_rate(period, period.accrual_end, &fixings, projection)?;
+                let coupon_rate = self.coupon_rate(index_rate);
+                let year_fraction = self
+                    .day_count
+                    .year_fraction(period.accrual_start, period.accrual_end);
+                let mut amount = self.principal * coupon_rate * year_fraction;
+                if i == last {
+                    amount += self.principal;
+                }
+                result.push(FloatingCashFlow {
+                    accrual_start: period.accrual_start,
+                    accrual_end: period.accrual_end,
+                    payment_date,
+                    year_fraction,
+                    index_rate,
+                    coupon_rate,
+                    amount,
+                    fixed,
+                });
+            }
+            Ok(result)
+        }
+
+        /// The interest accrued to `settlement`. A compounded index accrues at the rate
+        /// compounded up to the settlement date.
+        pub fn accrued_interest(
+            &self,
+            settlement: NaiveDate,
+            fixings: &[InterestRateData],
+            projection: &YieldCurve,
+        ) -> Result<f64, FrnError> {
+            let fixings = self.fixings_by_date(fixings)?;
+            let period = self
+                .schedule()
+                .periods()
+                .into_iter()
+                .find(|p| p.accrual_start <= settlement && settlement < p.accrual_end);
+            match period {
+                Some(period) => {
+                    let (index_rate, _) =
+                        self.index_rate(&period, settlement, &fixings, projection)?;
+                    Ok(self.principal
+                        * self.coupon_rate(index_rate)
+                        * self
+                            .day_count
+                            .year_fraction(period.accrual_start, settlement))
+                }
+                None => Ok(0.0),
+            }
+        }
+
+        /// The dirty price at `settlement` of the projected coupons discounted off the
+        /// `discount` curve, which may differ from the `projection` curve.
+        pub fn dirty_price(
+            &self,
+            settlement: NaiveDate,
+            fixings: &[InterestRateData],
+            projection: &YieldCurve,
+            discount: &YieldCurve,
+        ) -> Result<f64, FrnError> {
+            let settlement_discount = discount.discount_at(settlement);
+            Ok(self
+                .projected_cashflows(settlement, fixings, projection)?
+                .iter()
+                .map(|cf| cf.amount * discount.discount_at(cf.payment_date) / settlement_discount)
+                .sum())
+        }
+
+        /// The dirty price at `settlement` when every coupon is discounted at its index rate
+        /// plus the `discount_margin`, compounding period by period:
+        ///
+        ///     price = sum(amount_i / prod(1 + (index_j + discount_margin) * year_fraction_j, j <= i))
+        ///
+        /// The first period is discounted from the settlement date.
+        pub fn price_at_discount_margin(
+            &self,
+            settlement: NaiveDate,
+            fixings: &[InterestRateData],
+            projection: &YieldCurve,
+            discount_margin: f64,
+        ) -> Result<f64, FrnError> {
+            let cashflows = self.projected_cashflows(settlement, fixings, projection)?;
+            Ok(self.discount_at_margin(settlement, &cashflows, discount_margin))
+        }
+
+        fn discount_at_margin(
+            &self,
+            settlement: NaiveDate,
+            cashflows: &[FloatingCashFlow],
+            discount_margin: f64,
+        ) -> f64 {
+            let mut discount = 1.0;
+            let mut price = 0.0;
+            for cf in cashflows {
+                let start = cf.accrual_start.max(settlement);
+                let year_fraction = self.day_count.year_fraction(start, cf.accrual_end);
+                discount /= 1.0 + (cf.index_rate + discount_margin) * year_fraction;
+                price += cf.amount * discount;
+            }
+            price
+        }
+
+        /// The discount margin which prices the projected coupons at the `dirty_price`.
+        pub fn discount_margin(
+            &self,
+            settlement: NaiveDate,
+            fixings: &[InterestRateData],
+            projection: &YieldCurve,
+            dirty_price: f64,
+        ) -> Result<f64, FrnError> {
+            let cashflows = self.projected_cashflows(settlement, fixings, projection)?;
+            brent(
+                |margin| self.discount_at_margin(settlement, &cashflows, margin) - dirty_price,
+                -0.1,
+                1.0,
+                &SolverConfig::default(),
+            )
+            .map_err(|_| FrnError {
+                message: "No discount margin prices the note at the given price.",
+                message_code: FrnErrorType::NoSolution,
+            })
+        }
+
+        /// The percentage change in price for a change in the discount margin, by a central
+        /// difference of one basis point.
+        pub fn spread_duration(
+            &self,
+            settlement: NaiveDate,
+            fixings: &[InterestRateData],
+            projection: &YieldCurve,
+            discount_margin: f64,
+        ) -> Result<f64, FrnError> {
+            let cashflows = self.projected_cashflows(settlement, fixings, projection)?;
+            let bump = 0.0001;
+            let price = self.discount_at_margin(settlement, &cashflows, discount_margin);
+            let down = self.discount_at_margin(settlement, &cashflows, discount_margin - bump);
+            let up = self.discount_at_margin(settlement, &cashflows, discount_margin + bump);
+            Ok((down - up) / (2.0 * bump * price))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bond::bond::{DiscountFactor, Periodicity};
+    use crate::calendar::calendar::Calendar;
+    use crate::compounding::compounding::OvernightCompounding;
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::frn::frn::*;
+    use crate::interest_rate_swap::interest_rate_swap::InterestRateData;
+    use crate::rate_index::rate_index::RateIndexRegistry;
+    use crate::rates::rates::{OvernightRateType, YieldCurve};
+    use assert_approx_eq::assert_approx_eq;
+    use chrono::{Days, NaiveDate};
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap()
+    }
+
+    fn flat_curve(as_of: NaiveDate, rate: f64) -> YieldCurve {
+        let discount_factors: Vec<DiscountFactor> = (1..=40)
+            .map(|i| {
+                let term = i as f64 * 0.25;
+                DiscountFactor {
+                    term,
+                    discount: f64::exp(-rate * term),
+                }
+            })
+            .collect();
+        YieldCurve::from_discount_factors(
+            as_of,
+            DayCountConvention::Act360,
+            Periodicity::Quarterly,
+            &discount_factors,
+        )
+        .unwrap()
+    }
+
+    fn fixings(start: &str, days: u64, rate: f64) -> Vec<InterestRateData> {
+        (0..days)
+            .map(|i| InterestRateData {
+                time: date(start) + Days::new(i),
+                rate,
+                overnight_rate_type: OvernightRateType::SOFR,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_frn_prices_at_par() {
+        let issue = date("01/16/2024");
+        let curve = flat_curve(issue, 0.05);
+        for index in [
+            FloatingIndex::TermRate(OvernightRateType::SOFR),
+            FloatingIndex::CompoundedInArrears(OvernightRateType::SOFR),
+        ] {
+            let mut frn = FloatingRateNote::new(100.0, issue, date("01/16/2027"), index, 0.0);
+            frn.fixing_lag = 0;
+            let cashflows = frn.projected_cashflows(issue, &[], &curve).unwrap();
+            assert_eq!(cashflows.len(), 12);
+            assert!(cashflows.iter().all(|cf| !cf.fixed));
+            let year_fraction = cashflows[0].year_fraction;
+            assert_approx_eq!(
+                cashflows[0].index_rate,
+                (f64::exp(0.05 * year_fraction) - 1.0) / year_fraction,
+                1e-12
+            );
+            assert_approx_eq!(
+                frn.dirty_price(issue, &[], &curve, &curve).unwrap(),
+                100.0,
+                1e-9
+            );
+            assert_approx_eq!(
+                frn.price_at_discount_margin(issue, &[], &curve, 0.0)
+                    .unwrap(),
+                100.0,
+                1e-9
+            );
+        }
+        let mut frn = FloatingRateNote::new(
+            100.0,
+            issue,
+            date("01/16/2027"),
+            FloatingIndex::CompoundedInArrears(OvernightRateType::SOFR),
+            0.01,
+        );
+        frn.fixing_lag = 0;
+        let price = frn
+            .price_at_discount_margin(issue, &[], &curve, 0.012)
+            .unwrap();
+        assert!(price < 100.0);
+        let margin = frn.discount_margin(issue, &[], &curve, price).unwrap();
+        assert_approx_eq!(margin, 0.012, 1e-9);
+        // Close to the time to maturity for a note at par.
+        let duration = frn.spread_duration(issue, &[], &curve, 0.01).unwrap();
+        assert!(2.7 < duration && duration < 3.0, "{}", duration);
+    }
+
+    #[test]
+    fn test_frn_fixings_caps_and_floors() {
+        let issue = date("01/16/2024");
+        let today = date("02/15/2024");
+        let curve = flat_curve(today, 0.05);
+        let mut frn = FloatingRateNote::new(
+            100.0,
+            issue,
+            date("01/16/2026"),
+            FloatingIndex::TermRate(OvernightRateType::SOFR),
+            0.005,
+        );
+        // The first coupon fixed two business days before the issue date, Martin Luther King
+        // Day on the Monday is not a business day.
+        let history = fixings("01/11/2024", 1, 5.5);
+        let cashflows = frn.projected_cashflows(today, &history, &curve).unwrap();
+        assert!(cashflows[0].fixed && !cashflows[1].fixed);
+        assert_approx_eq!(cashflows[0].coupon_rate, 0.06, 1e-12);
+        assert_approx_eq!(cashflows[0].amount, 100.0 * 0.06 * 91.0 / 360.0, 1e-9);
+        assert_approx_eq!(
+            frn.accrued_interest(today, &history, &curve).unwrap(),
+            100.0 * 0.06 * 30.0 / 360.0,
+            1e-9
+        );
+        frn.cap = Some(0.055);
+        frn.floor = Some(0.0575);
+        assert_approx_eq!(frn.coupon_rate(0.07), 0.055, 1e-12);
+        frn.cap = None;
+        assert_approx_eq!(frn.coupon_rate(0.03), 0.0575, 1e-12);
+
+        let missing = frn.projected_cashflows(today, &[], &curve);
+        assert_eq!(
+            missing.unwrap_err().message_code,
+            FrnErrorType::MissingFixing
+        );
+        let sonia = vec![InterestRateData {
+            time: date("01/11/2024"),
+            rate: 5.2,
+            overnight_rate_type: OvernightRateType::SONIA,
+        }];
+        let mismatched = frn.projected_cashflows(today, &sonia, &curve);
+        assert_eq!(
+            mismatched.unwrap_err().message_code,
+            FrnErrorType::MismatchedIndex
+        );
+
+        // Compounded in arrears with a two day lookback; the published fixings are used up
+        // to today and the curve after.
+        let mut frn = FloatingRateNote::new(
+            100.0,
+            issue,
+            date("01/16/2026"),
+            FloatingIndex::CompoundedInArrears(OvernightRateType::SOFR),
+            0.0,
+        );
+        frn.reset_lag = 2;
+        let history = fixings("01/01/2024", 45, 5.3);
+        let cashflows = frn.projected_cashflows(today, &history, &curve).unwrap();
+        assert!(!cashflows[0].fixed);
+        assert!(0.05 < cashflows[0].index_rate && cashflows[0].index_rate < 0.053);
+        let all_history = fixings("01/01/2024", 120, 5.3);
+        let fixed = frn
+            .projected_cashflows(today, &all_history, &curve)
+            .unwrap();
+        assert!(fixed[0].fixed);
+        // Daily compounding at 5.3% over the quarter.
+        assert!(0.053 < fixed[0].index_rate && fixed[0].index_rate < 0.0535);
+    }
+
+    #[test]
+    fn test_frn_observation_shift_and_payment_delay() {
+        let issue = date("01/16/2024");
+        let today = date("04/17/2024");
+        let curve = flat_curve(today, 0.05);
+        let mut frn = FloatingRateNote::new(
+            100.0,
+            issue,
+            date("01/16/2025"),
+            FloatingIndex::CompoundedInArrears(OvernightRateType::SOFR),
+            0.0,
+        );
+        frn.observation_shift = true;
+        frn.payment_delay = 2;
+        let history = fixings("01/01/2024", 120, 5.3);
+        // The period ended on 04/16 and is paid two business days later.
+        let cashflows = frn.projected_cashflows(today, &history, &curve).unwrap();
+        assert_eq!(cashflows[0].accrual_end, date("04/16/2024"));
+        assert_eq!(cashflows[0].payment_date, date("04/18/2024"));
+        assert!(cashflows[0].fixed);
+        let paid = frn
+            .projected_cashflows(date("04/18/2024"), &history, &curve)
+            .unwrap();
+        assert_eq!(paid[0].accrual_start, date("04/16/2024"));
+
+        // With the observation period shifted two business days the coupon is the rate
+        // of the SOFR Index from 01/11 to 04/12.
+        let registry = RateIndexRegistry::default();
+        let sofr = OvernightCompounding::new(registry.get(OvernightRateType::SOFR).unwrap());
+        let index = sofr.index(&history, date("01/02/2024"), 1.0);
+        let (start, end) = (date("01/11/2024"), date("04/12/2024"));
+        assert_approx_eq!(
+            cashflows[0].index_rate,
+            sofr.rate_from_index(start, index[&start], end, index[&end]),
+            1e-6
+        );
+        frn.observation_shift = false;
+        let lookback = frn.projected_cashflows(today, &history, &curve).unwrap();
+        assert!((lookback[0].index_rate - cashflows[0].index_rate).abs() > 1e-8);
+    }
+
+    #[test]
+    fn test_frn_on_registered_index() {
+        let registry = RateIndexRegistry::default();
+        let issue = date("01/16/2024");
+        let corra = FloatingRateNote::for_index(
+            100.0,
+            issue,
+            date("01/16/2027"),
+            FloatingIndex::CompoundedInArrears(OvernightRateType::CORRA),
+            0.0,
+            &registry,
+        )
+        .unwrap();
+        assert_eq!(corra.day_count, DayCountConvention::Act365Fixed);
+        assert_eq!(corra.calendar, Calendar::Canada);
+        assert!(matches!(corra.periodicity, Periodicity::Quarterly));
+        let term = FloatingRateNote::for_index(
+            100.0,
+            issue,
+            date("01/16/2027"),
+            FloatingIndex::TermRate(OvernightRateType::TermSOFR6M),
+            0.0,
+            &registry,
+        )
+        .unwrap();
+        assert!(matches!(term.periodicity, Periodicity::SemiAnnual));
+        assert_eq!(term.schedule().periods().len(), 6);
+
+        // Term SOFR does not compound, SOFR has no term and one month has no schedule.
+        for index in [
+            FloatingIndex::CompoundedInArrears(OvernightRateType::TermSOFR3M),
+            FloatingIndex::TermRate(OvernightRateType::SOFR),
+            FloatingIndex::TermRate(OvernightRateType::TermSOFR1M),
+        ] {
+            let error = FloatingRateNote::for_index(
+                100.0,
+                issue,
+                date("01/16/2027"),
+                index,
+                0.0,
+                &registry,
+            )
+            .unwrap_err();
+            assert_eq!(error.message_code, FrnErrorType::MismatchedIndex);
+        }
+        let error = FloatingRateNote::for_index(
+            100.0,
+            issue,
+            date("01/16/2027"),
+            FloatingIndex::CompoundedInArrears(OvernightRateType::SOFR),
+            0.0,
+            &RateIndexRegistry::new(),
+        )
+        .unwrap_err();
+        assert_eq!(error.message_code, FrnErrorType::UnknownIndex);
+
+        // Fixings of another index are an error, not a panic.
+        let curve = flat_curve(date("02/15/2024"), 0.05);
+        let sofr_fixings = fixings("01/01/2024", 60, 5.3);
+        let mismatched = corra.projected_cashflows(date("02/15/2024"), &sofr_fixings, &curve);
+        assert_eq!(
+            mismatched.unwrap_err().message_code,
+            FrnErrorType::MismatchedIndex
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//! Inflation-linked bonds. The principal of a Treasury Inflation-Protected Security (TIPS)
+//! is indexed to the non seasonally adjusted CPI-U, the coupon rate is fixed and paid on
+//! the inflation-adjusted principal. The CPI used for a date lags the date by about three
+//! months, see [`inflation::CpiSeries::reference_cpi`].
+//!
+//! References :
+//!
+//!     . 31 CFR 356, Appendix B, Formulas for Inflation-Protected Securities
+pub mod inflation {
+    use crate::bond::bond::{Bond, CashFlow};
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::rates::rates::YieldCurve;
+    use crate::solver::solver::{brent, SolverConfig, SolverError};
+    use chrono::{Datelike, Months, NaiveDate};
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum InflationErrorType {
+        MissingCpi,
+        NoSolution,
+    }
+
+    #[derive(Debug, Copy, Clone)]
+    pub struct InflationError {
+        pub message: &'static str,
+        pub message_code: InflationErrorType,
+    }
+
+    fn first_of_month(date: NaiveDate) -> NaiveDate {
+        NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap()
+    }
+
+    /// Treasury rounds the reference CPI and the index ratio to five decimals.
+    fn round5(value: f64) -> f64 {
+        (value * 100000.0).round() / 100000.0
+    }
+
+    /// A monthly CPI series keyed by the first day of the month.
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct CpiSeries {
+        pub values: BTreeMap<NaiveDate, f64>,
+    }
+
+    impl CpiSeries {
+        /// A series from `(month, value)` pairs, any day of the month names the month.
+        pub fn new(observations: &[(NaiveDate, f64)]) -> CpiSeries {
+            CpiSeries {
+                values: observations
+                    .iter()
+                    .map(|(month, value)| (first_of_month(*month), *value))
+                    .collect(),
+            }
+        }
+
+        pub fn value(&self, month: NaiveDate) -> Option<f64> {
+            self.values.get(&first_of_month(month)).copied()
+        }
+
+        /// The last date with a reference CPI, the first day of the third month after the
+        /// last published CPI.
+        pub fn last_reference_date(&self) -> Option<NaiveDate> {
+            self.values
+                .keys()
+                .next_back()
+                .map(|month| *month + Months::new(3))
+        }
+
+        /// The reference CPI for `date`. The reference CPI for the first day of a month is the
+        /// CPI of the third preceding month, for the other days it is interpolated linearly
+        /// towards the CPI of the second preceding month:
+        ///
+        ///     ref_cpi = cpi(m - 3) + (day - 1) / days_in_month * (cpi(m - 2) - cpi(m - 3))
+        pub fn reference_cpi(&self, date: NaiveDate) -> Result<f64, InflationError> {
+            let month = first_of_month(date);
+            let missing = InflationError {
+                message: "No CPI for the reference months of the date.",
+                message_code: InflationErrorType::MissingCpi,
+            };
+            let start = self.value(month - Months::new(3)).ok_or(missing)?;
+            if date.day() == 1 {
+                return Ok(round5(start));
+            }
+            let end = self.value(month - Months::new(2)).ok_or(missing)?;
+            let days_in_month = ((month + Months::new(1)) - month).num_days() as f64;
+            let weight = (date.day() - 1) as f64 / days_in_month;
+            Ok(round5(start + weight * (end - start)))
+        }
+    }
+
+    /// A bond whose principal is indexed to the CPI. The `bond` holds the real terms, the
+    /// principal at issue and the fixed coupon rate. The `base_cpi` is the reference CPI on
+    /// the dated date of the bond.
+    ///
+    /// ### Deflation floor
+    /// Coupons are paid on the adjusted principal even if it is below par, but no principal
+    /// repayment, at maturity or before it, is less than its unadjusted amount.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct InflationLinkedBond {
+        pub bond: Bond,
+        pub base_cpi: f64,
+    }
+
+    impl InflationLinkedBond {
+        /// A bond with the reference CPI of its issue date as the base.
+        pub fn new(bond: Bond, cpi: &CpiSeries) -> Result<InflationLinkedBond, InflationError> {
+            let base_cpi = cpi.reference_cpi(bond.issue_date)?;
+            Ok(InflationLinkedBond { bond, base_cpi })
+        }
+
+        /// The reference CPI for `date` over the base CPI.
+        pub fn index_ratio(&self, cpi: &CpiSeries, date: NaiveDate) -> Result<f64, InflationError> {
+            Ok(round5(cpi.reference_cpi(date)? / self.base_cpi))
+        }
+
+        pub fn adjusted_principal(
+            &self,
+            cpi: &CpiSeries,
+            date: NaiveDate,
+        ) -> Result<f64, InflationError> {
+            Ok(self.bond.principal * self.index_ratio(cpi, date)?)
+        }
+
+        /// The index ratio for `date`, projected from the last reference CPI at the annual
+        /// `inflation` rate for dates past the published CPI.
+        fn projected_index_ratio(
+            &self,
+            cpi: &CpiSeries,
+            date: NaiveDate,
+            inflation: f64,
+        ) -> Result<f64, InflationError> {
+            match cpi.last_reference_date() {
+                Some(last) if date > last => {
+                    let years = DayCountConvention::Act365Fixed.year_fraction(last, date);
+                    Ok(self.index_ratio(cpi, last)? * f64::powf(1.0 + inflation, years))
+                }
+                _ => self.index_ratio(cpi, date),
+            }
+        }
+
+        /// The nominal cashflows paid after `settlement`, coupons and principal adjusted by
+        /// the index ratio of the payment date. Past the published CPI the index is
+        /// projected at the annual `inflation` rate. Every principal repayment is floored at its
+        /// unadjusted amount.
+        pub fn projected_cashflows(
+            &self,
+            settlement: NaiveDate,
+            cpi: &CpiSeries,
+            inflation: f64,
+        ) -> Result<Vec<CashFlow>, InflationError> {
+            let mut result = Vec::new();
+            for (time, interest, principal) in self.bond.payments() {
+                if time <= settlement {
+                    continue;
+                }
+                let ratio = self.projected_index_ratio(cpi, time, inflation)?;
+                result.push(CashFlow {
+                    bond: self.bond.clone(),
+                    time,
+                    amount: interest * ratio + principal * ratio.max(1.0),
+                });
+            }
+            Ok(result)
+        }
+
+        /// The amount paid at `settlement` for a quoted real `clean_price`, the real price and
+        /// accrued interest times the index ratio.
+        pub fn invoice_price(
+            &self,
+            settlement: NaiveDate,
+            cpi: &CpiSeries,
+            clean_price: f64,
+        ) -> Result<f64, InflationError> {
+            let real_dirty_price = clean_price + self.bond.accrued_interest(settlement);
+            Ok(real_dirty_price * self.index_ratio(cpi, settlement)?)
+        }
+
+        /// The real yield, the yield of the real cashflows at the quoted real `clean_price`.
+        pub fn real_yield(
+            &self,
+            settlement: NaiveDate,
+            clean_price: f64,
+        ) -> Result<f64, SolverError> {
+            self.bond.yield_from_clean_price(settlement, clean_price)
+        }
+
+        /// The constant annual inflation rate at which the projected nominal cashflows,
+        /// discounted off the `nominal` curve, are worth the invoice price. The value of the
+        /// deflation floor is included only when it is in the money at that rate.
+        pub fn breakeven_inflation(
+            &self,
+            settlement: NaiveDate,
+            cpi: &CpiSeries,
+            clean_price: f64,
+            nominal: &YieldCurve,
+        ) -> Result<f64, InflationError> {
+            let target = self.invoice_price(settlement, cpi, clean_price)?;
+            let settlement_discount = nominal.discount_at(settlement);
+            let value = |inflation: f64| -> f64 {
+                match self.projected_cashflows(settlement, cpi, inflation) {
+                    Ok(cashflows) => {
+                        cashflows
+                            .iter()
+                            .map(|cf| cf.amount * nominal.discount_at(cf.time))
+                            .sum::<f64>()
+                            / settlement_discount
+                            - target
+                    }
+                    Err(_) => f64::NAN,
+                }
+            };
+            // The index ratios of the published CPI must exist before solving.
+            self.projected_cashflows(settlement, cpi, 0.0)?;
+            brent(value, -0.2, 0.5, &SolverConfig::default()).map_err(|_| InflationError {
+                message: "No inflation rate prices the bond off the nominal curve.",
+                message_code: InflationErrorType::NoSolution,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bond::bond::{create_bond, CashFlow, DiscountFactor, Periodicity, PrincipalPayment};
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::inflation::inflation::*;
+    use crate::rates::rates::YieldCurve;
+    use assert_approx_eq::assert_approx_eq;
+    use chrono::NaiveDate;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap()
+    }
+
+    fn cpi_u() -> CpiSeries {
+        let values = [
+            ("09/01/2023", 307.789),
+            ("10/01/2023", 307.671),
+            ("11/01/2023", 307.051),
+            ("12/01/2023", 306.746),
+            ("01/01/2024", 308.417),
+            ("02/01/2024", 310.326),
+            ("03/01/2024", 312.332),
+        ];
+        let observations: Vec<(NaiveDate, f64)> =
+            values.iter().map(|(d, v)| (date(d), *v)).collect();
+        CpiSeries::new(&observations)
+    }
+
+    #[test]
+    fn test_reference_cpi() {
+        // The example of 31 CFR 356 Appendix B.
+        let cpi = CpiSeries::new(&[(date("01/01/1996"), 154.4), (date("02/01/1996"), 155.0)]);
+        assert_approx_eq!(cpi.reference_cpi(date("04/15/1996")).unwrap(), 154.68, 1e-9);
+        assert_approx_eq!(cpi.reference_cpi(date("04/01/1996")).unwrap(), 154.4, 1e-9);
+        let missing = cpi.reference_cpi(date("06/15/1996")).unwrap_err();
+        assert_eq!(missing.message_code, InflationErrorType::MissingCpi);
+
+        let cpi = cpi_u();
+        // October 2023 plus 14/31 of the change to November.
+        assert_approx_eq!(
+            cpi.reference_cpi(date("01/15/2024")).unwrap(),
+            307.391,
+            1e-9
+        );
+        assert_eq!(cpi.last_reference_date(), Some(date("06/01/2024")));
+    }
+
+    #[test]
+    fn test_inflation_linked_bond() {
+        let cpi = cpi_u();
+        let bond = create_bond(100.0, "01/15/2024", "01/15/2034", 0.0175, "%m/%d/%Y").unwrap();
+        let tips = InflationLinkedBond::new(*bond, &cpi).unwrap();
+        assert_approx_eq!(tips.base_cpi, 307.391, 1e-9);
+        let settlement = date("04/15/2024");
+        // January 2024 plus 14/30 of the change to February, over the base.
+        let ratio = tips.index_ratio(&cpi, settlement).unwrap();
+        assert_approx_eq!(ratio, f64::round(309.30787 / 307.391 * 1e5) / 1e5, 1e-12);
+        assert_approx_eq!(
+            tips.adjusted_principal(&cpi, settlement).unwrap(),
+            100.0 * ratio,
+            1e-9
+        );
+
+        // The deflation floor holds the principal at par at maturity.
+        let deflation = tips.projected_cashflows(settlement, &cpi, -0.05).unwrap();
+        let last = deflation.last().unwrap();
+        assert_eq!(deflation.len(), 20);
+        assert!(last.amount > 100.0 && last.amount < 100.0 + 0.875);
+        let cashflows = tips.projected_cashflows(settlement, &cpi, 0.02).unwrap();
+        // The July coupon needs the April CPI, projected from March for 44 days.
+        let march = f64::round(312.332 / 307.391 * 1e5) / 1e5;
+        assert_approx_eq!(
+            cashflows[0].amount,
+            0.875 * march * f64::powf(1.02, 44.0 / 365.0),
+            1e-12
+        );
+
+        let real_yield = tips.real_yield(settlement, 98.0).unwrap();
+        assert!(real_yield > 0.0175);
+        // A flat nominal curve 2.5% above the real yield breaks even near 2.5% inflation.
+        let nominal_rate = real_yield + 0.025;
+        let discount_factors: Vec<DiscountFactor> = (1..=22)
+            .map(|i| {
+                let term = i as f64 * 0.5;
+                DiscountFactor {
+                    term,
+                    discount: f64::powf(1.0 + nominal_rate / 2.0, -2.0 * term),
+                }
+            })
+            .collect();
+        let nominal = YieldCurve::from_discount_factors(
+            settlement,
+            DayCountConvention::ActActIsda,
+            Periodicity::SemiAnnual,
+            &discount_factors,
+        )
+        .unwrap();
+        let breakeven = tips
+            .breakeven_inflation(settlement, &cpi, 98.0, &nominal)
+            .unwrap();
+        assert!(0.02 < breakeven && breakeven < 0.03, "{}", breakeven);
+    }
+
+    #[test]
+    fn test_amortizing_inflation_linked_bond() {
+        let cpi = cpi_u();
+        let bond = create_bond(100.0, "01/15/2024", "01/15/2034", 0.0175, "%m/%d/%Y").unwrap();
+        let bullet = InflationLinkedBond::new(*bond.clone(), &cpi).unwrap();
+        let half = PrincipalPayment {
+            date: date("01/15/2029"),
+            amount: 50.0,
+            accelerated: 0.0,
+        };
+        let amortizing = bond.with_principal_schedule(vec![half]).unwrap();
+        let tips = InflationLinkedBond::new(amortizing, &cpi).unwrap();
+        let settlement = date("04/15/2024");
+        let amount_on = |cashflows: &[CashFlow], on: &str| {
+            cashflows
+                .iter()
+                .find(|cf| cf.time == date(on))
+                .unwrap()
+                .amount
+        };
+
+        // The repayment before maturity is indexed like the coupon paid with it.
+        let bullet_flows = bullet.projected_cashflows(settlement, &cpi, 0.02).unwrap();
+        let cashflows = tips.projected_cashflows(settlement, &cpi, 0.02).unwrap();
+        let ratio = amount_on(&bullet_flows, "01/15/2029") / 0.875;
+        assert!(ratio > 1.0);
+        assert_approx_eq!(
+            amount_on(&cashflows, "01/15/2029"),
+            (0.875 + 50.0) * ratio,
+            1e-9
+        );
+        // The coupons after it are paid on the half left outstanding.
+        let ratio = amount_on(&bullet_flows, "07/15/2029") / 0.875;
+        assert_approx_eq!(amount_on(&cashflows, "07/15/2029"), 0.4375 * ratio, 1e-9);
+
+        // Under deflation both repayments are floored, the coupons are not.
+        let deflation = tips.projected_cashflows(settlement, &cpi, -0.05).unwrap();
+        let early = amount_on(&deflation, "01/15/2029") - 50.0;
+        assert!(0.0 < early && early < 0.875, "{}", early);
+        let last = deflation.last().unwrap().amount - 50.0;
+        assert!(0.0 < last && last < 0.4375, "{}", last);
+    }
+}
@@ -1,28 +1,360 @@
-mod interest_rate_swap {
-    use crate::rates::rates::OvernightRateType;
-    use chrono::NaiveDate;
+//! Interest rate swaps. A swap exchanges a fixed leg, paying a fixed rate on a notional,
+//! for a floating leg paying an index rate plus a spread. Each leg has its own schedule and
+//! conventions; the legs are priced by discounting their cashflows off a curve, with the
+//! floating coupons projected from a projection curve as for a floating rate note.
+pub mod interest_rate_swap {
+    use crate::bond::bond::Periodicity;
+    use crate::calendar::calendar::{BusinessDayConvention, Calendar};
+    use crate::compounding::compounding::OvernightCompounding;
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::frn::frn::{FloatingIndex, FloatingRateNote, FrnError, FrnErrorType};
+    use crate::rate_index::rate_index::RateIndexRegistry;
+    use crate::rates::rates::{OvernightRateType, YieldCurve};
+    use crate::schedule::schedule::Schedule;
+    use chrono::{Days, NaiveDate};
+    use log::debug;
+    use serde::{Deserialize, Serialize};
     use std::cmp::Ordering;
     use std::cmp::{Eq, Ord, PartialEq, PartialOrd};
+    use std::collections::BTreeMap;
 
-    #[derive(Debug, PartialEq, Eq, PartialOrd)]
-    pub enum AccountingConvention {
-        AC360,
-        AC365,
+    /// Which leg the holder of the swap pays.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+    pub enum SwapDirection {
+        PayFixed,
+        ReceiveFixed,
     }
 
-    #[derive(Debug)]
+    /// The schedule and conventions of a leg. Accrual and payment dates are both moved to
+    /// business days of the `calendar` by the `business_day_convention`, and payments are
+    /// made `payment_delay` business days after the end of each period.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct LegTerms {
+        pub notional: f64,
+        pub effective_date: NaiveDate,
+        pub maturity_date: NaiveDate,
+        pub periodicity: Periodicity,
+        pub day_count: DayCountConvention,
+        pub calendar: Calendar,
+        pub business_day_convention: BusinessDayConvention,
+        #[serde(default)]
+        pub payment_delay: u32,
+    }
+
+    /// A leg paying the fixed `rate`, a decimal.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct FixedLeg {
+        pub terms: LegTerms,
+        pub rate: f64,
+    }
+
+    /// A leg paying the `index` plus the `spread`, a decimal. The index is observed
+    /// `fixing_lag` business days early, with or without an `observation_shift`, and stops
+    /// resetting `reset_lag` business days before the end of a period, see
+    /// [`FloatingRateNote`].
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct FloatingLeg {
+        pub terms: LegTerms,
+        pub index: FloatingIndex,
+        pub spread: f64,
+        pub fixing_lag: u32,
+        #[serde(default)]
+        pub reset_lag: u32,
+        #[serde(default)]
+        pub observation_shift: bool,
+    }
+
+    /// A swap of a fixed leg for a floating leg.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct IRS {
-        pub face_value: f32,
-        pub fixed_rate: f32,
-        pub overnight_rate_type: OvernightRateType,
-        pub time: f32,
-        pub accounting_convention: AccountingConvention,
+        pub fixed_leg: FixedLeg,
+        pub floating_leg: FloatingLeg,
+        pub direction: SwapDirection,
+    }
+
+    /// A coupon of a leg, the `rate` is the fixed rate or the projected index plus spread.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct SwapCashFlow {
+        pub accrual_start: NaiveDate,
+        pub accrual_end: NaiveDate,
+        pub payment_date: NaiveDate,
+        pub year_fraction: f64,
+        pub rate: f64,
+        pub amount: f64,
+    }
+
+    /// The value of a swap to the holder. The `npv` is the present value of the leg received
+    /// less that of the leg paid, the `par_rate` the fixed rate at which the npv is zero and
+    /// the `dv01` the change in npv for a one basis point fall in every zero rate.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct SwapValuation {
+        pub npv: f64,
+        pub fixed_leg_pv: f64,
+        pub floating_leg_pv: f64,
+        pub par_rate: f64,
+        pub dv01: f64,
+    }
+
+    impl LegTerms {
+        /// Annual periods on *ACT/360* with the US Government Securities calendar and
+        /// modified following without payment delay, the conventions of a SOFR swap.
+        pub fn new(notional: f64, effective_date: NaiveDate, maturity_date: NaiveDate) -> LegTerms {
+            LegTerms {
+                notional,
+                effective_date,
+                maturity_date,
+                periodicity: Periodicity::Annual,
+                day_count: DayCountConvention::Act360,
+                calendar: Calendar::Sifma,
+                business_day_convention: BusinessDayConvention::ModifiedFollowing,
+                payment_delay: 0,
+            }
+        }
+
+        pub fn schedule(&self) -> Schedule {
+            let mut schedule =
+                Schedule::new(self.effective_date, self.maturity_date, self.periodicity);
+            schedule.calendar = self.calendar.clone();
+            schedule.accrual_convention = self.business_day_convention;
+            schedule.payment_convention = self.business_day_convention;
+            schedule
+        }
+    }
+
+    /// The present value as of `valuation` of the `cashflows` discounted off the `discount`
+    /// curve.
+    fn present_value(
+        cashflows: &[SwapCashFlow],
+        valuation: NaiveDate,
+        discount: &YieldCurve,
+    ) -> f64 {
+        let valuation_discount = discount.discount_at(valuation);
+        cashflows
+            .iter()
+            .map(|cf| cf.amount * discount.discount_at(cf.payment_date) / valuation_discount)
+            .sum()
+    }
+
+    impl FixedLeg {
+        /// The coupons paid after `valuation`.
+        pub fn cashflows(&self, valuation: NaiveDate) -> Vec<SwapCashFlow> {
+            let delay = self.terms.payment_delay as i32;
+            self.terms
+                .schedule()
+                .periods()
+                .iter()
+                .map(|period| {
+                    let payment_date = self.terms.calendar.advance(period.payment_date, delay);
+                    (period, payment_date)
+                })
+                .filter(|(_, payment_date)| *payment_date > valuation)
+                .map(|(period, payment_date)| {
+                    let year_fraction = self
+                        .terms
+                        .day_count
+                        .year_fraction(period.accrual_start, period.accrual_end);
+                    SwapCashFlow {
+                        accrual_start: period.accrual_start,
+                        accrual_end: period.accrual_end,
+                        payment_date,
+                        year_fraction,
+                        rate: self.rate,
+                        amount: self.terms.notional * self.rate * year_fraction,
+                    }
+                })
+                .collect()
+        }
+
+        /// The present value of a fixed rate of one on the leg, the annuity.
+        pub fn annuity(&self, valuation: NaiveDate, discount: &YieldCurve) -> f64 {
+            let unit = FixedLeg {
+                rate: 1.0,
+                ..self.clone()
+            };
+            present_value(&unit.cashflows(valuation), valuation, discount)
+        }
+    }
+
+    impl FloatingLeg {
+        /// The leg as a floating rate note, without the principal.
+        fn as_note(&self) -> FloatingRateNote {
+            FloatingRateNote {
+                principal: self.terms.notional,
+                issue_date: self.terms.effective_date,
+                maturity_date: self.terms.maturity_date,
+                periodicity: self.terms.periodicity,
+                index: self.index,
+                quoted_margin: self.spread,
+                cap: None,
+                floor: None,
+                fixing_lag: self.fixing_lag,
+                reset_lag: self.reset_lag,
+                observation_shift: self.observation_shift,
+                payment_delay: self.terms.payment_delay,
+                day_count: self.terms.day_count.clone(),
+                calendar: self.terms.calendar.clone(),
+                payment_convention: self.terms.business_day_convention,
+                accrual_convention: self.terms.business_day_convention,
+            }
+        }
+
+        /// The coupons paid after `valuation`, with the published `fixings` and the forward
+        /// rates of the `projection` curve for the rest.
+        pub fn cashflows(
+            &self,
+            valuation: NaiveDate,
+            fixings: &[InterestRateData],
+            projection: &YieldCurve,
+        ) -> Result<Vec<SwapCashFlow>, FrnError> {
+            let note = self.as_note();
+            let mut cashflows = note.projected_cashflows(valuation, fixings, projection)?;
+            if let Some(last) = cashflows.last_mut() {
+                last.amount -= note.principal;
+            }
+            Ok(cashflows
+                .iter()
+                .map(|cf| SwapCashFlow {
+                    accrual_start: cf.accrual_start,
+                    accrual_end: cf.accrual_end,
+                    payment_date: cf.payment_date,
+                    year_fraction: cf.year_fraction,
+                    rate: cf.coupon_rate,
+                    amount: cf.amount,
+                })
+                .collect())
+        }
+    }
+
+    impl IRS {
+        /// A swap of `fixed_rate` for the `index` flat on the `notional`, both legs with the
+        /// conventions of [`LegTerms::new`] and the floating leg observing the index on the
+        /// day.
+        pub fn new(
+            notional: f64,
+            effective_date: NaiveDate,
+            maturity_date: NaiveDate,
+            fixed_rate: f64,
+            index: FloatingIndex,
+            direction: SwapDirection,
+        ) -> IRS {
+            let terms = LegTerms::new(notional, effective_date, maturity_date);
+            IRS {
+                fixed_leg: FixedLeg {
+                    terms: terms.clone(),
+                    rate: fixed_rate,
+                },
+                floating_leg: FloatingLeg {
+                    terms,
+                    index,
+                    spread: 0.0,
+                    fixing_lag: 0,
+                    reset_lag: 0,
+                    observation_shift: false,
+                },
+                direction,
+            }
+        }
+
+        /// A swap of `fixed_rate` for the `index` flat, both legs on the day count and fixing
+        /// calendar of the index in the `registry`, the conventions of an overnight index
+        /// swap. The floating leg of a term rate resets at its tenor.
+        pub fn for_index(
+            notional: f64,
+            effective_date: NaiveDate,
+            maturity_date: NaiveDate,
+            fixed_rate: f64,
+            index: FloatingIndex,
+            direction: SwapDirection,
+            registry: &RateIndexRegistry,
+        ) -> Result<IRS, FrnError> {
+            let rate_index = index.lookup(registry)?;
+            let mut irs = IRS::new(
+                notional,
+                effective_date,
+                maturity_date,
+                fixed_rate,
+                index,
+                direction,
+            );
+            for terms in [&mut irs.fixed_leg.terms, &mut irs.floating_leg.terms] {
+                terms.day_count = rate_index.day_count.clone();
+                terms.calendar = rate_index.calendar.clone();
+            }
+            if let Some(periodicity) = index.periodicity(rate_index)? {
+                irs.floating_leg.terms.periodicity = periodicity;
+            }
+            Ok(irs)
+        }
+
+        fn npv(&self, fixed_leg_pv: f64, floating_leg_pv: f64) -> f64 {
+            match self.direction {
+                SwapDirection::PayFixed => floating_leg_pv - fixed_leg_pv,
+                SwapDirection::ReceiveFixed => fixed_leg_pv - floating_leg_pv,
+            }
+        }
+
+        fn leg_values(
+            &self,
+            valuation: NaiveDate,
+            fixings: &[InterestRateData],
+            projection: &YieldCurve,
+            discount: &YieldCurve,
+        ) -> Result<(f64, f64), FrnError> {
+            let fixed = present_value(&self.fixed_leg.cashflows(valuation), valuation, discount);
+            let floating = present_value(
+                &self
+                    .floating_leg
+                    .cashflows(valuation, fixings, projection)?,
+                valuation,
+                discount,
+            );
+            Ok((fixed, floating))
+        }
+
+        /// The value at `valuation` with the floating coupons projected off the `projection`
+        /// curve and both legs discounted off the `discount` curve, the same curve for a
+        /// single curve valuation. The errors are those of projecting the floating leg, see
+        /// [`FloatingRateNote::projected_cashflows`]. The DV01 shifts the continuously
+        /// compounded zero rates of both curves by one basis point either way.
+        pub fn price(
+            &self,
+            valuation: NaiveDate,
+            fixings: &[InterestRateData],
+            projection: &YieldCurve,
+            discount: &YieldCurve,
+        ) -> Result<SwapValuation, FrnError> {
+            let (fixed_leg_pv, floating_leg_pv) =
+                self.leg_values(valuation, fixings, projection, discount)?;
+            let annuity = self.fixed_leg.annuity(valuation, discount);
+            let par_rate = if annuity == 0.0 {
+                0.0
+            } else {
+                floating_leg_pv / annuity
+            };
+            let bump = 0.0001;
+            let npv_at = |shift: f64| -> Result<f64, FrnError> {
+                let (fixed, floating) = self.leg_values(
+                    valuation,
+                    fixings,
+                    &projection.shifted(shift),
+                    &discount.shifted(shift),
+                )?;
+                Ok(self.npv(fixed, floating))
+            };
+            Ok(SwapValuation {
+                npv: self.npv(fixed_leg_pv, floating_leg_pv),
+                fixed_leg_pv,
+                floating_leg_pv,
+                par_rate,
+                dv01: (npv_at(-bump)? - npv_at(bump)?) / 2.0,
+            })
+        }
     }
 
     #[derive(Debug)]
     pub struct InterestRateData {
         pub time: NaiveDate,
-        pub rate: f32,
+        pub rate: f64,
         pub overnight_rate_type: OvernightRateType,
     }
 
@@ -45,49 +377,68 @@ mod interest_rate_swap {
     }
     impl Eq for InterestRateData {}
 
-    fn compute_variable_side(irs: &IRS, overnight_data: &Vec<InterestRateData>) -> f32 {
-        let mut result: f32 = 0.0;
-        let days_in_year: f32 = match irs.accounting_convention {
-            AccountingConvention::AC360 => 360.0,
-            AccountingConvention::AC365 => 365.0,
-        };
+    /// The interest of the floating leg over the period from `start` to `end`, compounded
+    /// from the overnight fixings by the conventions of the leg, see
+    /// [`OvernightCompounding`]. The rate of each business day accrues until the next
+    /// business day, fixings on other days are ignored. Every business day observed must
+    /// have a fixing, as for a [`FloatingRateNote`].
+    fn compute_variable_side(
+        irs: &IRS,
+        overnight_data: &[InterestRateData],
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<f64, FrnError> {
+        let leg = &irs.floating_leg;
+        let mut fixings = BTreeMap::new();
         for i in overnight_data {
-            if irs.overnight_rate_type != i.overnight_rate_type {
-                panic!(
-                    "Mismatched rate type irs : {:?}, market_data : {:?}",
-                    irs.overnight_rate_type, i.overnight_rate_type
-                );
-            }
-            if (result - 0.0).abs() < f32::EPSILON {
-                result = irs.face_value * (i.rate / (days_in_year * 100.0));
-            } else {
-                result = result * (1.0 + i.rate / (days_in_year * 100.0));
-            }
-        }
-        return result;
-    }
-
-    pub fn price_irs_at(irs: &IRS, overnight_data: &Vec<InterestRateData>, days: f32) -> f32 {
-        match irs.accounting_convention {
-            AccountingConvention::AC360 => {
-                let fixed_side: f32 = irs.face_value
-                    * (1.0 + (irs.fixed_rate / 100.0) * days / 360.0)
-                    - irs.face_value;
-                let variable_side: f32 = compute_variable_side(irs, overnight_data);
-                println!(
-                    "Variable side {:?} fixed_side {:?}",
-                    variable_side, fixed_side
-                );
-                return variable_side - fixed_side;
-            }
-            AccountingConvention::AC365 => {
-                let fixed_side: f32 =
-                    irs.face_value * (1.0 + (irs.fixed_rate / 100.0) * days / 365.0);
-                let variable_side: f32 =
-                    (irs.face_value * compute_variable_side(irs, overnight_data)) - irs.face_value;
-                return variable_side - fixed_side;
+            if leg.index.rate_type() != i.overnight_rate_type {
+                return Err(FrnError {
+                    message: "The fixings are for a different index.",
+                    message_code: FrnErrorType::MismatchedIndex,
+                });
             }
+            fixings.insert(i.time, i.rate / 100.0);
         }
+        let conventions = OvernightCompounding {
+            calendar: leg.terms.calendar.clone(),
+            day_count: leg.terms.day_count.clone(),
+            lookback: leg.fixing_lag,
+            observation_shift: leg.observation_shift,
+            lockout: leg.reset_lag,
+        };
+        let (rate, _) = conventions.compounded_rate(start, end, end, |day| {
+            fixings.get(&day).map(|rate| (*rate, true)).ok_or(FrnError {
+                message: "No fixing for a business day of the period.",
+                message_code: FrnErrorType::MissingFixing,
+            })
+        })?;
+        Ok(leg.terms.notional * rate * leg.terms.day_count.year_fraction(start, end))
+    }
+
+    /// The floating side realized from the overnight fixings net of the fixed side accrued
+    /// for `days` from the first fixing date, signed by the direction of the swap as in
+    /// [`IRS::price`]. Fixings of another index
+    /// or a business day of the period without a fixing are errors. See [`IRS::price`] for the
+    /// value off a curve.
+    pub fn price_irs_at(
+        irs: &IRS,
+        overnight_data: &[InterestRateData],
+        days: f64,
+    ) -> Result<f64, FrnError> {
+        let start = match overnight_data.first() {
+            Some(first) => first.time,
+            None => return Ok(0.0),
+        };
+        let end = start + Days::new(days.round() as u64);
+        let leg = &irs.fixed_leg;
+        let fixed_side: f64 =
+            leg.terms.notional * leg.rate * leg.terms.day_count.year_fraction(start, end);
+        let variable_side: f64 = compute_variable_side(irs, overnight_data, start, end)?;
+        debug!(
+            "Variable side {:?} fixed_side {:?}",
+            variable_side, fixed_side
+        );
+        Ok(irs.npv(fixed_side, variable_side))
     }
 }
 
@@ -95,13 +446,18 @@ mod interest_rate_swap {
 mod tests {
 
     use super::*;
+    use crate::bond::bond::{DiscountFactor, Periodicity};
+    use crate::calendar::calendar::Calendar;
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::frn::frn::{FloatingIndex, FrnErrorType};
+    use crate::rate_index::rate_index::RateIndexRegistry;
     use crate::rates::rates::OvernightRateType;
+    use crate::rates::rates::YieldCurve;
     use assert_approx_eq::assert_approx_eq;
     use chrono::{Days, NaiveDate};
     use interest_rate_swap::price_irs_at;
-    use interest_rate_swap::AccountingConvention;
     use interest_rate_swap::InterestRateData;
-    use interest_rate_swap::IRS;
+    use interest_rate_swap::{SwapDirection, IRS};
 
     #[test]
     fn test_price_irs() {
@@ -140,15 +496,19 @@ mod tests {
         }
         interest_rate_data.sort();
         println!("5 : {:?}, 170 : {:?}, 190 : {:?}", v1, v2, v3);
-        let irs: IRS = IRS {
-            face_value: 100000000.00,
-            fixed_rate: 0.1120,
-            overnight_rate_type: OvernightRateType::SOFR,
-            time: 2.0,
-            accounting_convention: AccountingConvention::AC360,
-        };
-        let valuation: f32 = price_irs_at(&irs, &mut interest_rate_data, 365.0);
-        assert_approx_eq!(valuation, -113281.55, 1.0);
+        let mut irs: IRS = IRS::new(
+            100000000.00,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            0.001120,
+            FloatingIndex::CompoundedInArrears(OvernightRateType::SOFR),
+            SwapDirection::PayFixed,
+        );
+        // The fixings start on 01/01/2024, a US holiday. Only the fixings of weekdays
+        // accrue, the Friday fixings for three days.
+        irs.floating_leg.terms.calendar = Calendar::WeekendsOnly;
+        let valuation: f64 = price_irs_at(&irs, &interest_rate_data, 365.0).unwrap();
+        assert_approx_eq!(valuation, 127286.544190, 1e-6);
     }
 
     #[test]
@@ -167,14 +527,168 @@ mod tests {
             start_date = start_date + Days::new(1);
         }
         interest_rate_data.sort();
-        let irs: IRS = IRS {
-            face_value: 100000000.00,
-            fixed_rate: 0.1120,
-            overnight_rate_type: OvernightRateType::SOFR,
-            time: 2.0,
-            accounting_convention: AccountingConvention::AC360,
-        };
-        let valuation: f32 = price_irs_at(&irs, &mut interest_rate_data, 1.0);
-        assert_approx_eq!(valuation, 0.00, 1.0);
+        let mut irs: IRS = IRS::new(
+            100000000.00,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            0.001120,
+            FloatingIndex::CompoundedInArrears(OvernightRateType::SOFR),
+            SwapDirection::PayFixed,
+        );
+        irs.floating_leg.terms.calendar = Calendar::WeekendsOnly;
+        // Over a single day the overnight rate accrues like the fixed rate.
+        let valuation: f64 = price_irs_at(&irs, &interest_rate_data, 1.0).unwrap();
+        assert_approx_eq!(valuation, 0.0, 1e-6);
+        // Over a week the Friday fixing accrues for the weekend and compounding adds to
+        // the floating side.
+        let valuation: f64 = price_irs_at(&irs, &interest_rate_data, 7.0).unwrap();
+        let growth = f64::powi(1.0 + 0.00112 / 360.0, 4) * (1.0 + 0.00112 * 3.0 / 360.0);
+        assert_approx_eq!(
+            valuation,
+            100000000.0 * (growth - 1.0 - 0.00112 * 7.0 / 360.0),
+            1e-6
+        );
+        // The receiver has the opposite side.
+        irs.direction = SwapDirection::ReceiveFixed;
+        let receiver: f64 = price_irs_at(&irs, &interest_rate_data, 7.0).unwrap();
+        assert_approx_eq!(receiver, -valuation, 1e-9);
+        assert!(receiver < 0.0);
+        // A business day missing from the fixings is not filled from the day before.
+        interest_rate_data.retain(|ir| ir.time != NaiveDate::from_ymd_opt(2024, 1, 4).unwrap());
+        assert_eq!(
+            price_irs_at(&irs, &interest_rate_data, 7.0)
+                .unwrap_err()
+                .message_code,
+            FrnErrorType::MissingFixing
+        );
+    }
+
+    #[test]
+    fn test_price_swap_off_curve() {
+        let date = |input: &str| NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap();
+        let valuation = date("01/02/2024");
+        let discount_factors: Vec<DiscountFactor> = (1..=24)
+            .map(|i| {
+                let term = i as f64 * 0.25;
+                DiscountFactor {
+                    term,
+                    discount: f64::exp(-(0.04 + 0.002 * term) * term),
+                }
+            })
+            .collect();
+        let curve = YieldCurve::from_discount_factors(
+            valuation,
+            DayCountConvention::Act365Fixed,
+            Periodicity::Annual,
+            &discount_factors,
+        )
+        .unwrap();
+        let notional = 10000000.0;
+        let mut irs = IRS::new(
+            notional,
+            date("01/04/2024"),
+            date("01/04/2029"),
+            0.04,
+            FloatingIndex::TermRate(OvernightRateType::SOFR),
+            SwapDirection::PayFixed,
+        );
+        let valuation_result = irs.price(valuation, &[], &curve, &curve).unwrap();
+        // On a single curve the floating leg is worth the notional at the start less the
+        // notional at the end.
+        let schedule = irs.floating_leg.terms.schedule();
+        let last = schedule.periods().last().unwrap().accrual_end;
+        assert_eq!(last, date("01/04/2029"));
+        assert_approx_eq!(
+            valuation_result.floating_leg_pv,
+            notional * (curve.discount_at(date("01/04/2024")) - curve.discount_at(last))
+                / curve.discount_at(valuation),
+            1e-6
+        );
+        assert_approx_eq!(
+            valuation_result.npv,
+            valuation_result.floating_leg_pv - valuation_result.fixed_leg_pv,
+            1e-9
+        );
+        assert!(valuation_result.par_rate > 0.04);
+
+        // At the par rate the swap is worth nothing, and the payer gains as rates rise.
+        irs.fixed_leg.rate = valuation_result.par_rate;
+        let at_par = irs.price(valuation, &[], &curve, &curve).unwrap();
+        assert_approx_eq!(at_par.npv, 0.0, 1e-6);
+        let annuity = irs.fixed_leg.annuity(valuation, &curve);
+        assert!(at_par.dv01 < 0.0);
+        assert_approx_eq!(at_par.dv01 / (annuity * 0.0001), -1.0, 0.1);
+        irs.direction = SwapDirection::ReceiveFixed;
+        let receiver = irs.price(valuation, &[], &curve, &curve).unwrap();
+        assert_approx_eq!(receiver.dv01, -at_par.dv01, 1e-9);
+
+        // A leg with a different schedule: semi-annual fixed on 30/360.
+        irs.fixed_leg.terms.periodicity = Periodicity::SemiAnnual;
+        irs.fixed_leg.terms.day_count = DayCountConvention::Thirty360Us;
+        assert_eq!(irs.fixed_leg.cashflows(valuation).len(), 10);
+        let semi_annual = irs.price(valuation, &[], &curve, &curve).unwrap();
+        assert!((semi_annual.par_rate - at_par.par_rate).abs() > 1e-6);
+
+        // A payment delay of two business days, the second period ends on Monday 01/06/2025.
+        irs.fixed_leg.terms.payment_delay = 2;
+        let delayed = irs.fixed_leg.cashflows(valuation);
+        assert_eq!(delayed[1].accrual_end, date("01/06/2025"));
+        assert_eq!(delayed[1].payment_date, date("01/08/2025"));
+    }
+
+    #[test]
+    fn test_swap_on_registered_index() {
+        let date = |input: &str| NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap();
+        let registry = RateIndexRegistry::default();
+        let irs = IRS::for_index(
+            1000000.0,
+            date("01/02/2024"),
+            date("01/02/2026"),
+            0.05,
+            FloatingIndex::CompoundedInArrears(OvernightRateType::SONIA),
+            SwapDirection::PayFixed,
+            &registry,
+        )
+        .unwrap();
+        assert_eq!(
+            irs.fixed_leg.terms.day_count,
+            DayCountConvention::Act365Fixed
+        );
+        assert_eq!(irs.floating_leg.terms.calendar, Calendar::UnitedKingdom);
+
+        // SOFR fixings on a SONIA swap, and business days without a fixing, are errors.
+        let sofr: Vec<InterestRateData> = (0..10)
+            .map(|i| InterestRateData {
+                time: date("01/02/2024") + Days::new(i),
+                rate: 5.3,
+                overnight_rate_type: OvernightRateType::SOFR,
+            })
+            .collect();
+        assert_eq!(
+            price_irs_at(&irs, &sofr, 7.0).unwrap_err().message_code,
+            FrnErrorType::MismatchedIndex
+        );
+        // Only Saturday 01/06/2024 has a fixing, none of the business days of the week.
+        let sonia = vec![InterestRateData {
+            time: date("01/06/2024"),
+            rate: 5.2,
+            overnight_rate_type: OvernightRateType::SONIA,
+        }];
+        assert_eq!(
+            price_irs_at(&irs, &sonia, 7.0).unwrap_err().message_code,
+            FrnErrorType::MissingFixing
+        );
+
+        let error = IRS::for_index(
+            1000000.0,
+            date("01/02/2024"),
+            date("01/02/2026"),
+            0.05,
+            FloatingIndex::CompoundedInArrears(OvernightRateType::TermSOFR3M),
+            SwapDirection::PayFixed,
+            &registry,
+        )
+        .unwrap_err();
+        assert_eq!(error.message_code, FrnErrorType::MismatchedIndex);
     }
 }
@@ -0,0 +1,292 @@
+//! Interpolation of a term structure between the terms of its discount factors. A
+//! bootstrap only determines the discount factors at the input terms, the method used
+//! between them is a modelling choice which changes prices, so it is stored with the curve.
+pub mod interpolation {
+    use crate::bond::bond::DiscountFactor;
+    use serde::{Deserialize, Serialize};
+
+    /// The interpolation methods. Zero rates are continuously compounded.
+    ///
+    /// * `LinearZero` - linear in the zero rates.
+    /// * `LogLinearDiscount` - linear in the log of the discount factors, which gives a
+    ///   piecewise constant instantaneous forward rate.
+    /// * `NaturalCubicSpline` - a natural cubic spline through the zero rates.
+    /// * `MonotoneConvex` - the method of Hagan and West (2006). The forward curve is
+    ///   continuous and reproduces the discrete forwards between the terms without the
+    ///   oscillations of a spline.
+    /// * `FlatForward` - the simply compounded forward rate is constant between the terms,
+    ///   which is how money market forwards are quoted.
+    ///
+    /// Beyond the last term every method extends the last zero rate flat. Before the first
+    /// term the zero rate is extended flat, except for `MonotoneConvex` and `FlatForward`
+    /// which interpolate from a discount factor of one today.
+    #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+    pub enum Interpolation {
+        LinearZero,
+        #[default]
+        LogLinearDiscount,
+        NaturalCubicSpline,
+        MonotoneConvex,
+        FlatForward,
+    }
+
+    fn zero_rate(df: &DiscountFactor) -> f64 {
+        -f64::ln(df.discount) / df.term
+    }
+
+    /// The index of the first term at or after `term`. The caller handles terms beyond the
+    /// last node.
+    fn segment(curve: &[DiscountFactor], term: f64) -> usize {
+        curve.iter().position(|df| term <= df.term).unwrap()
+    }
+
+    impl Interpolation {
+        /// The discount factor at `term` from the `curve`, whose terms are positive and sorted.
+        pub fn discount(&self, curve: &[DiscountFactor], term: f64) -> f64 {
+            if term <= 0.0 || curve.is_empty() {
+                return 1.0;
+            }
+            let last = curve[curve.len() - 1];
+            if term >= last.term {
+                return f64::exp(-zero_rate(&last) * term);
+            }
+            match self {
+                Interpolation::LinearZero => {
+                    let i = segment(curve, term);
+                    if i == 0 {
+                        return f64::exp(-zero_rate(&curve[0]) * term);
+                    }
+                    let (left, right) = (curve[i - 1], curve[i]);
+                    let weight = (term - left.term) / (right.term - left.term);
+                    let rate = zero_rate(&left) + weight * (zero_rate(&right) - zero_rate(&left));
+                    f64::exp(-rate * term)
+                }
+                Interpolation::LogLinearDiscount => log_linear(curve, term),
+                Interpolation::NaturalCubicSpline => {
+                    if term <= curve[0].term {
+                        return f64::exp(-zero_rate(&curve[0]) * term);
+                    }
+                    f64::exp(-natural_cubic_spline(curve, term) * term)
+                }
+                Interpolation::MonotoneConvex => f64::exp(-monotone_convex(curve, term)),
+                Interpolation::FlatForward => {
+                    let i = segment(curve, term);
+                    let left = if i == 0 {
+                        DiscountFactor {
+                            term: 0.0,
+                            discount: 1.0,
+                        }
+                    } else {
+                        curve[i - 1]
+                    };
+                    let right = curve[i];
+                    let forward = (left.discount / right.discount - 1.0) / (right.term - left.term);
+                    left.discount / (1.0 + forward * (term - left.term))
+                }
+            }
+        }
+    }
+
+    fn log_linear(curve: &[DiscountFactor], term: f64) -> f64 {
+        let i = segment(curve, term);
+        let left = if i == 0 {
+            DiscountFactor {
+                term: 0.0,
+                discount: 1.0,
+            }
+        } else {
+            curve[i - 1]
+        };
+        let right = curve[i];
+        let weight = (term - left.term) / (right.term - left.term);
+        left.discount * f64::powf(right.discount / left.discount, weight)
+    }
+
+    /// The zero rate at `term` from a natural cubic spline through the zero rates, with the
+    /// second derivatives found by the tridiagonal algorithm. See *Numerical Recipes*,
+    /// section 3.3.
+    fn natural_cubic_spline(curve: &[DiscountFactor], term: f64) -> f64 {
+        let n = curve.len();
+        let x: Vec<f64> = curve.iter().map(|df| df.term).collect();
+        let y: Vec<f64> = curve.iter().map(zero_rate).collect();
+        let mut second = vec![0.0; n];
+        let mut u = vec![0.0; n];
+        for i in 1..n - 1 {
+            let sig = (x[i] - x[i - 1]) / (x[i + 1] - x[i - 1]);
+            let p = sig * second[i - 1] + 2.0;
+            second[i] = (sig - 1.0) / p;
+            let slope =
+                (y[i + 1] - y[i]) / (x[i + 1] - x[i]) - (y[i] - y[i - 1]) / (x[i] - x[i - 1]);
+            u[i] = (6.0 * slope / (x[i + 1] - x[i - 1]) - sig * u[i - 1]) / p;
+        }
+        second[n - 1] = 0.0;
+        for i in (0..n - 1).rev() {
+            second[i] = second[i] * second[i + 1] + u[i];
+        }
+        let i = segment(curve, term);
+        let h = x[i] - x[i - 1];
+        let a = (x[i] - term) / h;
+        let b = (term - x[i - 1]) / h;
+        a * y[i - 1]
+            + b * y[i]
+            + ((a * a * a - a) * second[i - 1] + (b * b * b - b) * second[i]) * h * h / 6.0
+    }
+
+    /// The integral of the instantaneous forward rate from today to `term`, that is the zero
+    /// rate times the term, by the monotone convex method. The forward rate in each interval
+    /// is the discrete forward plus a correction `g(x)` that integrates to zero over the
+    /// interval, see Hagan and West, *Interpolation Methods for Curve Construction* (2006).
+    fn monotone_convex(curve: &[DiscountFactor], term: f64) -> f64 {
+        let n = curve.len();
+        let mut terms = vec![0.0];
+        let mut integral = vec![0.0];
+        for df in curve {
+            terms.push(df.term);
+            integral.push(-f64::ln(df.discount));
+        }
+        // The discrete forwards, `discrete[i]` is the forward from terms[i - 1] to terms[i].
+        let mut discrete = vec![0.0; n + 1];
+        for i in 1..=n {
+            discrete[i] = (integral[i] - integral[i - 1]) / (terms[i] - terms[i - 1]);
+        }
+        // The instantaneous forwards at the terms.
+        let mut forward = vec![0.0; n + 1];
+        for i in 1..n {
+            let width = terms[i + 1] - terms[i - 1];
+            forward[i] = (terms[i] - terms[i - 1]) / width * discrete[i + 1]
+                + (terms[i + 1] - terms[i]) / width * discrete[i];
+        }
+        if n == 1 {
+            forward[0] = discrete[1];
+            forward[1] = discrete[1];
+        } else {
+            forward[0] = discrete[1] - 0.5 * (forward[1] - discrete[1]);
+            forward[n] = discrete[n] - 0.5 * (forward[n - 1] - discrete[n]);
+        }
+        let i = (1..=n).find(|i| term <= terms[*i]).unwrap();
+        let width = terms[i] - terms[i - 1];
+        let x = (term - terms[i - 1]) / width;
+        let g0 = forward[i - 1] - discrete[i];
+        let g1 = forward[i] - discrete[i];
+        integral[i - 1] + (term - terms[i - 1]) * discrete[i] + width * correction(g0, g1, x)
+    }
+
+    /// The integral from zero to `x` of the correction `g`, which is `g0` at the start and
+    /// `g1` at the end of the interval. The four regions keep the forward curve monotone
+    /// between the terms.
+    fn correction(g0: f64, g1: f64, x: f64) -> f64 {
+        if x == 0.0 || (g0 == 0.0 && g1 == 0.0) {
+            return 0.0;
+        }
+        let region_1 = (g0 < 0.0 && -0.5 * g0 <= g1 && g1 <= -2.0 * g0)
+            || (g0 > 0.0 && -0.5 * g0 >= g1 && g1 >= -2.0 * g0);
+        let region_2 = (g0 < 0.0 && g1 > -2.0 * g0) || (g0 > 0.0 && g1 < -2.0 * g0);
+        let region_3 =
+            (g0 > 0.0 && 0.0 > g1 && g1 > -0.5 * g0) || (g0 < 0.0 && 0.0 < g1 && g1 < -0.5 * g0);
+        if region_1 {
+            g0 * (x - 2.0 * x * x + x * x * x) + g1 * (-x * x + x * x * x)
+        } else if region_2 {
+            let eta = (g1 + 2.0 * g0) / (g1 - g0);
+            if x <= eta {
+                g0 * x
+            } else {
+                g0 * x + (g1 - g0) * f64::powi(x - eta, 3) / f64::powi(1.0 - eta, 2) / 3.0
+            }
+        } else if region_3 {
+            let eta = 3.0 * g1 / (g1 - g0);
+            if x < eta {
+                g1 * x - (g0 - g1) / 3.0 * (f64::powi(eta - x, 3) / (eta * eta) - eta)
+            } else {
+                (g0 - g1) / 3.0 * eta + g1 * x
+            }
+        } else {
+            let eta = g1 / (g1 + g0);
+            let a = -g0 * g1 / (g0 + g1);
+            if x <= eta {
+                a * x - (g0 - a) / 3.0 * (f64::powi(eta - x, 3) / (eta * eta) - eta)
+            } else {
+                a * x
+                    + (g0 - a) / 3.0 * eta
+                    + (g1 - a) / 3.0 * f64::powi(x - eta, 3) / f64::powi(1.0 - eta, 2)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bond::bond::DiscountFactor;
+    use crate::interpolation::interpolation::Interpolation;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn create_test_curve() -> Vec<DiscountFactor> {
+        [(0.5, 0.02), (1.0, 0.025), (2.0, 0.03), (5.0, 0.028)]
+            .iter()
+            .map(|(term, rate): &(f64, f64)| DiscountFactor {
+                term: *term,
+                discount: f64::exp(-rate * term),
+            })
+            .collect()
+    }
+
+    const METHODS: [Interpolation; 5] = [
+        Interpolation::LinearZero,
+        Interpolation::LogLinearDiscount,
+        Interpolation::NaturalCubicSpline,
+        Interpolation::MonotoneConvex,
+        Interpolation::FlatForward,
+    ];
+
+    #[test]
+    fn test_interpolation_reprices_nodes() {
+        let curve = create_test_curve();
+        for method in METHODS {
+            assert_approx_eq!(method.discount(&curve, 0.0), 1.0, f64::EPSILON);
+            for df in &curve {
+                assert_approx_eq!(method.discount(&curve, df.term), df.discount, 1e-6);
+            }
+            // The last zero rate is extended flat.
+            assert_approx_eq!(method.discount(&curve, 10.0), f64::exp(-0.28), 1e-6);
+            // Discount factors fall between the nodes.
+            let (d1, d2) = (method.discount(&curve, 1.4), method.discount(&curve, 1.6));
+            assert!(curve[1].discount > d1 && d1 > d2 && d2 > curve[2].discount);
+        }
+    }
+
+    #[test]
+    fn test_interpolation_methods() {
+        let curve = create_test_curve();
+        let linear_zero = Interpolation::LinearZero.discount(&curve, 1.5);
+        assert_approx_eq!(linear_zero, f64::exp(-0.0275 * 1.5), 1e-6);
+        let log_linear = Interpolation::LogLinearDiscount.discount(&curve, 1.5);
+        assert_approx_eq!(log_linear, f64::exp(-(0.025 + 0.06) / 2.0), 1e-6);
+        let flat_forward = Interpolation::FlatForward.discount(&curve, 1.5);
+        let forward = (curve[1].discount / curve[2].discount - 1.0) / 1.0;
+        assert_approx_eq!(
+            flat_forward,
+            curve[1].discount / (1.0 + 0.5 * forward),
+            1e-6
+        );
+        // A straight line of zero rates is reproduced by the spline.
+        let line: Vec<DiscountFactor> = (1..5)
+            .map(|i| {
+                let term = i as f64;
+                DiscountFactor {
+                    term,
+                    discount: f64::exp(-(0.01 + 0.002 * term) * term),
+                }
+            })
+            .collect();
+        let spline = Interpolation::NaturalCubicSpline.discount(&line, 2.5);
+        assert_approx_eq!(spline, f64::exp(-0.015 * 2.5), 1e-6);
+        // A flat curve stays flat under the monotone convex method.
+        let flat: Vec<DiscountFactor> = (1..5)
+            .map(|i| DiscountFactor {
+                term: i as f64,
+                discount: f64::exp(-0.03 * i as f64),
+            })
+            .collect();
+        let monotone = Interpolation::MonotoneConvex.discount(&flat, 2.3);
+        assert_approx_eq!(monotone, f64::exp(-0.03 * 2.3), 1e-6);
+    }
+}
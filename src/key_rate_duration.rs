@@ -0,0 +1,209 @@
+//! Key-rate durations measure the sensitivity of a price to a change in a single
+//! part of the term structure, where the DV01 assumes a parallel shift of every term.
+pub mod key_rate_duration {
+    use crate::bond::bond::{Bond, DiscountCurve, DiscountFactor};
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Serialize};
+
+    /// The tenors in years at which the curve is bumped, unless others are specified.
+    pub const DEFAULT_KEY_RATES: [f64; 6] = [0.5, 1.0, 2.0, 5.0, 10.0, 30.0];
+
+    /// The size of the bump, one basis point.
+    const BUMP: f64 = 0.0001;
+
+    /// The risk of a single bucket. The `dv01` is the change in price for a one basis point
+    /// fall in the zero rates around the key `tenor`, and `duration` the percentage change.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct KeyRateBucket {
+        pub tenor: f64,
+        pub dv01: f64,
+        pub duration: f64,
+    }
+
+    /// The share of a bump at `key_rates[bucket]` applied to the zero rate at `term`. The
+    /// bump is one at the key tenor and falls linearly to zero at the neighbouring key tenors,
+    /// the first and last buckets extend flat to the ends of the curve. The shares across all
+    /// buckets add up to one, so bumping every bucket is a parallel shift.
+    fn bump_weight(key_rates: &[f64], bucket: usize, term: f64) -> f64 {
+        let key = key_rates[bucket];
+        if term <= key {
+            if bucket == 0 {
+                return 1.0;
+            }
+            let previous = key_rates[bucket - 1];
+            ((term - previous) / (key - previous)).max(0.0)
+        } else {
+            if bucket + 1 == key_rates.len() {
+                return 1.0;
+            }
+            let next = key_rates[bucket + 1];
+            ((next - term) / (next - key)).max(0.0)
+        }
+    }
+
+    /// Shift the continuously compounded zero rates of the `curve` by `shift` around the key
+    /// tenor `key_rates[bucket]`. The key tenors which are not terms of the curve are added
+    /// to it first, interpolated from the curve, so the bump is the full `shift` at its key
+    /// tenor however sparse the curve is.
+    pub fn bump_curve(
+        curve: &[DiscountFactor],
+        key_rates: &[f64],
+        bucket: usize,
+        shift: f64,
+    ) -> Vec<DiscountFactor> {
+        let mut nodes = curve.to_vec();
+        for key in key_rates {
+            if *key > 0.0 && !curve.iter().any(|df| df.term == *key) {
+                nodes.push(DiscountFactor {
+                    term: *key,
+                    discount: curve.discount(*key),
+                });
+            }
+        }
+        nodes.sort();
+        nodes
+            .iter()
+            .map(|df| DiscountFactor {
+                term: df.term,
+                discount: df.discount
+                    * f64::exp(-shift * bump_weight(key_rates, bucket, df.term) * df.term),
+            })
+            .collect()
+    }
+
+    fn portfolio_price<C: DiscountCurve + ?Sized>(
+        positions: &[(Bond, f64)],
+        settlement: NaiveDate,
+        curve: &C,
+    ) -> f64 {
+        positions
+            .iter()
+            .map(|(bond, quantity)| quantity * bond.price_from_curve(settlement, curve))
+            .sum()
+    }
+
+    /// The key-rate durations of a portfolio of `(bond, quantity)` positions priced off
+    /// `curve`, for example the output of [`crate::bond::bond::discount_factor`].
+    pub fn portfolio_key_rate_durations(
+        positions: &[(Bond, f64)],
+        settlement: NaiveDate,
+        curve: &[DiscountFactor],
+        key_rates: &[f64],
+    ) -> Vec<KeyRateBucket> {
+        let base = portfolio_price(positions, settlement, curve);
+        (0..key_rates.len())
+            .map(|bucket| {
+                let up = bump_curve(curve, key_rates, bucket, BUMP);
+                let down = bump_curve(curve, key_rates, bucket, -BUMP);
+                let dv01 = (portfolio_price(positions, settlement, &down[..])
+                    - portfolio_price(positions, settlement, &up[..]))
+                    / 2.0;
+                KeyRateBucket {
+                    tenor: key_rates[bucket],
+                    dv01,
+                    duration: if base == 0.0 {
+                        0.0
+                    } else {
+                        dv01 / (base * BUMP)
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// The key-rate durations of a single bond.
+    pub fn key_rate_durations(
+        bond: &Bond,
+        settlement: NaiveDate,
+        curve: &[DiscountFactor],
+        key_rates: &[f64],
+    ) -> Vec<KeyRateBucket> {
+        portfolio_key_rate_durations(&[(bond.clone(), 1.0)], settlement, curve, key_rates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bond::bond::*;
+    use crate::key_rate_duration::key_rate_duration::*;
+    use crate::Create_Market_Data;
+    use assert_approx_eq::assert_approx_eq;
+    use chrono::NaiveDate;
+
+    fn create_test_curve() -> Vec<DiscountFactor> {
+        let market_data = vec![
+            Create_Market_Data!(with 2.875 at term 0.5 @ 101.4297),
+            Create_Market_Data!(with 2.125 at term 1.0 @ 102.0662),
+            Create_Market_Data!(with 1.625 at term 1.5 @ 102.2862),
+            Create_Market_Data!(with 0.125 at term 2.0 @ 99.9538),
+            Create_Market_Data!(with 0.250 at term 2.5 @ 100.0795),
+            Create_Market_Data!(with 0.250 at term 3.0 @ 99.7670),
+            Create_Market_Data!(with 2.250 at term 3.5 @ 106.3091),
+        ];
+        discount_factor(&market_data, Periodicity::SemiAnnual)
+    }
+
+    #[test]
+    fn test_key_rates_add_to_parallel() {
+        let curve = create_test_curve();
+        let b1 = create_bond(100.0, "01/15/2020", "01/15/2023", 0.02, "%m/%d/%Y").unwrap();
+        let settlement = NaiveDate::parse_from_str("01/15/2020", "%m/%d/%Y").unwrap();
+        let buckets = key_rate_durations(&b1, settlement, &curve, &DEFAULT_KEY_RATES);
+        assert_eq!(buckets.len(), 6);
+        assert_approx_eq!(buckets[4].dv01, 0.0, f64::EPSILON);
+        assert_approx_eq!(buckets[5].dv01, 0.0, f64::EPSILON);
+        let total: f64 = buckets.iter().map(|b| b.dv01).sum();
+        let parallel = b1.metrics_from_curve(settlement, &curve);
+        assert_approx_eq!(total, parallel.dv01, 1e-4);
+        let duration: f64 = buckets.iter().map(|b| b.duration).sum();
+        assert_approx_eq!(duration, parallel.modified_duration, 0.05);
+        assert!(buckets[3].dv01 > buckets[0].dv01);
+    }
+
+    #[test]
+    fn test_portfolio_key_rates() {
+        let curve = create_test_curve();
+        let b1 = create_bond(100.0, "01/15/2020", "01/15/2023", 0.02, "%m/%d/%Y").unwrap();
+        let b2 = create_bond(100.0, "07/15/2019", "07/15/2021", 0.03, "%m/%d/%Y").unwrap();
+        let settlement = NaiveDate::parse_from_str("01/15/2020", "%m/%d/%Y").unwrap();
+        let single_1 = key_rate_durations(&b1, settlement, &curve, &DEFAULT_KEY_RATES);
+        let single_2 = key_rate_durations(&b2, settlement, &curve, &DEFAULT_KEY_RATES);
+        let positions = vec![(*b1, 2.0), (*b2, 1.0)];
+        let portfolio =
+            portfolio_key_rate_durations(&positions, settlement, &curve, &DEFAULT_KEY_RATES);
+        for i in 0..portfolio.len() {
+            assert_approx_eq!(
+                portfolio[i].dv01,
+                2.0 * single_1[i].dv01 + single_2[i].dv01,
+                1e-4
+            );
+        }
+    }
+
+    #[test]
+    fn test_key_rates_between_nodes() {
+        // Nodes at six months and three and a half years only, so the key tenors of one and
+        // two years fall between them.
+        let dense = create_test_curve();
+        let curve = vec![dense[0], dense[6]];
+        let bumped = bump_curve(&curve, &DEFAULT_KEY_RATES, 1, 0.0001);
+        assert_eq!(bumped.len(), 7);
+        for term in [1.0, 2.0, 3.5] {
+            let weight = if term == 1.0 { 1.0 } else { 0.0 };
+            assert_approx_eq!(
+                bumped.discount(term),
+                curve.discount(term) * f64::exp(-0.0001 * weight * term),
+                1e-12
+            );
+        }
+
+        let b1 = create_bond(100.0, "01/15/2020", "01/15/2023", 0.02, "%m/%d/%Y").unwrap();
+        let settlement = NaiveDate::parse_from_str("01/15/2020", "%m/%d/%Y").unwrap();
+        let buckets = key_rate_durations(&b1, settlement, &curve, &DEFAULT_KEY_RATES);
+        assert!(buckets[1].dv01 > 0.0);
+        assert!(buckets[2].dv01 > buckets[1].dv01);
+        let total: f64 = buckets.iter().map(|b| b.dv01).sum();
+        let parallel = b1.metrics_from_curve(settlement, &curve);
+        assert_approx_eq!(total, parallel.dv01, 1e-4);
+    }
+}
@@ -0,0 +1,286 @@
+//! Binomial short rate trees. The short rate moves up or down with equal probability every
+//! step and the drift at each step is calibrated so that the tree prices the zero coupon
+//! bonds of a discount curve. Bonds with embedded options are valued by backward induction
+//! on the tree, exercising at each node when it pays.
+//!
+//! References :
+//!
+//!     . "Fixed Income Securities, Tools for Today's Markets" (4th ed.), Bruce Tuckman, Angel Serrat
+//!     . Black, Derman, Toy, "A One-Factor Model of Interest Rates", Financial Analysts Journal, 1990
+pub mod lattice {
+    use crate::bond::bond::DiscountCurve;
+    use crate::solver::solver::{brent, SolverConfig};
+    use serde::{Deserialize, Serialize};
+
+    /// The short rate models.
+    ///
+    /// * `HoLee` - normal rates, `r(i, j) = a(i) + volatility * sqrt(dt) * (2j - i)`. The
+    ///   volatility is in rate per year, 0.01 is 100 basis points, and rates can be negative.
+    /// * `BlackDermanToy` - lognormal rates, `r(i, j) = u(i) * exp(volatility * sqrt(dt) * (2j - i))`.
+    ///   The volatility is proportional, 0.2 is 20% of the rate.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+    pub enum ShortRateModel {
+        HoLee { volatility: f64 },
+        BlackDermanToy { volatility: f64 },
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum LatticeErrorType {
+        InvalidParameters,
+        CalibrationFailed,
+        NoSolution,
+    }
+
+    #[derive(Debug, Copy, Clone)]
+    pub struct LatticeError {
+        pub message: &'static str,
+        pub message_code: LatticeErrorType,
+    }
+
+    /// An option to end the cashflows at a node of the tree. The issuer calls at the `Call`
+    /// price when the value of the bond is above it, the holder puts at the `Put` price when
+    /// the value is below it. With `CallAndPut` both can exercise on the same step.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+    pub enum Exercise {
+        Call(f64),
+        Put(f64),
+        CallAndPut { call: f64, put: f64 },
+    }
+
+    impl Exercise {
+        /// The call and the put price.
+        pub fn prices(&self) -> (Option<f64>, Option<f64>) {
+            match self {
+                Exercise::Call(call) => (Some(*call), None),
+                Exercise::Put(put) => (None, Some(*put)),
+                Exercise::CallAndPut { call, put } => (Some(*call), Some(*put)),
+            }
+        }
+    }
+
+    /// A recombining tree of continuously compounded short rates, `rates[i][j]` is the rate
+    /// over step `i` after `j` up moves. Every step is `dt` years long.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ShortRateTree {
+        pub model: ShortRateModel,
+        pub dt: f64,
+        pub rates: Vec<Vec<f64>>,
+    }
+
+    impl ShortRateModel {
+        fn volatility(&self) -> f64 {
+            match self {
+                ShortRateModel::HoLee { volatility } => *volatility,
+                ShortRateModel::BlackDermanToy { volatility } => *volatility,
+            }
+        }
+
+        /// The rate after `j` up moves at step `i` for the calibrated `level`.
+        fn rate(&self, level: f64, i: usize, j: usize, dt: f64) -> f64 {
+            let moves = (2 * j) as f64 - i as f64;
+            match self {
+                ShortRateModel::HoLee { volatility } => level + volatility * dt.sqrt() * moves,
+                ShortRateModel::BlackDermanToy { volatility } => {
+                    level * f64::exp(volatility * dt.sqrt() * moves)
+                }
+            }
+        }
+    }
+
+    impl ShortRateTree {
+        /// A tree of `steps` steps over `horizon` years which reprices the zero coupon bonds of
+        /// the `curve` at every step. The level of each step is solved from the Arrow-Debreu
+        /// prices of the nodes, in closed form for Ho-Lee and by Brent's method for
+        /// Black-Derman-Toy, which needs positive rates.
+        pub fn calibrate<C: DiscountCurve + ?Sized>(
+            model: ShortRateModel,
+            curve: &C,
+            horizon: f64,
+            steps: usize,
+        ) -> Result<ShortRateTree, LatticeError> {
+            if steps == 0 || horizon <= 0.0 || model.volatility() < 0.0 {
+                return Err(LatticeError {
+                    message: "The tree needs steps, a positive horizon and volatility.",
+                    message_code: LatticeErrorType::InvalidParameters,
+                });
+            }
+            let dt = horizon / steps as f64;
+            let mut rates: Vec<Vec<f64>> = Vec::with_capacity(steps);
+            let mut state_prices = vec![1.0];
+            for i in 0..steps {
+                let target = curve.discount((i + 1) as f64 * dt);
+                let price = |level: f64| -> f64 {
+                    state_prices
+                        .iter()
+                        .enumerate()
+                        .map(|(j, q)| q * f64::exp(-model.rate(level, i, j, dt) * dt))
+                        .sum::<f64>()
+                };
+                let level = match model {
+                    ShortRateModel::HoLee { .. } => f64::ln(price(0.0) / target) / dt,
+                    ShortRateModel::BlackDermanToy { .. } => {
+                        brent(|u| price(u) - target, 1e-12, 2.0, &SolverConfig::default()).map_err(
+                            |_| LatticeError {
+                                message: "No positive rate level reprices the curve.",
+                                message_code: LatticeErrorType::CalibrationFailed,
+                            },
+                        )?
+                    }
+                };
+                let step: Vec<f64> = (0..=i).map(|j| model.rate(level, i, j, dt)).collect();
+                let mut next = vec![0.0; i + 2];
+                for (j, q) in state_prices.iter().enumerate() {
+                    let discounted = 0.5 * q * f64::exp(-step[j] * dt);
+                    next[j] += discounted;
+                    next[j + 1] += discounted;
+                }
+                state_prices = next;
+                rates.push(step);
+            }
+            Ok(ShortRateTree { model, dt, rates })
+        }
+
+        pub fn steps(&self) -> usize {
+            self.rates.len()
+        }
+
+        /// The tree with `spread` added to every rate, for valuation at an option-adjusted
+        /// spread.
+        pub fn shifted(&self, spread: f64) -> ShortRateTree {
+            ShortRateTree {
+                rates: self
+                    .rates
+                    .iter()
+                    .map(|step| step.iter().map(|rate| rate + spread).collect())
+                    .collect(),
+                ..self.clone()
+            }
+        }
+
+        /// The discount factor over step `i` after `j` up moves.
+        pub fn discount(&self, i: usize, j: usize) -> f64 {
+            f64::exp(-self.rates[i][j] * self.dt)
+        }
+
+        /// The value at the root of `cashflows[k]` paid at every node of step `k`. The
+        /// `exercise` at step `k` is decided on the value after the cashflow of the step,
+        /// which is paid either way. Returns the
+        /// value and, for each step, the boundary rate of the nodes exercised: the highest
+        /// rate at which a call is exercised or the lowest at which a put is.
+        pub fn backward_induction(
+            &self,
+            cashflows: &[f64],
+            exercise: &[Option<Exercise>],
+        ) -> (f64, Vec<Option<f64>>) {
+            self.backward_induction_by_node(cashflows, |i, _| {
+                exercise
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .map_or((None, None), |exercise| exercise.prices())
+            })
+        }
+
+        /// The backward induction with the call and put prices at node `(i, j)` given by
+        /// `prices(i, j)`, for options whose strike depends on the rates, such as a
+        /// make-whole call.
+        pub fn backward_induction_by_node<F: Fn(usize, usize) -> (Option<f64>, Option<f64>)>(
+            &self,
+            cashflows: &[f64],
+            prices: F,
+        ) -> (f64, Vec<Option<f64>>) {
+            let steps = self.steps();
+            let cashflow = |k: usize| cashflows.get(k).copied().unwrap_or(0.0);
+            let mut boundary = vec![None; steps + 1];
+            let mut values = vec![cashflow(steps); steps + 1];
+            for i in (0..steps).rev() {
+                let mut next = Vec::with_capacity(i + 1);
+                for j in 0..=i {
+                    let mut value = 0.5 * (values[j] + values[j + 1]) * self.discount(i, j);
+                    let rate = self.rates[i][j];
+                    let (call, put) = if i > 0 { prices(i, j) } else { (None, None) };
+                    match (call, put) {
+                        (Some(price), _) if value > price => {
+                            value = price;
+                            boundary[i] = Some(boundary[i].map_or(rate, |b: f64| b.max(rate)));
+                        }
+                        (_, Some(price)) if value < price => {
+                            value = price;
+                            boundary[i] = Some(boundary[i].map_or(rate, |b: f64| b.min(rate)));
+                        }
+                        _ => {}
+                    }
+                    next.push(value + if i > 0 { cashflow(i) } else { 0.0 });
+                }
+                values = next;
+            }
+            (values[0], boundary)
+        }
+
+        /// The value at every node `(i, j)` of the `cashflows` after step `i`, without
+        /// options.
+        pub fn values_after(&self, cashflows: &[f64]) -> Vec<Vec<f64>> {
+            let steps = self.steps();
+            let cashflow = |k: usize| cashflows.get(k).copied().unwrap_or(0.0);
+            let mut values = vec![Vec::new(); steps + 1];
+            values[steps] = vec![0.0; steps + 1];
+            for i in (0..steps).rev() {
+                let paid: Vec<f64> = values[i + 1].iter().map(|v| v + cashflow(i + 1)).collect();
+                values[i] = (0..=i)
+                    .map(|j| 0.5 * (paid[j] + paid[j + 1]) * self.discount(i, j))
+                    .collect();
+            }
+            values
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bond::bond::{DiscountCurve, DiscountFactor};
+    use crate::lattice::lattice::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn upward_curve() -> Vec<DiscountFactor> {
+        (1..=20)
+            .map(|i| {
+                let term = i as f64 * 0.5;
+                DiscountFactor {
+                    term,
+                    discount: f64::exp(-(0.03 + 0.002 * term) * term),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_calibrated_trees_reprice_the_curve() {
+        let curve = upward_curve();
+        for model in [
+            ShortRateModel::HoLee { volatility: 0.01 },
+            ShortRateModel::BlackDermanToy { volatility: 0.2 },
+        ] {
+            let tree = ShortRateTree::calibrate(model, &curve, 10.0, 40).unwrap();
+            assert_eq!(tree.steps(), 40);
+            for steps in [1, 7, 20, 40] {
+                let zero_coupon = ShortRateTree {
+                    rates: tree.rates[..steps].to_vec(),
+                    ..tree.clone()
+                };
+                let mut cashflows = vec![0.0; steps + 1];
+                cashflows[steps] = 1.0;
+                let (price, _) = zero_coupon.backward_induction(&cashflows, &[]);
+                assert_approx_eq!(price, curve.discount(steps as f64 * 0.25), 1e-12);
+                let after = zero_coupon.values_after(&cashflows);
+                assert_approx_eq!(after[0][0], price, 1e-12);
+            }
+        }
+        let negative = vec![DiscountFactor {
+            term: 1.0,
+            discount: 1.01,
+        }];
+        let model = ShortRateModel::BlackDermanToy { volatility: 0.2 };
+        let error = ShortRateTree::calibrate(model, &negative, 1.0, 4).unwrap_err();
+        assert_eq!(error.message_code, LatticeErrorType::CalibrationFailed);
+    }
+}
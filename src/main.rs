@@ -1,12 +1,26 @@
 mod bintree;
 mod bond;
+mod bootstrap;
+mod calendar;
 mod callable_bond;
+mod compounding;
 mod data_loader;
+mod day_count;
+mod frn;
+mod inflation;
 mod interest_rate_swap;
+mod interpolation;
+mod key_rate_duration;
+mod lattice;
+mod multi_curve;
+mod nelson_siegel;
 mod pandl;
 mod payment;
+mod rate_index;
 mod rates;
 mod restful_service;
+mod schedule;
+mod solver;
 mod tbills;
 
 use actix_web::middleware::Logger;
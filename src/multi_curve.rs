@@ -0,0 +1,754 @@
+//! A set of curves keyed by currency and index. Since the move to overnight rates, cash
+//! collateralized trades are discounted on the overnight index (OIS) curve of their
+//! currency, while the coupons of a term rate are projected from a curve of its own, whose
+//! forwards sit above the overnight forwards by the term basis.
+//!
+//! The OIS curves are bootstrapped from par OIS rates, such as the [`SwapRate`] records of
+//! `load_spot_rates`, and futures on the overnight index; each discounts itself. The
+//! projection curves are bootstrapped from basis swaps and futures, with the OIS curve of
+//! the currency discounting. A curve depends on its discount curve and on the curves its
+//! basis swaps are quoted against, and is solved after them.
+//!
+//! Curves which depend on each other in a cycle, such as 3M quoted against 6M and 6M
+//! against 3M, form a strongly connected component of the dependencies and are bootstrapped
+//! jointly: the nodes of all the curves of the component are solved together, by Newton's
+//! method on the values of all their quotes, see [`solve_nodes_jointly`]. A curve outside
+//! any cycle is solved node by node by [`solve_nodes`].
+//!
+//! References :
+//!
+//!     . "Interest Rate Modeling", Leif B.G. Andersen, Vladimir V. Piterbarg, Volume 1,
+//!       section 6.5
+//!     . Marc Henrard, "Interest Rate Modelling in the Multi-curve Framework", 2014
+pub mod multi_curve {
+    use crate::bond::bond::{DiscountCurve, Periodicity};
+    use crate::bootstrap::bootstrap::{
+        solve_nodes, solve_nodes_jointly, FuturesQuote, JointValuation, Valuation,
+    };
+    use crate::frn::frn::FloatingIndex;
+    use crate::rate_index::rate_index::{Currency, RateIndex, RateIndexRegistry, Tenor};
+    use crate::rates::rates::{
+        CurveError, CurveErrorType, OvernightRateType, SwapRate, YieldCurve,
+    };
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    /// The curve of an `index` in a `currency`.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+    pub struct CurveKey {
+        pub currency: Currency,
+        pub index: OvernightRateType,
+    }
+
+    /// A swap of the `index` flat for the `against` index plus the `spread`, a decimal, for
+    /// `term` years. Both legs pay at the tenor of the `index`, quarterly when it is an
+    /// overnight rate. A term basis swap has the overnight index of the currency on the
+    /// `against` leg.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct BasisSwapQuote {
+        pub index: OvernightRateType,
+        pub against: OvernightRateType,
+        pub term: f64,
+        pub spread: f64,
+    }
+
+    /// The quotes the curves are bootstrapped from. `Ois` is the par rate in percent of an
+    /// overnight index swap paying annually against the compounded index. `Futures` is a
+    /// future on the index, whose rate is taken as the forward rate of the period without a
+    /// convexity adjustment.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub enum CurveQuote {
+        Ois(SwapRate),
+        Basis(BasisSwapQuote),
+        Futures(OvernightRateType, FuturesQuote),
+    }
+
+    impl CurveQuote {
+        /// The index of the curve the quote is bootstrapped into.
+        pub fn index(&self) -> OvernightRateType {
+            match self {
+                CurveQuote::Ois(quote) => quote.swap_rate_type,
+                CurveQuote::Basis(quote) => quote.index,
+                CurveQuote::Futures(index, _) => *index,
+            }
+        }
+
+        /// The term in years from `as_of` of the last date the quote depends on.
+        fn maturity(&self, as_of: NaiveDate, rate_index: &RateIndex) -> f64 {
+            match self {
+                CurveQuote::Ois(quote) => quote.term,
+                CurveQuote::Basis(quote) => quote.term,
+                CurveQuote::Futures(_, quote) => {
+                    rate_index.day_count.year_fraction(as_of, quote.end)
+                }
+            }
+        }
+    }
+
+    /// The periods of a leg of `term` years paying every `step` years, with a short stub
+    /// first when the term is not a whole number of steps.
+    fn periods(term: f64, step: f64) -> Vec<(f64, f64)> {
+        let mut result = Vec::new();
+        let mut end = term;
+        while end > 1e-9 {
+            let start = (end - step).max(0.0);
+            result.push((start, end));
+            end = start;
+        }
+        result
+    }
+
+    /// The years between the resets of a leg paying the index.
+    fn leg_step(rate_index: &RateIndex) -> f64 {
+        match rate_index.tenor {
+            Tenor::Months(months) => months as f64 / 12.0,
+            Tenor::Overnight => 0.25,
+        }
+    }
+
+    /// The value of the quoted instrument for a unit notional when the quote is
+    /// bootstrapped into `curve`, zero when the curve reprices it. Futures are repriced in
+    /// rate. The `discount` curve is the OIS curve of the currency, the `against` curve the
+    /// curve of the other leg of a basis swap.
+    fn quote_value(
+        quote: &CurveQuote,
+        as_of: NaiveDate,
+        rate_index: &RateIndex,
+        curve: &YieldCurve,
+        discount: &YieldCurve,
+        against: Option<&YieldCurve>,
+    ) -> f64 {
+        match quote {
+            CurveQuote::Ois(quote) => {
+                let annuity: f64 = periods(quote.term, 1.0)
+                    .iter()
+                    .map(|(start, end)| (end - start) * discount.discount(*end))
+                    .sum();
+                1.0 - curve.discount(quote.term) - quote.rate / 100.0 * annuity
+            }
+            CurveQuote::Futures(_, quote) => {
+                let day_count = &rate_index.day_count;
+                let start = day_count.year_fraction(as_of, quote.start);
+                let end = day_count.year_fraction(as_of, quote.end);
+                curve.forward_rate(start, end) - (100.0 - quote.price) / 100.0
+            }
+            CurveQuote::Basis(quote) => {
+                let against = against.unwrap_or(discount);
+                periods(quote.term, leg_step(rate_index))
+                    .iter()
+                    .map(|(start, end)| {
+                        let index_leg = curve.discount(*start) / curve.discount(*end) - 1.0;
+                        let against_leg = against.discount(*start) / against.discount(*end) - 1.0
+                            + quote.spread * (end - start);
+                        (index_leg - against_leg) * discount.discount(*end)
+                    })
+                    .sum()
+            }
+        }
+    }
+
+    /// A curve a quote depends on: solved before the curve of the quote, or solved with it
+    /// at a position among the curves of a strongly connected component.
+    #[derive(Clone, Copy)]
+    enum Dependency<'a> {
+        Solved(&'a YieldCurve),
+        Joint(usize),
+    }
+
+    impl<'a> Dependency<'a> {
+        fn resolve<'b>(self, curves: &'b [YieldCurve]) -> &'b YieldCurve
+        where
+            'a: 'b,
+        {
+            match self {
+                Dependency::Solved(curve) => curve,
+                Dependency::Joint(position) => &curves[position],
+            }
+        }
+    }
+
+    /// The strongly connected components of the graph with the `edges` out of each vertex,
+    /// by Tarjan's algorithm. A component comes after the components it has edges to.
+    fn strongly_connected(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        struct Search {
+            index: Vec<Option<usize>>,
+            low: Vec<usize>,
+            stack: Vec<usize>,
+            on_stack: Vec<bool>,
+            next: usize,
+            components: Vec<Vec<usize>>,
+        }
+
+        fn visit(vertex: usize, edges: &[Vec<usize>], search: &mut Search) {
+            search.index[vertex] = Some(search.next);
+            search.low[vertex] = search.next;
+            search.next += 1;
+            search.stack.push(vertex);
+            search.on_stack[vertex] = true;
+            for &next in &edges[vertex] {
+                match search.index[next] {
+                    None => {
+                        visit(next, edges, search);
+                        search.low[vertex] = search.low[vertex].min(search.low[next]);
+                    }
+                    Some(index) if search.on_stack[next] => {
+                        search.low[vertex] = search.low[vertex].min(index);
+                    }
+                    Some(_) => {}
+                }
+            }
+            if search.index[vertex] == Some(search.low[vertex]) {
+                let mut component = Vec::new();
+                while let Some(member) = search.stack.pop() {
+                    search.on_stack[member] = false;
+                    component.push(member);
+                    if member == vertex {
+                        break;
+                    }
+                }
+                component.sort();
+                search.components.push(component);
+            }
+        }
+
+        let mut search = Search {
+            index: vec![None; edges.len()],
+            low: vec![0; edges.len()],
+            stack: Vec::new(),
+            on_stack: vec![false; edges.len()],
+            next: 0,
+            components: Vec::new(),
+        };
+        for vertex in 0..edges.len() {
+            if search.index[vertex].is_none() {
+                visit(vertex, edges, &mut search);
+            }
+        }
+        search.components
+    }
+
+    /// Curves by currency and index, with the index whose curve discounts each currency.
+    #[derive(Debug, Clone, Default)]
+    pub struct CurveSet {
+        curves: HashMap<CurveKey, YieldCurve>,
+        discounting: HashMap<Currency, OvernightRateType>,
+    }
+
+    impl CurveSet {
+        pub fn new() -> CurveSet {
+            CurveSet::default()
+        }
+
+        /// Adds the `curve` at `key`, returning the curve it replaces. The first curve of a
+        /// currency discounts it until [`CurveSet::set_discounting`] says otherwise.
+        pub fn insert(&mut self, key: CurveKey, curve: YieldCurve) -> Option<YieldCurve> {
+            self.discounting.entry(key.currency).or_insert(key.index);
+            self.curves.insert(key, curve)
+        }
+
+        /// Discounts the `currency` on the curve of `index`, which must be in the set.
+        pub fn set_discounting(
+            &mut self,
+            currency: Currency,
+            index: OvernightRateType,
+        ) -> Result<(), CurveError> {
+            self.get(CurveKey { currency, index })?;
+            self.discounting.insert(currency, index);
+            Ok(())
+        }
+
+        pub fn get(&self, key: CurveKey) -> Result<&YieldCurve, CurveError> {
+            self.curves.get(&key).ok_or(CurveError {
+                message: "No curve for the currency and index.",
+                message_code: CurveErrorType::MissingCurve,
+            })
+        }
+
+        pub fn keys(&self) -> impl Iterator<Item = &CurveKey> {
+            self.curves.keys()
+        }
+
+        /// The curve discounting cashflows in the `currency`.
+        pub fn discount_curve(&self, currency: Currency) -> Result<&YieldCurve, CurveError> {
+            match self.discounting.get(&currency) {
+                Some(index) => self.get(CurveKey {
+                    currency,
+                    index: *index,
+                }),
+                None => Err(CurveError {
+                    message: "No discount curve for the currency.",
+                    message_code: CurveErrorType::MissingCurve,
+                }),
+            }
+        }
+
+        /// The projection and the discount curve of a leg paying the `index`, for
+        /// [`crate::interest_rate_swap::interest_rate_swap::IRS::price`].
+        pub fn curves_for(
+            &self,
+            index: FloatingIndex,
+            registry: &RateIndexRegistry,
+        ) -> Result<(&YieldCurve, &YieldCurve), CurveError> {
+            let rate_index = lookup(registry, index.rate_type())?;
+            let projection = self.get(CurveKey {
+                currency: rate_index.currency,
+                index: rate_index.rate_type,
+            })?;
+            Ok((projection, self.discount_curve(rate_index.currency)?))
+        }
+
+        /// Bootstraps a curve for every index quoted, as of `as_of`, with the conventions of
+        /// the indices in the `registry`. The indices with `Ois` quotes are the OIS curves,
+        /// the first quoted in a currency discounts it. Each curve is solved after the curve
+        /// discounting it and the curves on the other legs of its basis swaps, whatever the
+        /// order of the quotes. Curves whose basis swaps depend on each other in a cycle
+        /// are solved together by [`solve_nodes_jointly`].
+        ///
+        /// Each curve has a node at the maturity of each of its quotes; two quotes of a
+        /// curve cannot mature together.
+        pub fn bootstrap(
+            as_of: NaiveDate,
+            quotes: &[CurveQuote],
+            registry: &RateIndexRegistry,
+        ) -> Result<CurveSet, CurveError> {
+            let mut indices: Vec<OvernightRateType> = Vec::new();
+            for quote in quotes {
+                if !indices.contains(&quote.index()) {
+                    indices.push(quote.index());
+                }
+            }
+            let mut set = CurveSet::new();
+            for index in &indices {
+                if is_ois(quotes, *index) {
+                    let currency = lookup(registry, *index)?.currency;
+                    set.discounting.entry(currency).or_insert(*index);
+                }
+            }
+            let mut edges = Vec::new();
+            for index in &indices {
+                let currency = lookup(registry, *index)?.currency;
+                let mut depends_on: Vec<OvernightRateType> = quotes
+                    .iter()
+                    .filter_map(|quote| match quote {
+                        CurveQuote::Basis(basis) if basis.index == *index => Some(basis.against),
+                        _ => None,
+                    })
+                    .collect();
+                if !is_ois(quotes, *index) {
+                    depends_on.extend(set.discounting.get(&currency));
+                }
+                let vertices: Vec<usize> = indices
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, other)| *other != index && depends_on.contains(other))
+                    .map(|(vertex, _)| vertex)
+                    .collect();
+                edges.push(vertices);
+            }
+            for component in strongly_connected(&edges) {
+                let component: Vec<OvernightRateType> =
+                    component.iter().map(|vertex| indices[*vertex]).collect();
+                let curves = set.bootstrap_curves(as_of, &component, quotes, registry)?;
+                for (index, curve) in component.iter().zip(curves) {
+                    let currency = lookup(registry, *index)?.currency;
+                    set.insert(
+                        CurveKey {
+                            currency,
+                            index: *index,
+                        },
+                        curve,
+                    );
+                }
+            }
+            Ok(set)
+        }
+
+        /// The curves of the `indices`, which depend on each other when there are several.
+        /// An OIS curve discounts itself, a projection curve is discounted on the OIS curve
+        /// of its currency, in the set or among the `indices`. The curves on the other legs
+        /// of the basis swaps are likewise in the set or among the `indices`.
+        fn bootstrap_curves(
+            &self,
+            as_of: NaiveDate,
+            indices: &[OvernightRateType],
+            quotes: &[CurveQuote],
+            registry: &RateIndexRegistry,
+        ) -> Result<Vec<YieldCurve>, CurveError> {
+            let dependency = |currency: Currency, index: OvernightRateType| match indices
+                .iter()
+                .position(|other| *other == index)
+            {
+                Some(position) => Ok(Dependency::Joint(position)),
+                None => self
+                    .get(CurveKey { currency, index })
+                    .map(Dependency::Solved),
+            };
+            let mut curves = Vec::new();
+            let mut instruments: Vec<(usize, f64, JointValuation)> = Vec::new();
+            for (position, index) in indices.iter().enumerate() {
+                let rate_index = lookup(registry, *index)?;
+                let currency = rate_index.currency;
+                let is_ois = is_ois(quotes, *index);
+                let discount = match self.discounting.get(&currency) {
+                    _ if is_ois => Dependency::Joint(position),
+                    Some(discount) => dependency(currency, *discount)?,
+                    None => Dependency::Solved(self.discount_curve(currency)?),
+                };
+                let periodicity = if is_ois {
+                    Periodicity::Annual
+                } else {
+                    rate_index
+                        .tenor
+                        .periodicity()
+                        .unwrap_or(Periodicity::Quarterly)
+                };
+                curves.push(YieldCurve {
+                    as_of,
+                    day_count: rate_index.day_count.clone(),
+                    periodicity,
+                    discount_factors: Vec::new(),
+                    interpolation: Default::default(),
+                });
+                for quote in quotes.iter().filter(|quote| quote.index() == *index) {
+                    let against = match quote {
+                        CurveQuote::Basis(basis) => Some(dependency(currency, basis.against)?),
+                        _ => None,
+                    };
+                    let valuation: JointValuation = Box::new(move |curves: &[YieldCurve]| {
+                        let discount = discount.resolve(curves);
+                        let against = against.map(|against| against.resolve(curves));
+                        quote_value(
+                            quote,
+                            as_of,
+                            rate_index,
+                            &curves[position],
+                            discount,
+                            against,
+                        )
+                    });
+                    instruments.push((position, quote.maturity(as_of, rate_index), valuation));
+                }
+            }
+            if curves.len() > 1 {
+                return solve_nodes_jointly(curves, instruments);
+            }
+            let valuations: Vec<(f64, Valuation)> = instruments
+                .into_iter()
+                .map(|(_, maturity, valuation)| {
+                    let valuation: Valuation =
+                        Box::new(move |curve: &YieldCurve| valuation(std::slice::from_ref(curve)));
+                    (maturity, valuation)
+                })
+                .collect();
+            Ok(vec![solve_nodes(curves.remove(0), valuations)?])
+        }
+    }
+
+    /// Whether the `index` has OIS quotes, which make it the OIS curve of its currency.
+    fn is_ois(quotes: &[CurveQuote], index: OvernightRateType) -> bool {
+        quotes
+            .iter()
+            .any(|quote| matches!(quote, CurveQuote::Ois(ois) if ois.swap_rate_type == index))
+    }
+
+    fn lookup(
+        registry: &RateIndexRegistry,
+        index: OvernightRateType,
+    ) -> Result<&RateIndex, CurveError> {
+        registry.get(index).map_err(|_| CurveError {
+            message: "The index is not in the registry.",
+            message_code: CurveErrorType::UnknownIndex,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bond::bond::DiscountCurve;
+    use crate::bootstrap::bootstrap::FuturesQuote;
+    use crate::data_loader::data_loader::load_spot_rates;
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::frn::frn::FloatingIndex;
+    use crate::interest_rate_swap::interest_rate_swap::{SwapDirection, IRS};
+    use crate::multi_curve::multi_curve::*;
+    use crate::rate_index::rate_index::{Currency, RateIndexRegistry};
+    use crate::rates::rates::{CurveErrorType, OvernightRateType, SwapRate, YieldCurve};
+    use assert_approx_eq::assert_approx_eq;
+    use chrono::NaiveDate;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_bootstrap_curve_set() {
+        let registry = RateIndexRegistry::default();
+        let as_of = date("05/14/2021");
+        let swap_rates = load_spot_rates(
+            String::from("tests/spot_rates.csv"),
+            OvernightRateType::SOFR,
+        )
+        .await
+        .unwrap();
+        let mut quotes: Vec<CurveQuote> = swap_rates.iter().map(|q| CurveQuote::Ois(*q)).collect();
+        quotes.push(CurveQuote::Futures(
+            OvernightRateType::SOFR,
+            FuturesQuote {
+                start: date("06/16/2021"),
+                end: date("09/15/2021"),
+                price: 99.97,
+            },
+        ));
+        let term_sofr = OvernightRateType::TermSOFR3M;
+        // Six month term SOFR against three month, quoted before the three month curve.
+        let six_month = OvernightRateType::TermSOFR6M;
+        for (term, spread) in [(1.0, 0.0004), (2.0, 0.0005)] {
+            quotes.push(CurveQuote::Basis(BasisSwapQuote {
+                index: six_month,
+                against: term_sofr,
+                term,
+                spread,
+            }));
+        }
+        quotes.push(CurveQuote::Futures(
+            term_sofr,
+            FuturesQuote {
+                start: date("05/18/2021"),
+                end: date("08/18/2021"),
+                price: 99.85,
+            },
+        ));
+        for (term, spread) in [(1.0, 0.0010), (2.0, 0.0012)] {
+            quotes.push(CurveQuote::Basis(BasisSwapQuote {
+                index: term_sofr,
+                against: OvernightRateType::SOFR,
+                term,
+                spread,
+            }));
+        }
+        let set = CurveSet::bootstrap(as_of, &quotes, &registry).unwrap();
+        assert_eq!(set.keys().count(), 3);
+        let sofr = set.discount_curve(Currency::USD).unwrap();
+        let term = set
+            .get(CurveKey {
+                currency: Currency::USD,
+                index: term_sofr,
+            })
+            .unwrap();
+
+        // The OIS curve reprices the par rates, whose fixed legs pay annually.
+        assert_eq!(sofr.discount_factors.len(), 5);
+        for quote in &swap_rates {
+            assert_approx_eq!(sofr.par_rate(quote.term), quote.rate / 100.0, 1e-9);
+        }
+        // Both futures.
+        let years = |d: &str| DayCountConvention::Act360.year_fraction(as_of, date(d));
+        assert_approx_eq!(
+            sofr.forward_rate(years("06/16/2021"), years("09/15/2021")),
+            0.0003,
+            1e-9
+        );
+        assert_approx_eq!(
+            term.forward_rate(years("05/18/2021"), years("08/18/2021")),
+            0.0015,
+            1e-9
+        );
+        // The two year basis swap: quarterly term SOFR against compounded SOFR plus 12
+        // basis points, discounted on SOFR.
+        let mut basis = 0.0;
+        for quarter in 1..=8 {
+            let (start, end) = ((quarter - 1) as f64 * 0.25, quarter as f64 * 0.25);
+            let term_leg = term.discount(start) / term.discount(end) - 1.0;
+            let sofr_leg = sofr.discount(start) / sofr.discount(end) - 1.0 + 0.0012 * 0.25;
+            basis += (term_leg - sofr_leg) * sofr.discount(end);
+        }
+        assert_approx_eq!(basis, 0.0, 1e-12);
+        // The two year six month against three month basis, paying semi-annually.
+        let six = set
+            .get(CurveKey {
+                currency: Currency::USD,
+                index: six_month,
+            })
+            .unwrap();
+        let mut basis = 0.0;
+        for half in 1..=4 {
+            let (start, end) = ((half - 1) as f64 * 0.5, half as f64 * 0.5);
+            let six_leg = six.discount(start) / six.discount(end) - 1.0;
+            let three_leg = term.discount(start) / term.discount(end) - 1.0 + 0.0005 * 0.5;
+            basis += (six_leg - three_leg) * sofr.discount(end);
+        }
+        assert_approx_eq!(basis, 0.0, 1e-12);
+
+        // A term SOFR swap projects off its own curve and discounts on SOFR.
+        let irs = IRS::for_index(
+            1000000.0,
+            as_of,
+            date("05/14/2023"),
+            0.001,
+            FloatingIndex::TermRate(term_sofr),
+            SwapDirection::PayFixed,
+            &registry,
+        )
+        .unwrap();
+        let (projection, discount) = set.curves_for(irs.floating_leg.index, &registry).unwrap();
+        let multi_curve = irs.price(as_of, &[], projection, discount).unwrap();
+        let single_curve = irs.price(as_of, &[], discount, discount).unwrap();
+        assert!(multi_curve.floating_leg_pv > single_curve.floating_leg_pv);
+        assert_approx_eq!(multi_curve.fixed_leg_pv, single_curve.fixed_leg_pv, 1e-9);
+    }
+
+    /// The value of a basis swap of the `index` curve against the `against` curve plus the
+    /// `spread`, both legs paying every `step` years, discounted on `discount`.
+    fn basis_value(
+        index: &YieldCurve,
+        against: &YieldCurve,
+        discount: &YieldCurve,
+        term: f64,
+        step: f64,
+        spread: f64,
+    ) -> f64 {
+        let periods = (term / step).round() as i32;
+        (1..=periods)
+            .map(|period| {
+                let (start, end) = ((period - 1) as f64 * step, period as f64 * step);
+                let index_leg = index.discount(start) / index.discount(end) - 1.0;
+                let against_leg =
+                    against.discount(start) / against.discount(end) - 1.0 + spread * step;
+                (index_leg - against_leg) * discount.discount(end)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_cyclic_basis_swaps() {
+        let registry = RateIndexRegistry::default();
+        let as_of = date("05/14/2021");
+        let three_month = OvernightRateType::TermSOFR3M;
+        let six_month = OvernightRateType::TermSOFR6M;
+        let mut quotes: Vec<CurveQuote> = [(1.0, 0.5), (2.0, 0.7), (3.0, 0.9)]
+            .iter()
+            .map(|(term, rate)| {
+                CurveQuote::Ois(SwapRate {
+                    date: as_of,
+                    term: *term,
+                    rate: *rate,
+                    swap_rate_type: OvernightRateType::SOFR,
+                })
+            })
+            .collect();
+        // The three month curve is quoted against six month at two years, the six month
+        // curve against three month at one and three years: neither can be solved first.
+        quotes.push(CurveQuote::Futures(
+            three_month,
+            FuturesQuote {
+                start: date("05/18/2021"),
+                end: date("08/18/2021"),
+                price: 99.50,
+            },
+        ));
+        quotes.push(CurveQuote::Basis(BasisSwapQuote {
+            index: three_month,
+            against: six_month,
+            term: 2.0,
+            spread: -0.0006,
+        }));
+        for (term, spread) in [(1.0, 0.0005), (3.0, 0.0007)] {
+            quotes.push(CurveQuote::Basis(BasisSwapQuote {
+                index: six_month,
+                against: three_month,
+                term,
+                spread,
+            }));
+        }
+        let set = CurveSet::bootstrap(as_of, &quotes, &registry).unwrap();
+        let curve = |index| {
+            set.get(CurveKey {
+                currency: Currency::USD,
+                index,
+            })
+            .unwrap()
+        };
+        let (discount, three, six) = (
+            curve(OvernightRateType::SOFR),
+            curve(three_month),
+            curve(six_month),
+        );
+        assert_eq!(three.discount_factors.len(), 2);
+        assert_eq!(six.discount_factors.len(), 2);
+
+        let years = |d: &str| DayCountConvention::Act360.year_fraction(as_of, date(d));
+        assert_approx_eq!(
+            three.forward_rate(years("05/18/2021"), years("08/18/2021")),
+            0.005,
+            1e-9
+        );
+        assert_approx_eq!(
+            basis_value(three, six, discount, 2.0, 0.25, -0.0006),
+            0.0,
+            1e-9
+        );
+        for (term, spread) in [(1.0, 0.0005), (3.0, 0.0007)] {
+            assert_approx_eq!(
+                basis_value(six, three, discount, term, 0.5, spread),
+                0.0,
+                1e-9
+            );
+        }
+        // The six month forwards sit above the three month forwards by about the basis.
+        let forward = |curve: &YieldCurve| curve.forward_rate(0.5, 1.0);
+        assert!(forward(six) > forward(three));
+    }
+
+    #[test]
+    fn test_curve_set_errors() {
+        let registry = RateIndexRegistry::default();
+        let as_of = date("05/14/2021");
+        // A basis swap without the curve of its other leg.
+        let basis = CurveQuote::Basis(BasisSwapQuote {
+            index: OvernightRateType::TermSOFR3M,
+            against: OvernightRateType::SOFR,
+            term: 1.0,
+            spread: 0.001,
+        });
+        let error = CurveSet::bootstrap(as_of, &[basis], &registry).unwrap_err();
+        assert_eq!(error.message_code, CurveErrorType::MissingCurve);
+        // Two term rates quoted only against each other, without the OIS curve discounting.
+        let cycle = [
+            CurveQuote::Basis(BasisSwapQuote {
+                index: OvernightRateType::TermSOFR3M,
+                against: OvernightRateType::TermSOFR6M,
+                term: 1.0,
+                spread: 0.001,
+            }),
+            CurveQuote::Basis(BasisSwapQuote {
+                index: OvernightRateType::TermSOFR6M,
+                against: OvernightRateType::TermSOFR3M,
+                term: 1.0,
+                spread: -0.001,
+            }),
+        ];
+        let error = CurveSet::bootstrap(as_of, &cycle, &registry).unwrap_err();
+        assert_eq!(error.message_code, CurveErrorType::MissingCurve);
+        let ois = |term: f64| {
+            CurveQuote::Ois(SwapRate {
+                date: as_of,
+                term,
+                rate: 0.05,
+                swap_rate_type: OvernightRateType::ESTR,
+            })
+        };
+        let error = CurveSet::bootstrap(as_of, &[ois(1.0), ois(1.0)], &registry).unwrap_err();
+        assert_eq!(error.message_code, CurveErrorType::InvalidTerm);
+        let error = CurveSet::bootstrap(as_of, &[ois(1.0)], &RateIndexRegistry::new()).unwrap_err();
+        assert_eq!(error.message_code, CurveErrorType::UnknownIndex);
+
+        let mut set = CurveSet::bootstrap(as_of, &[ois(1.0), ois(2.0)], &registry).unwrap();
+        assert!(set.discount_curve(Currency::EUR).is_ok());
+        assert!(set.discount_curve(Currency::USD).is_err());
+        let error = set
+            .set_discounting(Currency::EUR, OvernightRateType::SOFR)
+            .unwrap_err();
+        assert_eq!(error.message_code, CurveErrorType::MissingCurve);
+    }
+}
@@ -0,0 +1,465 @@
+//! Parametric yield curves fitted to bond prices. Exact bootstrapping with
+//! [`crate::bond::bond::discount_factor`] needs a bond at every coupon date and passes
+//! through every price, including the noise. The Nelson-Siegel family describes the whole
+//! curve with a few parameters (level, slope and curvature) which are fitted by least squares.
+pub mod nelson_siegel {
+    use crate::bond::bond::{Bond, DiscountCurve, DiscountFactor, MarketData, Periodicity};
+    use crate::solver::solver::solve_linear;
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Serialize};
+
+    /// * `NelsonSiegel` - level `beta0`, slope `beta1` and a hump `beta2` at `tau1`.
+    /// * `Svensson` - adds a second hump `beta3` at `tau2`, which fits the long end better.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+    pub enum ParametricModel {
+        NelsonSiegel,
+        Svensson,
+    }
+
+    /// The continuously compounded zero rate for a term `t` is
+    ///
+    ///     beta0 + beta1 * h(t / tau1) + beta2 * (h(t / tau1) - exp(-t / tau1))
+    ///           + beta3 * (h(t / tau2) - exp(-t / tau2)),   h(x) = (1 - exp(-x)) / x
+    ///
+    /// where the last term is only used by the `Svensson` model. `beta0` is the long rate
+    /// and `beta0 + beta1` the short rate.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct ParametricCurve {
+        pub model: ParametricModel,
+        pub beta0: f64,
+        pub beta1: f64,
+        pub beta2: f64,
+        pub beta3: f64,
+        pub tau1: f64,
+        pub tau2: f64,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum FitErrorType {
+        TooFewInstruments,
+        NoConvergence,
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct FitError {
+        pub message: &'static str,
+        pub message_code: FitErrorType,
+    }
+
+    /// The fitted `curve` and the `residuals`, the model price less the market price of each
+    /// instrument in the order given.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct CurveFit {
+        pub curve: ParametricCurve,
+        pub residuals: Vec<f64>,
+        pub rmse: f64,
+        pub iterations: u32,
+    }
+
+    fn loading(x: f64) -> f64 {
+        if x < 1e-4 {
+            1.0 - x / 2.0
+        } else {
+            (1.0 - f64::exp(-x)) / x
+        }
+    }
+
+    impl ParametricCurve {
+        pub fn zero_rate(&self, term: f64) -> f64 {
+            let x1 = term / self.tau1;
+            let mut rate =
+                self.beta0 + self.beta1 * loading(x1) + self.beta2 * (loading(x1) - f64::exp(-x1));
+            if self.model == ParametricModel::Svensson {
+                let x2 = term / self.tau2;
+                rate += self.beta3 * (loading(x2) - f64::exp(-x2));
+            }
+            rate
+        }
+
+        /// The discount factors at `terms`, for the functions which take a slice of
+        /// discount factors rather than a [`DiscountCurve`].
+        pub fn discount_factors(&self, terms: &[f64]) -> Vec<DiscountFactor> {
+            terms
+                .iter()
+                .map(|term| DiscountFactor {
+                    term: *term,
+                    discount: self.discount(*term),
+                })
+                .collect()
+        }
+
+        /// The parameters searched by the fit. The decay terms are positive, so their logs
+        /// are searched instead.
+        fn parameters(&self) -> Vec<f64> {
+            match self.model {
+                ParametricModel::NelsonSiegel => {
+                    vec![self.beta0, self.beta1, self.beta2, self.tau1.ln()]
+                }
+                ParametricModel::Svensson => vec![
+                    self.beta0,
+                    self.beta1,
+                    self.beta2,
+                    self.beta3,
+                    self.tau1.ln(),
+                    self.tau2.ln(),
+                ],
+            }
+        }
+
+        fn from_parameters(model: ParametricModel, p: &[f64]) -> ParametricCurve {
+            match model {
+                ParametricModel::NelsonSiegel => ParametricCurve {
+                    model,
+                    beta0: p[0],
+                    beta1: p[1],
+                    beta2: p[2],
+                    beta3: 0.0,
+                    tau1: p[3].exp(),
+                    tau2: 1.0,
+                },
+                ParametricModel::Svensson => ParametricCurve {
+                    model,
+                    beta0: p[0],
+                    beta1: p[1],
+                    beta2: p[2],
+                    beta3: p[3],
+                    tau1: p[4].exp(),
+                    tau2: p[5].exp(),
+                },
+            }
+        }
+    }
+
+    impl DiscountCurve for ParametricCurve {
+        fn discount(&self, term: f64) -> f64 {
+            if term <= 0.0 {
+                return 1.0;
+            }
+            f64::exp(-self.zero_rate(term) * term)
+        }
+    }
+
+    /// The price per 100 of a bond in the `market_data`, which pays `coupon_rate` percent at
+    /// the `periodicity` and matures in `term` years.
+    pub fn market_data_price<C: DiscountCurve + ?Sized>(
+        market_data: &MarketData,
+        periodicity: Periodicity,
+        curve: &C,
+    ) -> f64 {
+        let periods_per_year = periodicity.periods_per_year();
+        let coupon = market_data.coupon_rate / periods_per_year;
+        let mut price = 100.0 * curve.discount(market_data.term);
+        let mut term = market_data.term;
+        while term > 1e-4 {
+            price += coupon * curve.discount(term);
+            term -= 1.0 / periods_per_year;
+        }
+        price
+    }
+
+    /// Fit the `model` to the `market_data` prices, which are full prices per 100.
+    pub fn fit_market_data(
+        market_data: &[MarketData],
+        periodicity: Periodicity,
+        model: ParametricModel,
+    ) -> Result<CurveFit, FitError> {
+        let level = market_data
+            .iter()
+            .max_by(|a, b| a.term.total_cmp(&b.term))
+            .map_or(0.0, |md| md.coupon_rate / 100.0);
+        let market_prices: Vec<f64> = market_data.iter().map(|md| md.market_price).collect();
+        fit(model, level, &market_prices, |curve| {
+            market_data
+                .iter()
+                .map(|md| market_data_price(md, periodicity, curve))
+                .collect()
+        })
+    }
+
+    /// Fit the `model` to `(bond, clean price)` pairs quoted at `settlement`.
+    pub fn fit_bonds(
+        settlement: NaiveDate,
+        bonds: &[(Bond, f64)],
+        model: ParametricModel,
+    ) -> Result<CurveFit, FitError> {
+        let level = bonds
+            .iter()
+            .max_by_key(|(bond, _)| bond.maturity_date)
+            .map_or(0.0, |(bond, _)| bond.coupon_rate);
+        let market_prices: Vec<f64> = bonds.iter().map(|(_, price)| *price).collect();
+        // The cashflows and accrued interest do not depend on the curve.
+        let cashflows = bonds
+            .iter()
+            .map(|(bond, _)| {
+                (
+                    bond.cashflow_times(settlement),
+                    bond.accrued_interest(settlement),
+                )
+            })
+            .collect::<Vec<_>>();
+        fit(model, level, &market_prices, |curve| {
+            cashflows
+                .iter()
+                .map(|(times, accrued)| {
+                    times
+                        .iter()
+                        .map(|(_, time, amount)| amount * curve.discount(*time))
+                        .sum::<f64>()
+                        - accrued
+                })
+                .collect()
+        })
+    }
+
+    fn sum_of_squares(residuals: &[f64]) -> f64 {
+        residuals.iter().map(|r| r * r).sum()
+    }
+
+    const MAX_ITERATIONS: u32 = 200;
+
+    /// Least squares by Levenberg-Marquardt with a forward difference Jacobian. The decay
+    /// terms make the problem non-convex, so the search starts from a small grid of them
+    /// and keeps the best fit.
+    fn fit<F: Fn(&ParametricCurve) -> Vec<f64>>(
+        model: ParametricModel,
+        level: f64,
+        market_prices: &[f64],
+        price: F,
+    ) -> Result<CurveFit, FitError> {
+        let residuals = |curve: &ParametricCurve| -> Vec<f64> {
+            price(curve)
+                .iter()
+                .zip(market_prices)
+                .map(|(model_price, market_price)| model_price - market_price)
+                .collect()
+        };
+        let mut best: Option<CurveFit> = None;
+        for tau1 in [0.5, 1.5, 4.0] {
+            let tau2s: &[f64] = match model {
+                ParametricModel::NelsonSiegel => &[1.0],
+                ParametricModel::Svensson => &[3.0, 10.0],
+            };
+            for tau2 in tau2s {
+                let start = ParametricCurve {
+                    model,
+                    beta0: level,
+                    beta1: 0.0,
+                    beta2: 0.0,
+                    beta3: 0.0,
+                    tau1,
+                    tau2: *tau2,
+                };
+                if market_prices.len() < start.parameters().len() {
+                    return Err(FitError {
+                        message: "Fewer instruments than parameters.",
+                        message_code: FitErrorType::TooFewInstruments,
+                    });
+                }
+                let result = levenberg_marquardt(start, &residuals);
+                if result.rmse.is_finite() && best.as_ref().is_none_or(|fit| result.rmse < fit.rmse)
+                {
+                    best = Some(result);
+                }
+            }
+        }
+        best.ok_or(FitError {
+            message: "The fit did not converge to finite parameters.",
+            message_code: FitErrorType::NoConvergence,
+        })
+    }
+
+    fn levenberg_marquardt<R: Fn(&ParametricCurve) -> Vec<f64>>(
+        start: ParametricCurve,
+        residuals: &R,
+    ) -> CurveFit {
+        let model = start.model;
+        let mut p = start.parameters();
+        let mut r = residuals(&start);
+        let mut cost = sum_of_squares(&r);
+        let mut lambda = 1e-3;
+        let mut iterations = 0;
+        while iterations < MAX_ITERATIONS {
+            iterations += 1;
+            let jacobian: Vec<Vec<f64>> = (0..p.len())
+                .map(|k| {
+                    let h = 1e-3 * p[k].abs().max(1.0);
+                    let mut bumped = p.clone();
+                    bumped[k] += h;
+                    residuals(&ParametricCurve::from_parameters(model, &bumped))
+                        .iter()
+                        .zip(&r)
+                        .map(|(up, base)| (up - base) / h)
+                        .collect()
+                })
+                .collect();
+            let jtj: Vec<Vec<f64>> = jacobian
+                .iter()
+                .map(|a| {
+                    jacobian
+                        .iter()
+                        .map(|b| a.iter().zip(b).map(|(x, y)| x * y).sum())
+                        .collect()
+                })
+                .collect();
+            let gradient: Vec<f64> = jacobian
+                .iter()
+                .map(|a| -a.iter().zip(&r).map(|(x, y)| x * y).sum::<f64>())
+                .collect();
+            let mut improved = false;
+            while lambda < 1e10 {
+                let mut damped = jtj.clone();
+                for (k, row) in damped.iter_mut().enumerate() {
+                    row[k] += lambda * jtj[k][k].max(1e-6);
+                }
+                let candidate: Vec<f64> = match solve_linear(damped, gradient.clone()) {
+                    Some(step) => p.iter().zip(&step).map(|(x, dx)| x + dx).collect(),
+                    None => {
+                        lambda *= 10.0;
+                        continue;
+                    }
+                };
+                let candidate_r = residuals(&ParametricCurve::from_parameters(model, &candidate));
+                let candidate_cost = sum_of_squares(&candidate_r);
+                if candidate_cost.is_finite() && candidate_cost < cost {
+                    let converged = cost - candidate_cost <= 1e-6 * cost;
+                    p = candidate;
+                    r = candidate_r;
+                    cost = candidate_cost;
+                    lambda = (lambda / 10.0).max(1e-7);
+                    improved = !converged;
+                    break;
+                }
+                lambda *= 10.0;
+            }
+            if !improved {
+                break;
+            }
+        }
+        CurveFit {
+            curve: ParametricCurve::from_parameters(model, &p),
+            rmse: (cost / r.len() as f64).sqrt(),
+            residuals: r,
+            iterations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bond::bond::*;
+    use crate::nelson_siegel::nelson_siegel::*;
+    use crate::Create_Market_Data;
+    use assert_approx_eq::assert_approx_eq;
+    use chrono::NaiveDate;
+
+    fn create_test_curve() -> ParametricCurve {
+        ParametricCurve {
+            model: ParametricModel::NelsonSiegel,
+            beta0: 0.045,
+            beta1: -0.02,
+            beta2: 0.01,
+            beta3: 0.0,
+            tau1: 2.0,
+            tau2: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_fit_nelson_siegel() {
+        let curve = create_test_curve();
+        assert_approx_eq!(curve.zero_rate(0.0), 0.025, 1e-6);
+        let market_data: Vec<MarketData> = [1.0, 2.0, 3.0, 5.0, 7.0, 10.0, 20.0, 30.0]
+            .iter()
+            .map(|term| {
+                let mut md = MarketData {
+                    coupon_rate: 4.0,
+                    term: *term,
+                    market_price: 0.0,
+                };
+                md.market_price = market_data_price(&md, Periodicity::SemiAnnual, &curve);
+                md
+            })
+            .collect();
+        let fit = fit_market_data(
+            &market_data,
+            Periodicity::SemiAnnual,
+            ParametricModel::NelsonSiegel,
+        )
+        .unwrap();
+        assert_eq!(fit.residuals.len(), market_data.len());
+        assert!(fit.rmse < 1e-3);
+        for term in [0.5, 2.5, 8.0, 25.0] {
+            assert_approx_eq!(fit.curve.zero_rate(term), curve.zero_rate(term), 1e-4);
+        }
+        let too_few = fit_market_data(
+            &market_data[..3],
+            Periodicity::SemiAnnual,
+            ParametricModel::NelsonSiegel,
+        );
+        assert_eq!(
+            too_few.unwrap_err().message_code,
+            FitErrorType::TooFewInstruments
+        );
+    }
+
+    #[test]
+    fn test_fit_bonds() {
+        let settlement = NaiveDate::parse_from_str("02/15/2021", "%m/%d/%Y").unwrap();
+        let curve = create_test_curve();
+        let maturities = [
+            "08/15/2022",
+            "11/15/2023",
+            "02/15/2026",
+            "05/15/2028",
+            "08/15/2030",
+            "11/15/2035",
+            "02/15/2041",
+        ];
+        // Noise of a few cents on the prices.
+        let noise = [0.02, -0.03, 0.01, 0.0, -0.02, 0.03, -0.01];
+        let bonds: Vec<(Bond, f64)> = maturities
+            .iter()
+            .zip(noise)
+            .map(|(maturity, noise)| {
+                let bond = *create_bond(100.0, "02/15/2001", maturity, 0.035, "%m/%d/%Y").unwrap();
+                let clean =
+                    bond.price_from_curve(settlement, &curve) - bond.accrued_interest(settlement);
+                (bond, clean + noise)
+            })
+            .collect();
+        let fit = fit_bonds(settlement, &bonds, ParametricModel::Svensson).unwrap();
+        assert_eq!(fit.curve.model, ParametricModel::Svensson);
+        assert!(fit.rmse < 0.05);
+        for (residual, noise) in fit.residuals.iter().zip(noise) {
+            assert!((residual + noise).abs() < 0.05);
+        }
+        // The fitted curve prices through the bond functions.
+        let (bond, price) = &bonds[2];
+        let model_price =
+            bond.price_from_curve(settlement, &fit.curve) - bond.accrued_interest(settlement);
+        assert_approx_eq!(model_price - price, fit.residuals[2], 1e-4);
+    }
+
+    #[test]
+    fn test_fit_treasury_data() {
+        let market_data = vec![
+            Create_Market_Data!(with 2.875 at term 0.5 @ 101.4297),
+            Create_Market_Data!(with 2.125 at term 1.0 @ 102.0662),
+            Create_Market_Data!(with 1.625 at term 1.5 @ 102.2862),
+            Create_Market_Data!(with 0.125 at term 2.0 @ 99.9538),
+            Create_Market_Data!(with 0.250 at term 2.5 @ 100.0795),
+            Create_Market_Data!(with 0.250 at term 3.0 @ 99.7670),
+            Create_Market_Data!(with 2.250 at term 3.5 @ 106.3091),
+        ];
+        let fit = fit_market_data(
+            &market_data,
+            Periodicity::SemiAnnual,
+            ParametricModel::NelsonSiegel,
+        )
+        .unwrap();
+        assert!(fit.rmse < 0.1);
+        let bootstrapped = discount_factor(&market_data, Periodicity::SemiAnnual);
+        let sampled = fit.curve.discount_factors(&[3.5]);
+        assert_approx_eq!(sampled[0].discount, bootstrapped[6].discount, 2e-3);
+    }
+}
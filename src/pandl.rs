@@ -1,36 +1,62 @@
 pub mod pandl {
     use crate::bond::bond::Bond;
     use crate::bond::bond::DiscountFactor;
+    use crate::callable_bond::callable_bonds::EffectiveMetrics;
+    use crate::solver::solver::{brent, SolverConfig, SolverError};
     use chrono::NaiveDate;
     use log::debug;
     use std::result::Result::*;
 
-    type TermRate = f32;
+    type TermRate = f64;
 
     pub enum RealizedForwards {
         RealizedForwards,
         UnrealizedForwards,
     }
 
+    /// The buckets of a P&L attribution. The `Spread` bucket is the change in the
+    /// option-adjusted spread over the period, which for a bond without options is its
+    /// spread over the curve.
     pub enum Attribution {
         CashCarry,
         CashRollDown,
         Rates,
-        Spread,
+        Spread { start_oas: f64, end_oas: f64 },
+    }
+
+    impl Attribution {
+        /// The P&L of the `Spread` bucket for a position worth `price`, from the effective
+        /// duration and convexity at the starting OAS:
+        ///
+        ///     pnl = -duration * price * d_oas + 0.5 * convexity * price * d_oas^2
+        ///
+        /// Other buckets return `None`.
+        pub fn spread_pnl(&self, price: f64, metrics: &EffectiveMetrics) -> Option<f64> {
+            match self {
+                Attribution::Spread { start_oas, end_oas } => {
+                    let change = end_oas - start_oas;
+                    Some(
+                        -metrics.effective_duration * price * change
+                            + 0.5 * metrics.effective_convexity * price * change * change,
+                    )
+                }
+                _ => None,
+            }
+        }
     }
 
     pub struct PriceStructure {
         pub pricing_date: NaiveDate,
         pub term_structure: TermStructure,
-        pub spread: f32,
-        pub price: f32,
+        pub spread: f64,
+        pub price: f64,
     }
 
     /// The term structure associated with an attribution.
     pub struct TermStructure {
         pub starting_period: NaiveDate,
-        pub term: f32, // One of the term values for the bond.
-        pub spot_rate: f32,
+        pub term: f64, // One of the term values for the bond.
+        pub spot_rate: f64,
     }
 
     /// The profit and loss entry for a PandL report.
@@ -47,7 +73,7 @@ pub mod pandl {
     }
 
     impl PriceStructure {
-        pub fn change(&self) -> f32 {
+        pub fn change(&self) -> f64 {
             return 0.0;
         }
     }
@@ -67,9 +93,9 @@ pub mod pandl {
     pub struct BondTransaction<'a> {
         pub underlying: &'a Bond,
         pub purchase_date: NaiveDate,
-        pub purchase_price: f32,
+        pub purchase_price: f64,
         pub sale_date: NaiveDate,
-        pub sale_price: f32,
+        pub sale_price: f64,
         pub term_rate: Vec<TermRate>,
     }
 
@@ -85,9 +111,9 @@ pub mod pandl {
 
         fn compute_individual_term(
             &self,
-            previous_discount: Option<f32>,
-            current_discount: Option<f32>,
-        ) -> f32 {
+            previous_discount: Option<f64>,
+            current_discount: Option<f64>,
+        ) -> f64 {
             match (previous_discount, current_discount) {
                 (None, Some(discount)) => ((1.0 / discount) - 1.0) * 2.0,
                 (Some(d1), Some(d2)) => ((d1 / d2) - 1.0) * 2.0,
@@ -99,7 +125,7 @@ pub mod pandl {
             if discount_factors.len() == 0 {
                 Vec::new()
             } else {
-                let mut prev_discount: Option<f32> = None;
+                let mut prev_discount: Option<f64> = None;
                 let mut result = Vec::new();
                 for discount_factor in discount_factors {
                     let current =
@@ -115,8 +141,8 @@ pub mod pandl {
         pub fn compute_realized_forwards(
             &'a self,
             forward: usize,
-            spread: f32,
-        ) -> std::result::Result<f32, &str> {
+            spread: f64,
+        ) -> std::result::Result<f64, &str> {
             if self.term_rate.len() == 0 {
                 Err("Term Structure is not initialized")
             } else {
@@ -126,24 +152,34 @@ pub mod pandl {
                     let effective_rate = self.underlying.get_effective_rate(rate + spread);
                     denom = denom * (1.0 + effective_rate);
                     let coupon_rate = self.underlying.get_effective_coupon_payment();
-                    println!(
-                        "Using coupon {:?} rate {:?} : effective_rate {:?}, spread : {:?}",
-                        coupon_rate, rate, effective_rate, spread
-                    );
                     let current = coupon_rate / denom;
-                    println!("Using coupon {:?} rate {:?} : effective_rate {:?}, spread : {:?}, denom : {:?}, current_value : {:?}",
+                    debug!("Using coupon {:?} rate {:?} : effective_rate {:?}, spread : {:?}, denom : {:?}, current_value : {:?}",
                             coupon_rate, rate, effective_rate, spread, denom, current);
 
                     result += current;
                 }
-                println!("Principal {:?}", self.underlying.principal);
+                debug!("Principal {:?}", self.underlying.principal);
                 result += self.underlying.principal / denom;
                 Ok(result)
             }
         }
 
+        /// The spread over the term rates at which the realized forwards from `forward`
+        /// reprice the bond to `price`.
+        pub fn implied_spread(&'a self, forward: usize, price: f64) -> Result<f64, SolverError> {
+            brent(
+                |spread| match self.compute_realized_forwards(forward, spread) {
+                    Ok(value) => value - price,
+                    Err(_) => f64::NAN,
+                },
+                -0.05,
+                0.05,
+                &SolverConfig::default(),
+            )
+        }
+
         /// Returns the realized returns in percentage points.
-        pub fn compute_realized_return(&self) -> f32 {
+        pub fn compute_realized_return(&self) -> f64 {
             let cashflows = self
                 .underlying
                 .cashflow_between_inclusive(self.purchase_date, self.sale_date);
@@ -182,9 +218,10 @@ mod tests {
     use chrono::NaiveDate;
 
     use crate::bond::bond::*;
+    use crate::callable_bond::callable_bonds::EffectiveMetrics;
     use crate::pandl::pandl::*;
 
-    fn create_test_bond(interest: f32) -> Result<Box<Bond>, BondError> {
+    fn create_test_bond(interest: f64) -> Result<Box<Bond>, BondError> {
         return create_bond_with_periodicity(
             100.0,
             String::from("11/15/2012").as_str(),
@@ -243,4 +280,37 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_implied_spread() {
+        let b1 = create_bond(100.0, "11/16/1992", "11/15/2022", 7.625, "%m/%d/%Y").unwrap();
+        let date_format = "%m/%d/%Y";
+        let mut bt = BondTransaction {
+            underlying: &*b1,
+            purchase_date: NaiveDate::parse_from_str("11/13/2020", date_format).unwrap(),
+            purchase_price: 114.87654,
+            sale_date: NaiveDate::parse_from_str("05/14/2021", date_format).unwrap(),
+            sale_price: 114.87654,
+            term_rate: Vec::new(),
+        };
+        assert!(bt.implied_spread(1, 111.11555).is_err());
+        bt.set_term_rates(&[0.001013, 0.001746, 0.002429, 0.002185].to_vec());
+        assert_approx_eq!(bt.implied_spread(1, 111.11555).unwrap(), -0.000116, 2e-6);
+    }
+
+    #[test]
+    fn test_spread_attribution() {
+        let metrics = EffectiveMetrics {
+            price: 100.0,
+            oas: 0.01,
+            effective_duration: 5.0,
+            effective_convexity: 30.0,
+        };
+        let spread = Attribution::Spread {
+            start_oas: 0.01,
+            end_oas: 0.011,
+        };
+        assert_approx_eq!(spread.spread_pnl(100.0, &metrics).unwrap(), -0.4985, 1e-12);
+        assert!(Attribution::Rates.spread_pnl(100.0, &metrics).is_none());
+    }
 }
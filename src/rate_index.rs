@@ -0,0 +1,290 @@
+//! The conventions of the interest rate indices. Swaps, floating rate notes and loaders look
+//! an index up in a registry for its currency, day count, fixing calendar, publication lag
+//! and tenor, rather than assuming the conventions of SOFR.
+//!
+//! References :
+//!
+//!     . "2021 ISDA Interest Rate Derivatives Definitions", Floating Rate Matrix
+//!     . The administrators of the rates: the New York Fed, the Bank of England, the ECB,
+//!       the Bank of Japan, SIX, the Bank of Canada, the RBA and CME Group
+pub mod rate_index {
+    use crate::bond::bond::Periodicity;
+    use crate::calendar::calendar::Calendar;
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::rates::rates::OvernightRateType;
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    /// ISO 4217 currency codes.
+    #[allow(clippy::upper_case_acronyms)]
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+    pub enum Currency {
+        USD,
+        GBP,
+        EUR,
+        JPY,
+        CHF,
+        CAD,
+        AUD,
+    }
+
+    /// The period a rate is for, overnight or a term of months.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+    pub enum Tenor {
+        Overnight,
+        Months(u32),
+    }
+
+    impl Tenor {
+        /// The periods of a schedule resetting at the tenor, for the tenors with one.
+        pub fn periodicity(&self) -> Option<Periodicity> {
+            match self {
+                Tenor::Months(3) => Some(Periodicity::Quarterly),
+                Tenor::Months(6) => Some(Periodicity::SemiAnnual),
+                Tenor::Months(12) => Some(Periodicity::Annual),
+                _ => None,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum RateIndexErrorType {
+        UnknownIndex,
+        MismatchedIndex,
+    }
+
+    #[derive(Debug, Copy, Clone)]
+    pub struct RateIndexError {
+        pub message: &'static str,
+        pub message_code: RateIndexErrorType,
+    }
+
+    /// An interest rate index. The rate for a date is published `publication_lag` business
+    /// days of the fixing `calendar` later, SOFR for today is published tomorrow morning.
+    /// Overnight rates accrue on the `day_count` between business days of the calendar.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct RateIndex {
+        pub rate_type: OvernightRateType,
+        pub name: String,
+        pub currency: Currency,
+        pub day_count: DayCountConvention,
+        pub calendar: Calendar,
+        pub publication_lag: u32,
+        pub tenor: Tenor,
+    }
+
+    impl RateIndex {
+        pub fn new(
+            rate_type: OvernightRateType,
+            name: &str,
+            currency: Currency,
+            day_count: DayCountConvention,
+            calendar: Calendar,
+            publication_lag: u32,
+            tenor: Tenor,
+        ) -> RateIndex {
+            RateIndex {
+                rate_type,
+                name: String::from(name),
+                currency,
+                day_count,
+                calendar,
+                publication_lag,
+                tenor,
+            }
+        }
+
+        pub fn is_overnight(&self) -> bool {
+            self.tenor == Tenor::Overnight
+        }
+
+        /// The date the rate for `fixing_date` is published.
+        pub fn publication_date(&self, fixing_date: NaiveDate) -> NaiveDate {
+            self.calendar
+                .advance(fixing_date, self.publication_lag as i32)
+        }
+
+        /// An error unless `rate_type` is this index, for fixings or quotes of another
+        /// index passed to a trade on this one.
+        pub fn check(&self, rate_type: OvernightRateType) -> Result<(), RateIndexError> {
+            if rate_type != self.rate_type {
+                return Err(RateIndexError {
+                    message: "The rates are for a different index.",
+                    message_code: RateIndexErrorType::MismatchedIndex,
+                });
+            }
+            Ok(())
+        }
+    }
+
+    /// The indices by rate type. The default registry has the conventions published by
+    /// the administrator of each rate; [`RateIndexRegistry::register`] adds an index or
+    /// replaces the conventions of one.
+    ///
+    /// ```ignore
+    /// let registry = RateIndexRegistry::default();
+    /// let estr = registry.find("€STR")?;
+    /// assert_eq!(estr.currency, Currency::EUR);
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct RateIndexRegistry {
+        indices: HashMap<OvernightRateType, RateIndex>,
+    }
+
+    impl Default for RateIndexRegistry {
+        fn default() -> Self {
+            use Currency::*;
+            use DayCountConvention::{Act360, Act365Fixed};
+            use OvernightRateType::*;
+            let overnight = [
+                (SOFR, "SOFR", USD, Act360, Calendar::Sifma, 1),
+                (SONIA, "SONIA", GBP, Act365Fixed, Calendar::UnitedKingdom, 1),
+                (ESTR, "€STR", EUR, Act360, Calendar::Target, 1),
+                (TONA, "TONA", JPY, Act365Fixed, Calendar::Japan, 1),
+                (SARON, "SARON", CHF, Act360, Calendar::Switzerland, 0),
+                (CORRA, "CORRA", CAD, Act365Fixed, Calendar::Canada, 1),
+                (AONIA, "AONIA", AUD, Act365Fixed, Calendar::Australia, 0),
+                (
+                    FedFunds,
+                    "Fed Funds",
+                    USD,
+                    Act360,
+                    Calendar::FederalReserve,
+                    1,
+                ),
+            ];
+            let term = [
+                (TermSOFR1M, "Term SOFR 1M", 1),
+                (TermSOFR3M, "Term SOFR 3M", 3),
+                (TermSOFR6M, "Term SOFR 6M", 6),
+                (TermSOFR12M, "Term SOFR 12M", 12),
+            ];
+            let mut registry = RateIndexRegistry::new();
+            for (rate_type, name, currency, day_count, calendar, lag) in overnight {
+                registry.register(RateIndex::new(
+                    rate_type,
+                    name,
+                    currency,
+                    day_count,
+                    calendar,
+                    lag,
+                    Tenor::Overnight,
+                ));
+            }
+            for (rate_type, name, months) in term {
+                registry.register(RateIndex::new(
+                    rate_type,
+                    name,
+                    USD,
+                    Act360,
+                    Calendar::Sifma,
+                    0,
+                    Tenor::Months(months),
+                ));
+            }
+            registry
+        }
+    }
+
+    impl RateIndexRegistry {
+        /// An empty registry.
+        pub fn new() -> RateIndexRegistry {
+            RateIndexRegistry {
+                indices: HashMap::new(),
+            }
+        }
+
+        /// Adds the `index`, returning the conventions it replaces.
+        pub fn register(&mut self, index: RateIndex) -> Option<RateIndex> {
+            self.indices.insert(index.rate_type, index)
+        }
+
+        pub fn get(&self, rate_type: OvernightRateType) -> Result<&RateIndex, RateIndexError> {
+            self.indices.get(&rate_type).ok_or(RateIndexError {
+                message: "The index is not in the registry.",
+                message_code: RateIndexErrorType::UnknownIndex,
+            })
+        }
+
+        /// The index by its `name`, or the name of its rate type, ignoring case: "€STR" and
+        /// "estr" are both €STR.
+        pub fn find(&self, name: &str) -> Result<&RateIndex, RateIndexError> {
+            let name = name.trim();
+            self.indices
+                .values()
+                .find(|index| {
+                    index.name.eq_ignore_ascii_case(name)
+                        || format!("{:?}", index.rate_type).eq_ignore_ascii_case(name)
+                })
+                .ok_or(RateIndexError {
+                    message: "No index of that name in the registry.",
+                    message_code: RateIndexErrorType::UnknownIndex,
+                })
+        }
+
+        pub fn indices(&self) -> impl Iterator<Item = &RateIndex> {
+            self.indices.values()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calendar::calendar::Calendar;
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::rate_index::rate_index::*;
+    use crate::rates::rates::OvernightRateType;
+    use chrono::NaiveDate;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap()
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = RateIndexRegistry::default();
+        assert_eq!(registry.indices().count(), 12);
+        let tona = registry.get(OvernightRateType::TONA).unwrap();
+        assert_eq!(tona.currency, Currency::JPY);
+        assert_eq!(tona.day_count, DayCountConvention::Act365Fixed);
+        // SOFR for Friday 03/28/2024 is published on Monday, after Good Friday.
+        let sofr = registry.find("sofr").unwrap();
+        assert_eq!(
+            sofr.publication_date(date("03/28/2024")),
+            date("04/01/2024")
+        );
+        // The Fed Funds rate is published on Good Friday.
+        let fed_funds = registry.find("FedFunds").unwrap();
+        assert_eq!(
+            fed_funds.publication_date(date("03/28/2024")),
+            date("03/29/2024")
+        );
+        assert_eq!(registry.find("€STR").unwrap().currency, Currency::EUR);
+        let term = registry.find("Term SOFR 3M").unwrap();
+        assert!(!term.is_overnight());
+        assert_eq!(term.tenor, Tenor::Months(3));
+
+        assert_eq!(
+            registry.find("LIBOR").unwrap_err().message_code,
+            RateIndexErrorType::UnknownIndex
+        );
+        assert_eq!(
+            sofr.check(OvernightRateType::SONIA)
+                .unwrap_err()
+                .message_code,
+            RateIndexErrorType::MismatchedIndex
+        );
+
+        // Replacing the conventions of an index.
+        let mut aonia = registry.get(OvernightRateType::AONIA).unwrap().clone();
+        aonia.calendar = Calendar::Joint(vec![Calendar::Australia, Calendar::Sifma]);
+        assert!(registry.register(aonia).is_some());
+        assert!(matches!(
+            registry.get(OvernightRateType::AONIA).unwrap().calendar,
+            Calendar::Joint(_)
+        ));
+        let empty = RateIndexRegistry::new();
+        assert!(empty.get(OvernightRateType::SOFR).is_err());
+    }
+}
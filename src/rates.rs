@@ -5,7 +5,11 @@
 /// if, a 10-year Treasury bond has a coupon of 1.625% and costs 100.00 then the par rate is
 /// 1.625%.
 pub mod rates {
+    use crate::bond::bond::DiscountCurve;
     use crate::bond::bond::DiscountFactor;
+    use crate::bond::bond::{discount_factor, MarketData};
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::interpolation::interpolation::Interpolation;
     use serde::{Deserialize, Serialize};
     use std::collections::HashMap;
     use std::hash::{Hash, Hasher};
@@ -16,10 +20,31 @@ pub mod rates {
     /// Acronyms
     /// * SOFR - Secured Overnight Financing Rate.
     /// * SONIA - Sterling Overnight Interbank Average.
-    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd)]
+    /// * ESTR - Euro Short-Term Rate, €STR.
+    /// * TONA - Tokyo Overnight Average Rate.
+    /// * SARON - Swiss Average Rate Overnight.
+    /// * CORRA - Canadian Overnight Repo Rate Average.
+    /// * AONIA - Australian Overnight Index Average, the RBA cash rate.
+    /// * FedFunds - the effective Federal Funds rate.
+    /// * TermSOFR - the CME term SOFR rates, forward looking rates for one to twelve months.
+    ///
+    /// The conventions of each rate are in the
+    /// [`crate::rate_index::rate_index::RateIndexRegistry`].
+    #[allow(clippy::upper_case_acronyms)]
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Hash)]
     pub enum OvernightRateType {
         SOFR,
         SONIA,
+        ESTR,
+        TONA,
+        SARON,
+        CORRA,
+        AONIA,
+        FedFunds,
+        TermSOFR1M,
+        TermSOFR3M,
+        TermSOFR6M,
+        TermSOFR12M,
     }
 
     /// The `SwapRate` for a `date` for a `term`. These elements are
@@ -28,15 +53,15 @@ pub mod rates {
     #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
     pub struct SwapRate {
         pub date: NaiveDate,
-        pub term: f32,
-        pub rate: f32,
+        pub term: f64,
+        pub rate: f64,
         pub swap_rate_type: OvernightRateType,
     }
 
     #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
     pub struct NextSettlementDate {
         pub start_date: NaiveDate,
-        pub term: f32,
+        pub term: f64,
         pub next_settlement_date: NaiveDate,
     }
 
@@ -49,7 +74,7 @@ pub mod rates {
     impl PartialEq for NextSettlementDate {
         fn eq(&self, other: &Self) -> bool {
             return self.start_date == other.start_date
-                && (f32::EPSILON < (self.term - other.term).abs());
+                && (f64::EPSILON < (self.term - other.term).abs());
         }
     }
 
@@ -85,22 +110,259 @@ pub mod rates {
     }
 
     /// Approximate discount factors for spot rates. The number of days is assumed.
+    /// The `i`-th rate in `market_data` is the spot rate for `(i + 1) * term` years. Terms of
+    /// at least one period compound at the `periodicity`, shorter terms earn simple interest
+    /// over a year of `number_of_days` days.
     pub fn discount_factors(
-        market_data: Vec<f32>,
+        market_data: Vec<f64>,
         periodicity: Periodicity,
-        number_of_days: f32,
-        term: f32,
+        number_of_days: f64,
+        term: f64,
     ) -> Vec<DiscountFactor> {
+        let periods_per_year = periodicity.periods_per_year();
         let mut result: Vec<DiscountFactor> = Vec::new();
-        return result;
+        for (i, rate) in market_data.iter().enumerate() {
+            let current_term = (i + 1) as f64 * term;
+            let discount = if current_term * periods_per_year < 1.0 {
+                1.0 / (1.0 + rate * current_term * 365.0 / number_of_days)
+            } else {
+                1.0 / f64::powf(
+                    1.0 + rate / periods_per_year,
+                    current_term * periods_per_year,
+                )
+            };
+            result.push(DiscountFactor {
+                term: current_term,
+                discount,
+            });
+        }
+        result
+    }
+
+    /// How a rate is compounded when it is converted to and from a discount factor.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub enum Compounding {
+        Simple,
+        Continuous,
+        Compounded(Periodicity),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum CurveErrorType {
+        EmptyCurve,
+        InvalidTerm,
+        MissingCurve,
+        UnknownIndex,
+        NoSolution,
+    }
+
+    #[derive(Debug, Copy, Clone)]
+    pub struct CurveError {
+        pub message: &'static str,
+        pub message_code: CurveErrorType,
+    }
+
+    /// A term structure of discount factors as of a date. Terms are in years from `as_of`,
+    /// measured with the `day_count`; dates are converted to terms before they are looked up,
+    /// so the curve answers for any date, not only the input terms. Par rates assume a fixed
+    /// leg paying at the `periodicity`.
+    ///
+    /// Between the terms the discount factors are found by the `interpolation`, which is
+    /// log-linear unless another method is chosen with [`YieldCurve::with_interpolation`].
+    /// The method is part of the curve, and is serialized with it, so a curve reproduces
+    /// the same prices wherever it is used.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct YieldCurve {
+        pub as_of: NaiveDate,
+        pub day_count: DayCountConvention,
+        pub periodicity: Periodicity,
+        pub discount_factors: Vec<DiscountFactor>,
+        #[serde(default)]
+        pub interpolation: Interpolation,
+    }
+
+    impl DiscountCurve for YieldCurve {
+        fn discount(&self, term: f64) -> f64 {
+            self.interpolation.discount(&self.discount_factors, term)
+        }
+    }
+
+    impl YieldCurve {
+        /// A curve from discount factors, for example the output of
+        /// [`crate::bond::bond::discount_factor`].
+        pub fn from_discount_factors(
+            as_of: NaiveDate,
+            day_count: DayCountConvention,
+            periodicity: Periodicity,
+            discount_factors: &[DiscountFactor],
+        ) -> Result<YieldCurve, CurveError> {
+            if discount_factors.is_empty() {
+                return Err(CurveError {
+                    message: "No discount factors.",
+                    message_code: CurveErrorType::EmptyCurve,
+                });
+            }
+            if discount_factors.iter().any(|df| df.term <= 0.0) {
+                return Err(CurveError {
+                    message: "Discount factor terms must be positive.",
+                    message_code: CurveErrorType::InvalidTerm,
+                });
+            }
+            let mut sorted = discount_factors.to_vec();
+            sorted.sort();
+            Ok(YieldCurve {
+                as_of,
+                day_count,
+                periodicity,
+                discount_factors: sorted,
+                interpolation: Interpolation::default(),
+            })
+        }
+
+        /// A curve from the prices of par bonds, through [`crate::bond::bond::discount_factor`].
+        pub fn from_market_data(
+            as_of: NaiveDate,
+            day_count: DayCountConvention,
+            periodicity: Periodicity,
+            market_data: &Vec<MarketData>,
+        ) -> Result<YieldCurve, CurveError> {
+            let discount_factors = discount_factor(market_data, periodicity);
+            YieldCurve::from_discount_factors(as_of, day_count, periodicity, &discount_factors)
+        }
+
+        pub fn with_interpolation(mut self, interpolation: Interpolation) -> YieldCurve {
+            self.interpolation = interpolation;
+            self
+        }
+
+        /// Bootstrap a curve from par swap rates quoted in percent, such as the records from
+        /// `load_spot_rates`. The fixed leg pays at the `periodicity`; par rates for payment
+        /// dates between the quoted terms are interpolated linearly.
+        ///
+        ///     discount_n = (1 - c_n / f * sum(discount_i, i < n)) / (1 + c_n / f)
+        pub fn from_swap_rates(
+            swap_rates: &[SwapRate],
+            day_count: DayCountConvention,
+            periodicity: Periodicity,
+        ) -> Result<YieldCurve, CurveError> {
+            let mut quotes = swap_rates.to_vec();
+            quotes.sort_by(|a, b| a.term.total_cmp(&b.term));
+            let (first, last) = match (quotes.first(), quotes.last()) {
+                (Some(first), Some(last)) => (*first, *last),
+                _ => {
+                    return Err(CurveError {
+                        message: "No swap rates.",
+                        message_code: CurveErrorType::EmptyCurve,
+                    })
+                }
+            };
+            let periods_per_year = periodicity.periods_per_year();
+            let periods = (last.term * periods_per_year).round() as usize;
+            let par_rate = |term: f64| -> f64 {
+                let mut previous = first;
+                for quote in &quotes {
+                    if term <= quote.term {
+                        if quote.term == previous.term {
+                            return quote.rate / 100.0;
+                        }
+                        let weight = (term - previous.term) / (quote.term - previous.term);
+                        return (previous.rate + weight * (quote.rate - previous.rate)) / 100.0;
+                    }
+                    previous = *quote;
+                }
+                last.rate / 100.0
+            };
+            let mut annuity = 0.0;
+            let mut discount_factors = Vec::new();
+            for period in 1..=periods {
+                let term = period as f64 / periods_per_year;
+                let coupon = par_rate(term) / periods_per_year;
+                let discount = (1.0 - coupon * annuity) / (1.0 + coupon);
+                annuity += discount;
+                discount_factors.push(DiscountFactor { term, discount });
+            }
+            YieldCurve::from_discount_factors(first.date, day_count, periodicity, &discount_factors)
+        }
+
+        /// The curve with the continuously compounded zero rates of its discount factors
+        /// shifted by `shift`, a parallel shift when the interpolation is log-linear.
+        pub fn shifted(&self, shift: f64) -> YieldCurve {
+            YieldCurve {
+                discount_factors: self
+                    .discount_factors
+                    .iter()
+                    .map(|df| DiscountFactor {
+                        term: df.term,
+                        discount: df.discount * f64::exp(-shift * df.term),
+                    })
+                    .collect(),
+                ..self.clone()
+            }
+        }
+
+        /// The term in years from the `as_of` date of the curve to `date`.
+        pub fn term(&self, date: NaiveDate) -> f64 {
+            self.day_count.year_fraction(self.as_of, date)
+        }
+
+        pub fn discount_at(&self, date: NaiveDate) -> f64 {
+            self.discount(self.term(date))
+        }
+
+        /// The zero (spot) rate for `term` with the `compounding`.
+        pub fn zero_rate(&self, term: f64, compounding: Compounding) -> f64 {
+            if term <= 0.0 {
+                return self.zero_rate(f64::EPSILON.sqrt(), compounding);
+            }
+            let discount = self.discount(term);
+            match compounding {
+                Compounding::Simple => (1.0 / discount - 1.0) / term,
+                Compounding::Continuous => -f64::ln(discount) / term,
+                Compounding::Compounded(periodicity) => {
+                    let f = periodicity.periods_per_year();
+                    f * (f64::powf(discount, -1.0 / (f * term)) - 1.0)
+                }
+            }
+        }
+
+        /// The simply compounded forward rate between `start` and `end`, or the
+        /// instantaneous forward rate at `start` when the two coincide.
+        pub fn forward_rate(&self, start: f64, end: f64) -> f64 {
+            let step = f64::EPSILON.cbrt();
+            if (end - start).abs() < step {
+                let term = start.max(step);
+                return f64::ln(self.discount(term - step) / self.discount(term + step))
+                    / (2.0 * step);
+            }
+            (self.discount(start) / self.discount(end) - 1.0) / (end - start)
+        }
+
+        /// The fixed rate of a swap of `tenor` years, which prices at par. The first
+        /// period is a short stub if the tenor is not a whole number of periods.
+        pub fn par_rate(&self, tenor: f64) -> f64 {
+            let periods_per_year = self.periodicity.periods_per_year();
+            let mut annuity = 0.0;
+            let mut end = tenor;
+            while end > 0.0 {
+                let start = (end - 1.0 / periods_per_year).max(0.0);
+                annuity += (end - start) * self.discount(end);
+                end = start;
+            }
+            (1.0 - self.discount(tenor)) / annuity
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use core::f64;
-
-    use crate::rates::rates::NextSettlementDate;
+    use crate::bond::bond::{
+        discount_factor, DiscountCurve, DiscountFactor, MarketData, Periodicity,
+    };
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::interpolation::interpolation::Interpolation;
+    use crate::rates::rates::*;
+    use crate::solver::solver::{brent, SolverConfig};
+    use crate::Create_Market_Data;
     use assert_approx_eq::assert_approx_eq;
     use chrono::NaiveDate;
     #[test]
@@ -139,47 +401,126 @@ mod tests {
         result - target
     }
 
-    #[derive(PartialEq)]
-    enum Sign {
-        POSITIVE,
-        NEGATIVE,
+    #[test]
+    fn test_rates_ytd() {
+        let target = 111.3969;
+        let current = brent(
+            |rate| test_rate_generated(3.8125, 100.00, rate, 3, target),
+            0.00,
+            0.01,
+            &SolverConfig::default(),
+        )
+        .unwrap();
+        assert_approx_eq!(0.00025155303361024117, current);
+    }
+
+    fn create_test_swap_rates() -> Vec<SwapRate> {
+        let date = NaiveDate::parse_from_str("05/14/2021", "%m/%d/%Y").unwrap();
+        [(0.5, 0.0340), (1.0, 0.0460), (1.5, 0.0670), (2.0, 0.1120)]
+            .iter()
+            .map(|(term, rate)| SwapRate {
+                date,
+                term: *term,
+                rate: *rate,
+                swap_rate_type: OvernightRateType::SOFR,
+            })
+            .collect()
     }
 
-    fn sign(test: f64) -> Sign {
-        if test < 0.0 {
-            Sign::NEGATIVE
-        } else {
-            Sign::POSITIVE
-        }
+    #[test]
+    fn test_discount_factors() {
+        let result = discount_factors([0.01, 0.02].to_vec(), Periodicity::SemiAnnual, 360.0, 0.5);
+        assert_approx_eq!(result[0].discount, 1.0 / 1.005, 1e-6);
+        assert_approx_eq!(result[1].discount, 1.0 / (1.01 * 1.01), 1e-6);
+        let short = discount_factors([0.01].to_vec(), Periodicity::SemiAnnual, 360.0, 0.25);
+        assert_approx_eq!(
+            short[0].discount,
+            1.0 / (1.0 + 0.01 * 0.25 * 365.0 / 360.0),
+            1e-6
+        );
     }
 
     #[test]
-    fn test_rates_ytd() {
-        let target = 111.3969;
-        let mut iter = 1;
-        let max_iter = 100;
-        let mut x = 0.0;
-        let mut low = 0.00;
-        let mut high = 0.01;
-        let mut current = 0.0;
-        let init_a = test_rate_generated(3.8125, 100.00, low, 3, target);
-        let init_b = test_rate_generated(3.8125, 100.00, high, 3, target);
-        while iter < max_iter {
-            println!("Current {:?}", current);
-            current = (low + high) / 2.0;
-            x = test_rate_generated(3.8125, 100.00, current, 3, target);
-            println!("{:?} : {:?} : {:?} : {:?}", current, x, init_a, init_b);
-            if high - low / 2.0 < f64::EPSILON {
-                println!("{:?}", current);
-                break;
-            }
-            if sign(x) == sign(init_a) {
-                low = current;
-            } else {
-                high = current;
-            }
-            iter = iter + 1;
+    fn test_yield_curve_from_swap_rates() {
+        let curve = YieldCurve::from_swap_rates(
+            &create_test_swap_rates(),
+            DayCountConvention::Act360,
+            Periodicity::SemiAnnual,
+        )
+        .unwrap();
+        assert_eq!(curve.discount_factors.len(), 4);
+        for quote in create_test_swap_rates() {
+            assert_approx_eq!(curve.par_rate(quote.term), quote.rate / 100.0, 1e-6);
         }
-        assert_approx_eq!(0.00025155303361024117, current);
+        // A date between the input terms.
+        let date = NaiveDate::parse_from_str("02/01/2023", "%m/%d/%Y").unwrap();
+        let discount = curve.discount_at(date);
+        assert!(discount < curve.discount(1.5) && discount > curve.discount(2.0));
+    }
+
+    #[test]
+    fn test_yield_curve_rates() {
+        let date = NaiveDate::parse_from_str("05/14/2021", "%m/%d/%Y").unwrap();
+        let discount_factors: Vec<DiscountFactor> = (1..5)
+            .map(|i| DiscountFactor {
+                term: i as f64 / 2.0,
+                discount: f64::exp(-0.03 * i as f64 / 2.0),
+            })
+            .collect();
+        let curve = YieldCurve::from_discount_factors(
+            date,
+            DayCountConvention::Act365Fixed,
+            Periodicity::SemiAnnual,
+            &discount_factors,
+        )
+        .unwrap();
+        assert_approx_eq!(curve.zero_rate(1.25, Compounding::Continuous), 0.03, 1e-5);
+        let semi_annual = curve.zero_rate(1.25, Compounding::Compounded(Periodicity::SemiAnnual));
+        assert_approx_eq!(semi_annual, 2.0 * (f64::exp(0.015) - 1.0), 1e-5);
+        assert_approx_eq!(
+            curve.forward_rate(1.0, 1.5),
+            (f64::exp(0.015) - 1.0) / 0.5,
+            1e-5
+        );
+        assert_approx_eq!(curve.forward_rate(1.25, 1.25), 0.03, 1e-6);
+        assert_approx_eq!(curve.forward_rate(0.0, 0.0), 0.03, 1e-6);
+        assert!(YieldCurve::from_discount_factors(
+            date,
+            DayCountConvention::Act365Fixed,
+            Periodicity::SemiAnnual,
+            &[]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_yield_curve_interpolation() {
+        let market_data = vec![
+            Create_Market_Data!(with 2.875 at term 0.5 @ 101.4297),
+            Create_Market_Data!(with 2.125 at term 1.0 @ 102.0662),
+            Create_Market_Data!(with 1.625 at term 1.5 @ 102.2862),
+            Create_Market_Data!(with 0.125 at term 2.0 @ 99.9538),
+        ];
+        let date = NaiveDate::parse_from_str("05/14/2021", "%m/%d/%Y").unwrap();
+        let curve = YieldCurve::from_market_data(
+            date,
+            DayCountConvention::Act365Fixed,
+            Periodicity::SemiAnnual,
+            &market_data,
+        )
+        .unwrap();
+        assert_eq!(curve.interpolation, Interpolation::LogLinearDiscount);
+        let nodes = discount_factor(&market_data, Periodicity::SemiAnnual);
+        assert_approx_eq!(curve.discount(1.25), nodes.discount(1.25), f64::EPSILON);
+        let spline = curve
+            .clone()
+            .with_interpolation(Interpolation::NaturalCubicSpline);
+        assert_approx_eq!(spline.discount(1.5), nodes[2].discount, 1e-6);
+        assert!((spline.discount(1.25) - curve.discount(1.25)).abs() > 0.0);
+        // The method survives a round trip through JSON.
+        let json = serde_json::to_string(&spline).unwrap();
+        let restored: YieldCurve = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.interpolation, Interpolation::NaturalCubicSpline);
+        assert_approx_eq!(restored.discount(1.25), spline.discount(1.25), f64::EPSILON);
     }
 }
@@ -0,0 +1,336 @@
+//! Coupon schedules. The regular coupon dates are rolled backward from the maturity date
+//! (or the penultimate coupon date) so that any irregular period, the *stub*, falls at the
+//! start, which is the convention for bonds. A stub at the end is rolled forward from the
+//! effective date (or the first coupon date) instead.
+pub mod schedule {
+    use crate::bond::bond::Periodicity;
+    use crate::calendar::calendar::{BusinessDayConvention, Calendar};
+    use crate::day_count::day_count::{is_last_day_of_month, DayCountConvention};
+    use chrono::{Datelike, Months, NaiveDate};
+    use serde::{Deserialize, Serialize};
+
+    /// Where an irregular period goes when the dates do not divide into whole periods.
+    ///
+    /// * `ShortFirst` - a first period shorter than a regular period.
+    /// * `LongFirst` - the short first period is joined with the next one.
+    /// * `ShortLast` - a last period shorter than a regular period.
+    /// * `LongLast` - the short last period is joined with the one before.
+    ///
+    /// An explicit first or penultimate coupon date takes precedence.
+    #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+    pub enum StubType {
+        #[default]
+        ShortFirst,
+        LongFirst,
+        ShortLast,
+        LongLast,
+    }
+
+    /// A single coupon period. Interest accrues from `accrual_start` to `accrual_end` and is
+    /// paid on the `payment_date`. The reference period is the regular period used by
+    /// *ACT/ACT ICMA* to measure a stub; for a regular period it is the period itself.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct SchedulePeriod {
+        pub accrual_start: NaiveDate,
+        pub accrual_end: NaiveDate,
+        pub payment_date: NaiveDate,
+        pub reference_start: NaiveDate,
+        pub reference_end: NaiveDate,
+        pub is_stub: bool,
+    }
+
+    /// The rules for the coupon dates between the `effective_date` and the `maturity_date`.
+    /// With the `end_of_month` rule a schedule anchored on the last day of a month pays on the
+    /// last day of every month, otherwise on the same day of the month where it exists.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct Schedule {
+        pub effective_date: NaiveDate,
+        pub maturity_date: NaiveDate,
+        pub periodicity: Periodicity,
+        pub first_coupon_date: Option<NaiveDate>,
+        pub penultimate_coupon_date: Option<NaiveDate>,
+        pub stub: StubType,
+        pub end_of_month: bool,
+        pub calendar: Calendar,
+        pub accrual_convention: BusinessDayConvention,
+        pub payment_convention: BusinessDayConvention,
+    }
+
+    fn last_day_of_month(date: NaiveDate) -> NaiveDate {
+        let first = NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap();
+        (first + Months::new(1)).pred_opt().unwrap()
+    }
+
+    /// The date `months` months from `anchor`. Dates are always rolled from the anchor rather
+    /// than the previous date, so a day lost at the end of February is not lost for good.
+    fn roll(anchor: NaiveDate, months: i32, end_of_month: bool) -> NaiveDate {
+        let date = if months >= 0 {
+            anchor + Months::new(months as u32)
+        } else {
+            anchor - Months::new(months.unsigned_abs())
+        };
+        if end_of_month && is_last_day_of_month(anchor) {
+            last_day_of_month(date)
+        } else {
+            date
+        }
+    }
+
+    impl Schedule {
+        /// A schedule with a short first period, no business day adjustments and the end of
+        /// month rule.
+        pub fn new(
+            effective_date: NaiveDate,
+            maturity_date: NaiveDate,
+            periodicity: Periodicity,
+        ) -> Schedule {
+            Schedule {
+                effective_date,
+                maturity_date,
+                periodicity,
+                first_coupon_date: None,
+                penultimate_coupon_date: None,
+                stub: StubType::ShortFirst,
+                end_of_month: true,
+                calendar: Calendar::WeekendsOnly,
+                accrual_convention: BusinessDayConvention::Unadjusted,
+                payment_convention: BusinessDayConvention::Unadjusted,
+            }
+        }
+
+        fn months(&self) -> i32 {
+            (12.0 / self.periodicity.periods_per_year()) as i32
+        }
+
+        fn rolls_backward(&self) -> bool {
+            self.penultimate_coupon_date.is_some()
+                || !matches!(self.stub, StubType::ShortLast | StubType::LongLast)
+        }
+
+        /// The unadjusted dates from the effective date to the maturity date.
+        pub fn dates(&self) -> Vec<NaiveDate> {
+            let months = self.months();
+            let start = self.first_coupon_date.unwrap_or(self.effective_date);
+            let end = self.penultimate_coupon_date.unwrap_or(self.maturity_date);
+            let mut regular = Vec::new();
+            if self.rolls_backward() {
+                let mut k = 0;
+                let mut date = end;
+                while date > start {
+                    regular.push(date);
+                    k += 1;
+                    date = roll(end, -k * months, self.end_of_month);
+                }
+                regular.reverse();
+                let merge = date != start
+                    && self.stub == StubType::LongFirst
+                    && self.first_coupon_date.is_none();
+                if merge && regular.len() > 1 {
+                    regular.remove(0);
+                }
+            } else {
+                let mut k = 1;
+                let mut date = roll(start, months, self.end_of_month);
+                while date < end {
+                    regular.push(date);
+                    k += 1;
+                    date = roll(start, k * months, self.end_of_month);
+                }
+                let merge = date != end && self.stub == StubType::LongLast;
+                if merge && !regular.is_empty() {
+                    regular.pop();
+                }
+                regular.push(end);
+            }
+            let mut dates = vec![self.effective_date];
+            if start > self.effective_date {
+                dates.push(start);
+            }
+            dates.extend(regular);
+            if end < self.maturity_date {
+                dates.push(self.maturity_date);
+            }
+            dates
+        }
+
+        /// The coupon periods, with accrual and payment dates moved to business days of the
+        /// `calendar`. The effective date is not adjusted.
+        pub fn periods(&self) -> Vec<SchedulePeriod> {
+            let months = self.months();
+            let front_stub = self.rolls_backward() || self.first_coupon_date.is_some();
+            self.dates()
+                .windows(2)
+                .enumerate()
+                .map(|(i, period)| {
+                    let (start, end) = (period[0], period[1]);
+                    let is_stub = roll(end, -months, self.end_of_month) != start
+                        && roll(start, months, self.end_of_month) != end;
+                    let (reference_start, reference_end) = if !is_stub {
+                        (start, end)
+                    } else if i == 0 && front_stub {
+                        (roll(end, -months, self.end_of_month), end)
+                    } else {
+                        (start, roll(start, months, self.end_of_month))
+                    };
+                    SchedulePeriod {
+                        accrual_start: if i == 0 {
+                            start
+                        } else {
+                            self.calendar.adjust(start, self.accrual_convention)
+                        },
+                        accrual_end: self.calendar.adjust(end, self.accrual_convention),
+                        payment_date: self.calendar.adjust(end, self.payment_convention),
+                        reference_start,
+                        reference_end,
+                        is_stub,
+                    }
+                })
+                .collect()
+        }
+    }
+
+    impl SchedulePeriod {
+        /// The year fraction from `start` to `end` within the period. With *ACT/ACT ICMA*
+        /// the interval is measured in regular periods of the reference grid, so a long stub
+        /// counts its regular part as whole periods and the rest pro rata.
+        pub fn year_fraction(
+            &self,
+            start: NaiveDate,
+            end: NaiveDate,
+            day_count: DayCountConvention,
+            periodicity: Periodicity,
+        ) -> f64 {
+            match day_count {
+                DayCountConvention::ActActIcma => {
+                    let periods_per_year = periodicity.periods_per_year();
+                    let months = (12.0 / periods_per_year) as i32;
+                    // A reference period between month ends belongs to an end of month schedule.
+                    let end_of_month = is_last_day_of_month(self.reference_start)
+                        && is_last_day_of_month(self.reference_end);
+                    let grid = |j: i32| roll(self.reference_start, j * months, end_of_month);
+                    let mut j = 0;
+                    while grid(j) > start {
+                        j -= 1;
+                    }
+                    let mut total = 0.0;
+                    while grid(j) < end {
+                        let (period_start, period_end) = (grid(j), grid(j + 1));
+                        let overlap = (end.min(period_end) - start.max(period_start)).num_days();
+                        if overlap > 0 {
+                            total += overlap as f64
+                                / (periods_per_year
+                                    * (period_end - period_start).num_days() as f64);
+                        }
+                        j += 1;
+                    }
+                    total
+                }
+                _ => day_count.year_fraction(start, end),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bond::bond::Periodicity;
+    use crate::calendar::calendar::{BusinessDayConvention, Calendar};
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::schedule::schedule::*;
+    use assert_approx_eq::assert_approx_eq;
+    use chrono::NaiveDate;
+
+    fn date(input: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(input, "%m/%d/%Y").unwrap()
+    }
+
+    #[test]
+    fn test_stubs() {
+        let mut schedule = Schedule::new(
+            date("04/15/2014"),
+            date("05/15/2016"),
+            Periodicity::SemiAnnual,
+        );
+        let short_first = schedule.dates();
+        assert_eq!(short_first.len(), 6);
+        assert_eq!(short_first[1], date("05/15/2014"));
+        let periods = schedule.periods();
+        assert!(periods[0].is_stub && !periods[1].is_stub);
+        assert_eq!(periods[0].reference_start, date("11/15/2013"));
+        let stub = periods[0].year_fraction(
+            periods[0].accrual_start,
+            periods[0].accrual_end,
+            DayCountConvention::ActActIcma,
+            Periodicity::SemiAnnual,
+        );
+        assert_approx_eq!(stub, 30.0 / (2.0 * 181.0), 1e-6);
+
+        schedule.stub = StubType::LongFirst;
+        let long_first = schedule.dates();
+        assert_eq!(long_first.len(), 5);
+        assert_eq!(long_first[1], date("11/15/2014"));
+        let period = schedule.periods()[0];
+        let long = period.year_fraction(
+            period.accrual_start,
+            period.accrual_end,
+            DayCountConvention::ActActIcma,
+            Periodicity::SemiAnnual,
+        );
+        assert_approx_eq!(long, 0.5 + 30.0 / (2.0 * 181.0), 1e-6);
+
+        schedule.stub = StubType::ShortLast;
+        let short_last = schedule.dates();
+        assert_eq!(short_last[1], date("10/15/2014"));
+        assert_eq!(short_last[short_last.len() - 2], date("04/15/2016"));
+        schedule.stub = StubType::LongLast;
+        let long_last = schedule.dates();
+        assert_eq!(long_last[long_last.len() - 2], date("10/15/2015"));
+    }
+
+    #[test]
+    fn test_explicit_coupon_dates() {
+        let mut schedule = Schedule::new(
+            date("01/10/2020"),
+            date("06/30/2022"),
+            Periodicity::SemiAnnual,
+        );
+        schedule.first_coupon_date = Some(date("06/30/2020"));
+        schedule.penultimate_coupon_date = Some(date("12/31/2021"));
+        let dates = schedule.dates();
+        assert_eq!(
+            dates,
+            vec![
+                date("01/10/2020"),
+                date("06/30/2020"),
+                date("12/31/2020"),
+                date("06/30/2021"),
+                date("12/31/2021"),
+                date("06/30/2022"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_end_of_month_and_adjustment() {
+        let mut schedule = Schedule::new(
+            date("02/28/2023"),
+            date("02/29/2024"),
+            Periodicity::Quarterly,
+        );
+        assert_eq!(schedule.dates()[1], date("05/31/2023"));
+        schedule.end_of_month = false;
+        assert_eq!(schedule.dates()[1], date("05/29/2023"));
+        let mut schedule = Schedule::new(
+            date("03/31/2023"),
+            date("03/31/2024"),
+            Periodicity::Quarterly,
+        );
+        schedule.calendar = Calendar::Sifma;
+        schedule.payment_convention = BusinessDayConvention::ModifiedFollowing;
+        let periods = schedule.periods();
+        // 30 September 2023 is a Saturday, paid on the Friday before.
+        assert_eq!(periods[1].accrual_end, date("09/30/2023"));
+        assert_eq!(periods[1].payment_date, date("09/29/2023"));
+        assert_eq!(periods[2].accrual_start, date("09/30/2023"));
+    }
+}
@@ -0,0 +1,307 @@
+//! Root finding used by the yield computations. Yields are the rates at which the
+//! discounted cashflows equal a price, which has no closed form for a coupon paying
+//! instrument.
+pub mod solver {
+
+    /// Newton's method converges quadratically near the root but needs a derivative and can
+    /// diverge; Brent's method and bisection need a bracket `[low, high]` where the function
+    /// changes sign but always converge.
+    #[derive(Debug, Clone, Copy)]
+    pub struct SolverConfig {
+        pub tolerance: f64,
+        pub max_iterations: u32,
+    }
+
+    impl Default for SolverConfig {
+        fn default() -> SolverConfig {
+            SolverConfig {
+                tolerance: 1e-10,
+                max_iterations: 100,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum SolverErrorType {
+        NoBracket,
+        NoConvergence,
+        ZeroDerivative,
+        NotFinite,
+    }
+
+    /// `estimate` is the last value reached by the solver.
+    #[derive(Debug, Clone, Copy)]
+    pub struct SolverError {
+        pub message: &'static str,
+        pub message_code: SolverErrorType,
+        pub iterations: u32,
+        pub estimate: f64,
+    }
+
+    fn no_bracket(low: f64) -> SolverError {
+        SolverError {
+            message: "The function does not change sign between the endpoints.",
+            message_code: SolverErrorType::NoBracket,
+            iterations: 0,
+            estimate: low,
+        }
+    }
+
+    fn no_convergence(iterations: u32, estimate: f64) -> SolverError {
+        SolverError {
+            message: "Failed to converge within the maximum iterations.",
+            message_code: SolverErrorType::NoConvergence,
+            iterations,
+            estimate,
+        }
+    }
+
+    fn not_finite(estimate: f64) -> SolverError {
+        SolverError {
+            message: "The function is not finite at the endpoints.",
+            message_code: SolverErrorType::NotFinite,
+            iterations: 0,
+            estimate,
+        }
+    }
+
+    /// The tolerance is never below the resolution of an `f64` at `x`.
+    fn tolerance(config: &SolverConfig, x: f64) -> f64 {
+        config.tolerance.max(f64::EPSILON * x.abs())
+    }
+
+    /// Bisection halves the bracket `[low, high]` until it is narrower than the tolerance.
+    pub fn bisection<F: Fn(f64) -> f64>(
+        f: F,
+        mut low: f64,
+        mut high: f64,
+        config: &SolverConfig,
+    ) -> Result<f64, SolverError> {
+        let (f_low, f_high) = (f(low), f(high));
+        if !f_low.is_finite() || !f_high.is_finite() {
+            return Err(not_finite(low));
+        }
+        if f_low == 0.0 {
+            return Ok(low);
+        }
+        if f_low * f_high > 0.0 {
+            return Err(no_bracket(low));
+        }
+        let mut current = low;
+        for _ in 0..config.max_iterations {
+            current = (low + high) / 2.0;
+            let x = f(current);
+            if x == 0.0 || (high - low) / 2.0 <= tolerance(config, current) {
+                return Ok(current);
+            }
+            if x.signum() == f_low.signum() {
+                low = current;
+            } else {
+                high = current;
+            }
+        }
+        Err(no_convergence(config.max_iterations, current))
+    }
+
+    /// Brent's method combines bisection, the secant method and inverse quadratic
+    /// interpolation. See *Numerical Recipes*, section 9.3.
+    pub fn brent<F: Fn(f64) -> f64>(
+        f: F,
+        low: f64,
+        high: f64,
+        config: &SolverConfig,
+    ) -> Result<f64, SolverError> {
+        let (mut a, mut b) = (low, high);
+        let (mut fa, mut fb) = (f(a), f(b));
+        if !fa.is_finite() || !fb.is_finite() {
+            return Err(not_finite(low));
+        }
+        if fa == 0.0 {
+            return Ok(a);
+        }
+        if fb == 0.0 {
+            return Ok(b);
+        }
+        if fa * fb > 0.0 {
+            return Err(no_bracket(low));
+        }
+        let (mut c, mut fc) = (b, fb);
+        let mut d = b - a;
+        let mut e = d;
+        for _ in 0..config.max_iterations {
+            if fb.signum() == fc.signum() {
+                c = a;
+                fc = fa;
+                d = b - a;
+                e = d;
+            }
+            if fc.abs() < fb.abs() {
+                a = b;
+                b = c;
+                c = a;
+                fa = fb;
+                fb = fc;
+                fc = fa;
+            }
+            let tol = 2.0 * f64::EPSILON * b.abs() + 0.5 * config.tolerance;
+            let xm = 0.5 * (c - b);
+            if xm.abs() <= tol || fb == 0.0 {
+                return Ok(b);
+            }
+            if e.abs() >= tol && fa.abs() > fb.abs() {
+                let s = fb / fa;
+                let (mut p, mut q) = if a == c {
+                    (2.0 * xm * s, 1.0 - s)
+                } else {
+                    let q = fa / fc;
+                    let r = fb / fc;
+                    (
+                        s * (2.0 * xm * q * (q - r) - (b - a) * (r - 1.0)),
+                        (q - 1.0) * (r - 1.0) * (s - 1.0),
+                    )
+                };
+                if p > 0.0 {
+                    q = -q;
+                }
+                p = p.abs();
+                let min1 = 3.0 * xm * q - (tol * q).abs();
+                let min2 = (e * q).abs();
+                if 2.0 * p < min1.min(min2) {
+                    e = d;
+                    d = p / q;
+                } else {
+                    d = xm;
+                    e = d;
+                }
+            } else {
+                d = xm;
+                e = d;
+            }
+            a = b;
+            fa = fb;
+            b += if d.abs() > tol { d } else { tol.copysign(xm) };
+            fb = f(b);
+        }
+        Err(no_convergence(config.max_iterations, b))
+    }
+
+    /// Newton's method starting at `guess` using the analytic derivative `df`.
+    pub fn newton<F: Fn(f64) -> f64, D: Fn(f64) -> f64>(
+        f: F,
+        df: D,
+        guess: f64,
+        config: &SolverConfig,
+    ) -> Result<f64, SolverError> {
+        let mut x = guess;
+        for iter in 0..config.max_iterations {
+            let slope = df(x);
+            if slope == 0.0 || !slope.is_finite() {
+                return Err(SolverError {
+                    message: "The derivative vanished.",
+                    message_code: SolverErrorType::ZeroDerivative,
+                    iterations: iter,
+                    estimate: x,
+                });
+            }
+            let dx = f(x) / slope;
+            x -= dx;
+            if !x.is_finite() {
+                return Err(no_convergence(iter, x));
+            }
+            if dx.abs() <= tolerance(config, x) {
+                return Ok(x);
+            }
+        }
+        Err(no_convergence(config.max_iterations, x))
+    }
+
+    /// Newton's method from `guess`, falling back to Brent's method and then bisection on
+    /// `[low, high]` when Newton fails or leaves the bracket.
+    pub fn solve<F: Fn(f64) -> f64, D: Fn(f64) -> f64>(
+        f: F,
+        df: D,
+        guess: f64,
+        low: f64,
+        high: f64,
+        config: &SolverConfig,
+    ) -> Result<f64, SolverError> {
+        match newton(&f, df, guess, config) {
+            Ok(root) if low <= root && root <= high => Ok(root),
+            _ => match brent(&f, low, high, config) {
+                Err(err) if err.message_code == SolverErrorType::NoConvergence => {
+                    bisection(&f, low, high, config)
+                }
+                result => result,
+            },
+        }
+    }
+
+    /// Solves the linear system `a x = b` by Gaussian elimination with partial pivoting,
+    /// for the steps of the multidimensional Newton and Levenberg-Marquardt methods. `None`
+    /// if the system is singular.
+    pub fn solve_linear(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+        let n = b.len();
+        for col in 0..n {
+            let pivot = (col..n).max_by(|i, j| a[*i][col].abs().total_cmp(&a[*j][col].abs()))?;
+            if a[pivot][col] == 0.0 {
+                return None;
+            }
+            a.swap(col, pivot);
+            b.swap(col, pivot);
+            for row in col + 1..n {
+                let factor = a[row][col] / a[col][col];
+                let pivot_row = a[col].clone();
+                for (x, p) in a[row].iter_mut().zip(pivot_row).skip(col) {
+                    *x -= factor * p;
+                }
+                b[row] -= factor * b[col];
+            }
+        }
+        let mut x = vec![0.0; n];
+        for row in (0..n).rev() {
+            let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+            x[row] = (b[row] - sum) / a[row][row];
+        }
+        Some(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solver::solver::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn cubic(x: f64) -> f64 {
+        x * x * x - 2.0 * x - 5.0
+    }
+
+    #[test]
+    fn test_solvers() {
+        let config = SolverConfig::default();
+        let root = 2.0945516;
+        assert_approx_eq!(bisection(cubic, 2.0, 3.0, &config).unwrap(), root, 1e-6);
+        assert_approx_eq!(brent(cubic, 2.0, 3.0, &config).unwrap(), root, 1e-6);
+        let newton_root = newton(cubic, |x| 3.0 * x * x - 2.0, 2.0, &config).unwrap();
+        assert_approx_eq!(newton_root, root, 1e-6);
+    }
+
+    #[test]
+    fn test_solver_errors() {
+        let config = SolverConfig::default();
+        let no_bracket = brent(cubic, 3.0, 4.0, &config).unwrap_err();
+        assert_eq!(no_bracket.message_code, SolverErrorType::NoBracket);
+        let limited = SolverConfig {
+            tolerance: 1e-7,
+            max_iterations: 2,
+        };
+        let no_convergence = bisection(cubic, 2.0, 3.0, &limited).unwrap_err();
+        assert_eq!(no_convergence.message_code, SolverErrorType::NoConvergence);
+        let flat = newton(cubic, |_| 0.0, 2.0, &config).unwrap_err();
+        assert_eq!(flat.message_code, SolverErrorType::ZeroDerivative);
+        let undefined = brent(|x| x.ln(), -1.0, 2.0, &config).unwrap_err();
+        assert_eq!(undefined.message_code, SolverErrorType::NotFinite);
+        // Without a usable derivative the bracket recovers the root.
+        let root = solve(cubic, |_| 0.0, 2.0, 2.0, 3.0, &config).unwrap();
+        assert_approx_eq!(root, 2.0945516, 1e-6);
+    }
+}
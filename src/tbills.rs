@@ -1,6 +1,8 @@
 mod tbills {
 
-    use chrono::NaiveDate;
+    use crate::day_count::day_count::DayCountConvention;
+    use crate::solver::solver::{solve, SolverConfig, SolverError};
+    use chrono::{Days, Months, NaiveDate};
 
     #[derive(Clone, Copy)]
     pub enum TimeIntervalType {
@@ -14,23 +16,45 @@ mod tbills {
     /// * 13 weeks
     /// * 26 weeks
     ///
+    /// The discount accrues over the `time` from the `issue_date` using the `day_count`,
+    /// which is *ACT/360* for US Treasury bills.
     pub struct TBills {
         pub issue_date: NaiveDate,
-        pub face_value: f32,
+        pub face_value: f64,
         pub time_interval_type: TimeIntervalType,
-        pub discount_rate: f32,
-        pub time: f32,
+        pub discount_rate: f64,
+        pub time: f64,
         pub maturity_date: NaiveDate,
+        pub day_count: DayCountConvention,
     }
 
     impl TBills {
-        pub fn normalize_days(&self) -> f32 {
+        /// The number of days in `time` as counted by the `day_count`. Months are calendar
+        /// months from the `issue_date`.
+        pub fn normalize_days(&self) -> f64 {
             match self.time_interval_type {
                 TimeIntervalType::Weeks => self.time * 7.0,
                 TimeIntervalType::Days => self.time,
-                TimeIntervalType::Months => self.time * 30.0,
+                TimeIntervalType::Months => self
+                    .day_count
+                    .day_count(self.issue_date, self.accrual_end_date())
+                    as f64,
+            }
+        }
+
+        /// The date on which the discount stops accruing. A fractional month is
+        /// pro-rated over the days of the month it falls in.
+        pub fn accrual_end_date(&self) -> NaiveDate {
+            match self.time_interval_type {
+                TimeIntervalType::Months => {
+                    let start = self.issue_date + Months::new(self.time.trunc() as u32);
+                    let days_in_month = (start + Months::new(1) - start).num_days() as f64;
+                    start + Days::new((self.time.fract() * days_in_month).round() as u64)
+                }
+                _ => self.issue_date + Days::new(self.normalize_days().round() as u64),
             }
         }
+
         pub fn is_time_valid(&self) -> bool {
             if self.maturity_date > self.issue_date {
                 true
@@ -39,32 +63,46 @@ mod tbills {
                 false
             }
         }
-        pub fn valuation(&self) -> Option<f32> {
+        pub fn valuation(&self) -> Option<f64> {
             if self.is_time_valid() {
-                Some(match self.time_interval_type {
-                    TimeIntervalType::Weeks => {
-                        self.face_value
-                            * (1.0 - (self.time * 7.0) * (self.discount_rate / (100.0 * 360.0)))
-                    }
-                    TimeIntervalType::Days => {
-                        self.face_value
-                            * (1.0 - (self.time) * (self.discount_rate / (100.0 * 360.0)))
-                    }
-                    TimeIntervalType::Months => {
-                        self.face_value
-                            * (1.0 - (self.time * 30.0) * (self.discount_rate / (100.0 * 360.0)))
-                    }
-                })
+                let year_fraction = self
+                    .day_count
+                    .year_fraction(self.issue_date, self.accrual_end_date());
+                Some(self.face_value * (1.0 - year_fraction * (self.discount_rate / 100.0)))
             } else {
                 None
             }
         }
+
+        /// The bond equivalent (coupon equivalent) yield for a `price`, which makes a bill
+        /// comparable with coupon securities. Bills of up to half a year earn simple interest
+        /// over a 365 day year. Longer bills assume a coupon is paid and reinvested after half
+        /// a year, which gives the quadratic
+        ///
+        ///     face_value / price = (1 + y / 2) * (1 + y * (days - 182.5) / 365)
+        pub fn bond_equivalent_yield(&self, price: f64) -> Result<f64, SolverError> {
+            let days = (self.accrual_end_date() - self.issue_date).num_days() as f64;
+            let ratio = self.face_value / price;
+            if days <= 182.0 {
+                return Ok((ratio - 1.0) * 365.0 / days);
+            }
+            let stub = (days - 182.5) / 365.0;
+            solve(
+                |y| (1.0 + y / 2.0) * (1.0 + y * stub) - ratio,
+                |y| 0.5 * (1.0 + y * stub) + stub * (1.0 + y / 2.0),
+                (ratio - 1.0) * 365.0 / days,
+                0.0,
+                1.0,
+                &SolverConfig::default(),
+            )
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::day_count::day_count::DayCountConvention;
     use assert_approx_eq::assert_approx_eq;
     use chrono::NaiveDate;
     use tbills::TBills;
@@ -83,6 +121,7 @@ mod tests {
             discount_rate: 0.145,
             time: 26.0,
             maturity_date: m,
+            day_count: DayCountConvention::Act360,
         };
         assert_approx_eq!(999.27, v.valuation().unwrap(), 0.01);
     }
@@ -99,6 +138,7 @@ mod tests {
             discount_rate: 0.145,
             time: 26.0 * 7.0,
             maturity_date: m,
+            day_count: DayCountConvention::Act360,
         };
         assert_approx_eq!(999.27, v.valuation().unwrap(), 0.01);
     }
@@ -115,8 +155,27 @@ mod tests {
             discount_rate: 0.145,
             time: 26.0 * 7.0 / 30.0,
             maturity_date: m,
+            day_count: DayCountConvention::Act360,
         };
         assert_eq!(true, v.is_time_valid());
         assert_approx_eq!(999.27, v.valuation().unwrap(), 0.01);
     }
+
+    #[test]
+    fn test_bond_equivalent_yield() {
+        let i: NaiveDate = NaiveDate::parse_from_str("01/13/2025", "%m/%d/%Y").unwrap();
+        let m: NaiveDate = NaiveDate::parse_from_str("01/12/2026", "%m/%d/%Y").unwrap();
+        let v: TBills = TBills {
+            issue_date: i,
+            face_value: 100.0,
+            time_interval_type: TimeIntervalType::Weeks,
+            discount_rate: 4.0,
+            time: 52.0,
+            maturity_date: m,
+            day_count: DayCountConvention::Act360,
+        };
+        let price = v.valuation().unwrap();
+        assert_approx_eq!(price, 95.95556, 1e-4);
+        assert_approx_eq!(v.bond_equivalent_yield(price).unwrap(), 0.041829, 1e-5);
+    }
 }